@@ -0,0 +1,411 @@
+use std::collections::{BTreeMap, HashMap};
+use serde::{Serialize, Deserialize};
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use crate::sessions::{is_session_correct, load_all_sessions, SessionRecord};
+use crate::error::ZosError;
+use crate::skills::model::SkillVector;
+use crate::config::skills::SkillDomains;
+
+const SECS_PER_WEEK: i64 = 7 * 86_400;
+
+/// Aggregated progress data for the analytics dashboard
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnalyticsPayload {
+    pub total_sessions: usize,
+    /// Per-skill (timestamp, skill_after) history, oldest first
+    pub skill_histories: HashMap<String, Vec<(i64, f32)>>,
+    /// 7-day trend per skill
+    pub weekly_trends: HashMap<String, f32>,
+    pub sessions_per_skill: HashMap<String, usize>,
+    /// Mean difficulty across all sessions (0.0 when there are none)
+    pub avg_difficulty: f32,
+    /// (week start, fraction of correct sessions) per ISO week with sessions,
+    /// oldest first. Weeks start Monday 00:00 local time.
+    pub overall_accuracy_by_week: Vec<(i64, f32)>,
+    /// How often each Step 1 issue type was found across all sessions.
+    /// Records saved before issue types were stored don't contribute.
+    pub issue_type_counts: HashMap<String, usize>,
+    /// Activity in the 7 days before the payload was computed
+    pub this_week: WeekActivity,
+}
+
+/// Sessions in a 7-day window and the problems they attempted
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WeekActivity {
+    pub sessions: usize,
+    pub correct: usize,
+    /// Each problem once, in order of first attempt, with the outcome of its
+    /// latest attempt
+    pub problems: Vec<ProblemOutcome>,
+}
+
+/// A problem attempted in the window and how its latest attempt went
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProblemOutcome {
+    pub problem_id: String,
+    pub skill: String,
+    /// "correct", "incorrect" or "revealed"
+    pub outcome: String,
+}
+
+/// Headline numbers for the dashboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub total_sessions: usize,
+    /// Consecutive local calendar days with at least one session
+    pub current_streak_days: u32,
+    /// Fraction of correct sessions per skill
+    pub per_skill_accuracy: HashMap<String, f32>,
+    /// Mean difficulty of sessions in the last 7 days (0.0 when there are none)
+    pub avg_difficulty_this_week: f32,
+}
+
+/// One row of the skills table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillInfo {
+    pub name: String,
+    pub value: f32,
+    /// Human-readable name, e.g. "RL Theory"
+    pub label: String,
+    /// Timestamp of the latest session in this skill, None if never practiced
+    pub last_practiced: Option<i64>,
+    pub session_count: usize,
+    /// 7-day trend (skill units per day), 0.0 without recent sessions
+    pub weekly_trend: f32,
+}
+
+/// Whether a session was a failed attempt: exactly the sessions
+/// `is_session_correct` rejects
+pub fn is_failed_session(record: &SessionRecord) -> bool {
+    !is_session_correct(record)
+}
+
+/// Build the analytics payload from all stored sessions
+pub async fn compute_analytics() -> Result<AnalyticsPayload, ZosError> {
+    let sessions = load_all_sessions().await?;
+    let weekly_trends = crate::brain::compute_weekly_trends().await
+        .unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Failed to compute weekly trends for analytics");
+            HashMap::new()
+        });
+
+    let mut skill_histories: HashMap<String, Vec<(i64, f32)>> = HashMap::new();
+    let mut sessions_per_skill: HashMap<String, usize> = HashMap::new();
+    let mut difficulty_sum = 0.0_f32;
+
+    // Sessions are already sorted by timestamp
+    for s in &sessions {
+        skill_histories.entry(s.skill.clone())
+            .or_default()
+            .push((s.timestamp, s.skill_after));
+        *sessions_per_skill.entry(s.skill.clone()).or_insert(0) += 1;
+        difficulty_sum += s.difficulty;
+    }
+
+    let avg_difficulty = if sessions.is_empty() {
+        0.0
+    } else {
+        difficulty_sum / sessions.len() as f32
+    };
+
+    Ok(AnalyticsPayload {
+        total_sessions: sessions.len(),
+        skill_histories,
+        weekly_trends,
+        sessions_per_skill,
+        avg_difficulty,
+        overall_accuracy_by_week: accuracy_by_week(&sessions, &Local),
+        issue_type_counts: issue_type_counts(&sessions),
+        this_week: week_activity(&sessions, Utc::now().timestamp()),
+    })
+}
+
+/// Sessions in the 7 days before `now`
+pub fn week_activity(sessions: &[SessionRecord], now: i64) -> WeekActivity {
+    let mut week: Vec<&SessionRecord> = sessions.iter()
+        .filter(|s| s.timestamp > now - SECS_PER_WEEK && s.timestamp <= now)
+        .collect();
+    week.sort_by_key(|s| s.timestamp);
+
+    let mut problems: Vec<ProblemOutcome> = Vec::new();
+    for s in &week {
+        let outcome = if s.revealed {
+            "revealed"
+        } else if is_session_correct(s) {
+            "correct"
+        } else {
+            "incorrect"
+        };
+        match problems.iter_mut().find(|p| p.problem_id == s.problem_id) {
+            Some(p) => p.outcome = outcome.to_string(),
+            None => problems.push(ProblemOutcome {
+                problem_id: s.problem_id.clone(),
+                skill: s.skill.clone(),
+                outcome: outcome.to_string(),
+            }),
+        }
+    }
+
+    WeekActivity {
+        sessions: week.len(),
+        correct: week.iter().filter(|s| is_session_correct(s)).count(),
+        problems,
+    }
+}
+
+/// Fraction of correct sessions per ISO week (in `tz`), keyed by the
+/// timestamp of the week's Monday midnight, oldest first
+pub fn accuracy_by_week<Tz: TimeZone>(sessions: &[SessionRecord], tz: &Tz) -> Vec<(i64, f32)> {
+    let mut weeks: BTreeMap<i64, (usize, usize)> = BTreeMap::new();
+    for s in sessions {
+        let Some(dt) = tz.timestamp_opt(s.timestamp, 0).single() else { continue };
+        let day = dt.date_naive();
+        let monday = day - Duration::days(day.weekday().num_days_from_monday() as i64);
+        let Some(week_start) = tz.from_local_datetime(&monday.and_time(NaiveTime::MIN)).earliest() else { continue };
+        let entry = weeks.entry(week_start.timestamp()).or_insert((0, 0));
+        entry.1 += 1;
+        if is_session_correct(s) {
+            entry.0 += 1;
+        }
+    }
+    weeks.into_iter()
+        .map(|(week, (correct, total))| (week, correct as f32 / total as f32))
+        .collect()
+}
+
+/// Number of times each issue type appears in `sessions`
+pub fn issue_type_counts(sessions: &[SessionRecord]) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for issue_type in sessions.iter().flat_map(|s| &s.issue_types) {
+        *counts.entry(issue_type.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Timestamp of the most recent session per skill
+pub fn last_practiced_per_skill(sessions: &[SessionRecord]) -> HashMap<String, i64> {
+    let mut last: HashMap<String, i64> = HashMap::new();
+    for s in sessions {
+        let entry = last.entry(s.skill.clone()).or_insert(s.timestamp);
+        *entry = (*entry).max(s.timestamp);
+    }
+    last
+}
+
+/// Display name for a skill id: the known label, otherwise the id in title case
+pub fn skill_label(skill: &str) -> String {
+    let known = match skill {
+        "rl_theory" => Some("RL Theory"),
+        "ml_theory" => Some("ML Theory"),
+        "ai_research" => Some("AI Research"),
+        "coding_debugging" => Some("Coding Debugging"),
+        "algorithms" => Some("Algorithms & Data Structures"),
+        "production_engineering" => Some("Production Engineering"),
+        "analysis_math" => Some("Analysis & Real Math"),
+        "putnam_competition" => Some("Putnam/Competition Math"),
+        "proof_strategy" => Some("Proof Strategy"),
+        "logical_reasoning" => Some("Logical Reasoning"),
+        _ => None,
+    };
+    if let Some(label) = known {
+        return label.to_string();
+    }
+    skill.split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// One row per skill in `skills`, sorted by name
+pub fn skill_details(
+    skills: &SkillVector,
+    sessions: &[SessionRecord],
+    weekly_trends: &HashMap<String, f32>,
+) -> Vec<SkillInfo> {
+    let last_practiced = last_practiced_per_skill(sessions);
+    let mut details: Vec<SkillInfo> = skills.skills.iter()
+        .map(|(name, value)| SkillInfo {
+            name: name.clone(),
+            value: *value,
+            label: skill_label(name),
+            last_practiced: last_practiced.get(name).copied(),
+            session_count: sessions.iter().filter(|s| &s.skill == name).count(),
+            weekly_trend: weekly_trends.get(name).copied().unwrap_or(0.0),
+        })
+        .collect();
+    details.sort_by(|a, b| a.name.cmp(&b.name));
+    details
+}
+
+/// Mean value of each domain's member skills. Members missing from `skills`
+/// are skipped; a domain with none present is left out.
+pub fn domain_scores(skills: &SkillVector, domains: &SkillDomains) -> HashMap<String, f32> {
+    domains.domains.iter()
+        .filter_map(|(domain, members)| {
+            let values: Vec<f32> = members.iter()
+                .filter_map(|skill| skills.skills.get(skill).copied())
+                .collect();
+            if values.is_empty() {
+                return None;
+            }
+            Some((domain.clone(), values.iter().sum::<f32>() / values.len() as f32))
+        })
+        .collect()
+}
+
+/// Skills table for the dashboard
+pub async fn compute_skill_details(skills: &SkillVector) -> Result<Vec<SkillInfo>, ZosError> {
+    let sessions = load_all_sessions().await?;
+    let weekly_trends = crate::brain::compute_weekly_trends().await?;
+    Ok(skill_details(skills, &sessions, &weekly_trends))
+}
+
+/// Most recent failed sessions, newest first
+pub async fn recent_failures(limit: usize) -> Result<Vec<SessionRecord>, ZosError> {
+    let sessions = load_all_sessions().await?;
+    Ok(sessions.into_iter()
+        .rev()
+        .filter(is_failed_session)
+        .take(limit)
+        .collect())
+}
+
+/// Net skill change per skill across all recorded sessions
+/// (last skill_after minus first skill_before)
+pub async fn compute_skill_drift() -> Result<HashMap<String, f32>, ZosError> {
+    let sessions = load_all_sessions().await?;
+    let mut bounds: HashMap<String, (f32, f32)> = HashMap::new();
+
+    for s in &sessions {
+        bounds.entry(s.skill.clone())
+            .and_modify(|(_, last)| *last = s.skill_after)
+            .or_insert((s.skill_before, s.skill_after));
+    }
+
+    Ok(bounds.into_iter()
+        .map(|(skill, (first, last))| (skill, last - first))
+        .collect())
+}
+
+/// Length of the run of consecutive calendar days (in `tz`) with a session,
+/// ending today. A streak that ended yesterday still counts, since today's
+/// session may not have happened yet.
+pub fn current_streak<Tz: TimeZone>(timestamps: &[i64], today: NaiveDate, tz: &Tz) -> u32 {
+    let mut days: Vec<NaiveDate> = timestamps.iter()
+        .filter_map(|ts| tz.timestamp_opt(*ts, 0).single())
+        .map(|dt| dt.date_naive())
+        .collect();
+    days.sort();
+    days.dedup();
+    
+    let mut expected = if days.last() == Some(&today) {
+        today
+    } else {
+        today - Duration::days(1)
+    };
+    let mut streak = 0;
+    for day in days.iter().rev() {
+        if *day == expected {
+            streak += 1;
+            expected -= Duration::days(1);
+        } else if *day < expected {
+            break;
+        }
+    }
+    streak
+}
+
+/// Summarize sessions: totals, streak, per-skill accuracy and this week's difficulty
+pub fn summarize_sessions<Tz: TimeZone>(sessions: &[SessionRecord], now: i64, tz: &Tz) -> SessionSummary {
+    let timestamps: Vec<i64> = sessions.iter().map(|s| s.timestamp).collect();
+    let today = tz.timestamp_opt(now, 0)
+        .single()
+        .map(|dt| dt.date_naive())
+        .unwrap_or_else(|| Utc::now().date_naive());
+    
+    let mut per_skill: HashMap<String, (usize, usize)> = HashMap::new();
+    for s in sessions {
+        let entry = per_skill.entry(s.skill.clone()).or_insert((0, 0));
+        entry.1 += 1;
+        if is_session_correct(s) {
+            entry.0 += 1;
+        }
+    }
+    let per_skill_accuracy = per_skill.into_iter()
+        .map(|(skill, (correct, total))| (skill, correct as f32 / total as f32))
+        .collect();
+    
+    let this_week: Vec<f32> = sessions.iter()
+        .filter(|s| s.timestamp >= now - SECS_PER_WEEK)
+        .map(|s| s.difficulty)
+        .collect();
+    let avg_difficulty_this_week = if this_week.is_empty() {
+        0.0
+    } else {
+        this_week.iter().sum::<f32>() / this_week.len() as f32
+    };
+    
+    SessionSummary {
+        total_sessions: sessions.len(),
+        current_streak_days: current_streak(&timestamps, today, tz),
+        per_skill_accuracy,
+        avg_difficulty_this_week,
+    }
+}
+
+/// Session summary for the dashboard, with days bucketed in local time
+pub async fn compute_session_summary() -> Result<SessionSummary, ZosError> {
+    let sessions = load_all_sessions().await?;
+    Ok(summarize_sessions(&sessions, Utc::now().timestamp(), &Local))
+}
+
+/// Markdown summary of `analytics`' last 7 days: session count, accuracy,
+/// the skills with the steepest weekly trend up and down, and the problems
+/// attempted. The week ends at `now`, shown as a date in `tz`.
+pub fn render_weekly_report<Tz: TimeZone>(analytics: &AnalyticsPayload, now: i64, tz: &Tz) -> String {
+    let end = tz.timestamp_opt(now, 0)
+        .single()
+        .map(|dt| dt.date_naive())
+        .unwrap_or_else(|| Utc::now().date_naive());
+    let mut report = format!("# zOS weekly report\n\nWeek ending {}\n\n", end.format("%Y-%m-%d"));
+
+    let week = &analytics.this_week;
+    if week.sessions == 0 {
+        report.push_str("No sessions this week. One problem is all it takes to get going again!\n");
+        return report;
+    }
+
+    report.push_str(&format!("- Sessions: {}\n", week.sessions));
+    report.push_str(&format!("- Accuracy: {:.0}% ({}/{})\n", 100.0 * week.correct as f32 / week.sessions as f32, week.correct, week.sessions));
+
+    // Sorted so ties go to the first skill by name
+    let trends: BTreeMap<&str, f32> = analytics.weekly_trends.iter()
+        .map(|(skill, trend)| (skill.as_str(), *trend))
+        .collect();
+    let improved = trends.iter().filter(|(_, t)| **t > 0.0).rev().max_by(|a, b| a.1.total_cmp(b.1));
+    let declined = trends.iter().filter(|(_, t)| **t < 0.0).min_by(|a, b| a.1.total_cmp(b.1));
+    if let Some((skill, trend)) = improved {
+        report.push_str(&format!("- Most improved: {} ({:+.3}/day)\n", skill, trend));
+    }
+    if let Some((skill, trend)) = declined {
+        report.push_str(&format!("- Most declined: {} ({:+.3}/day)\n", skill, trend));
+    }
+
+    report.push_str("\n## Problems attempted\n\n");
+    for p in &week.problems {
+        report.push_str(&format!("- {} ({}): {}\n", p.problem_id, p.skill, p.outcome));
+    }
+    report
+}
+
+/// This week's report for the dashboard, with dates in local time
+pub async fn generate_weekly_report() -> Result<String, ZosError> {
+    let analytics = compute_analytics().await?;
+    Ok(render_weekly_report(&analytics, Utc::now().timestamp(), &Local))
+}
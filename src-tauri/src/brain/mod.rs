@@ -1,10 +1,20 @@
 pub mod store;
+pub mod schedule;
+pub mod prerequisites;
 
 use std::collections::HashMap;
 use chrono::{Utc, Duration};
 use serde::{Serialize, Deserialize};
-use crate::skills::store::load_skill_vector;
+use crate::problems::selector::target_difficulty;
+use crate::skills::mastery::mastered_skills;
+use crate::skills::store::{load_focus_skills, load_skill_vector};
 use crate::sessions::load_all_sessions;
+use crate::error::ZosError;
+
+/// Trends below this slope (skill units per day) trigger a review task
+pub const REVIEW_TREND_THRESHOLD: f32 = -0.03;
+
+const SECS_PER_DAY: f32 = 86_400.0;
 
 /// One task directive in a daily plan.
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -15,29 +25,60 @@ pub enum TaskDirective {
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct CurriculumPlan {
+    /// Tasks still to do, next first
     pub tasks: Vec<TaskDirective>,
     pub generated_at: i64,
     pub expires_at: i64,
+    /// Tasks already consumed from this plan
+    #[serde(default)]
+    pub completed: usize,
+}
+
+/// How far through today's plan the user is
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PlanProgress {
+    pub completed: usize,
+    pub total: usize,
+    /// Completed share of the plan, 0-100 (0 for an empty plan)
+    pub percent: f32,
 }
 
 impl CurriculumPlan {
     pub fn is_expired(&self) -> bool {
         Utc::now().timestamp() > self.expires_at
     }
+
+    /// Remove the next task and count it as completed
+    pub fn take_next_task(&mut self) -> Option<TaskDirective> {
+        if self.tasks.is_empty() {
+            return None;
+        }
+        self.completed += 1;
+        Some(self.tasks.remove(0))
+    }
+
+    pub fn progress(&self) -> PlanProgress {
+        let total = self.completed + self.tasks.len();
+        let percent = if total == 0 {
+            0.0
+        } else {
+            self.completed as f32 / total as f32 * 100.0
+        };
+        PlanProgress { completed: self.completed, total, percent }
+    }
 }
 
-/// Compute 7-day skill trend (Δ skill score).
-pub async fn compute_weekly_trends() -> HashMap<String, f32> {
+/// Compute 7-day skill trend (least-squares slope, skill units per day).
+pub async fn compute_weekly_trends() -> Result<HashMap<String, f32>, ZosError> {
     skill_trends(7).await
 }
 
-/// Compute N-day skill trend (Δ skill score).
-async fn skill_trends(days: i64) -> HashMap<String, f32> {
+/// Compute N-day skill trend (least-squares slope, skill units per day).
+async fn skill_trends(days: i64) -> Result<HashMap<String, f32>, ZosError> {
     let cutoff = Utc::now() - Duration::days(days);
     let mut hist: HashMap<String, Vec<(i64, f32)>> = HashMap::new();
     
-    let all_sessions = load_all_sessions().await
-        .unwrap_or_default();
+    let all_sessions = load_all_sessions().await?;
     
     for s in all_sessions.into_iter().filter(|s| s.timestamp > cutoff.timestamp()) {
         hist.entry(s.skill.clone())
@@ -45,47 +86,184 @@ async fn skill_trends(days: i64) -> HashMap<String, f32> {
             .push((s.timestamp, s.skill_after));
     }
     
-    hist.into_iter()
-        .map(|(k, v)| {
-            let trend = if v.len() > 1 {
-                v.last().map(|last| last.1).unwrap_or(0.0) - 
-                v.first().map(|first| first.1).unwrap_or(0.0)
-            } else { 
-                0.0 
-            };
-            (k, trend)
-        })
-        .collect()
+    Ok(hist.into_iter()
+        .map(|(k, v)| (k, trend_slope(&v)))
+        .collect())
+}
+
+/// Least-squares slope of (timestamp, skill) points, in skill units per day.
+/// Returns 0.0 with fewer than two points or when all timestamps coincide.
+pub fn trend_slope(points: &[(i64, f32)]) -> f32 {
+    if points.len() < 2 {
+        return 0.0;
+    }
+    
+    // Measure time in days relative to the first point to keep values small
+    let origin = points[0].0;
+    let xs: Vec<f32> = points.iter()
+        .map(|(t, _)| (t - origin) as f32 / SECS_PER_DAY)
+        .collect();
+    let n = points.len() as f32;
+    let mean_x = xs.iter().sum::<f32>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f32>() / n;
+    
+    let mut covariance = 0.0;
+    let mut variance = 0.0;
+    for (x, (_, y)) in xs.iter().zip(points) {
+        covariance += (x - mean_x) * (y - mean_y);
+        variance += (x - mean_x) * (x - mean_x);
+    }
+    
+    if variance.abs() < f32::EPSILON {
+        0.0
+    } else {
+        covariance / variance
+    }
+}
+
+/// Drills per plan when not overridden
+pub const DEFAULT_NUM_DRILLS: usize = 2;
+/// Days of history the review trend is computed over when not overridden
+pub const DEFAULT_TREND_HORIZON_DAYS: i64 = 7;
+
+/// Parameters for plan generation; the defaults give the standard daily plan
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PlanOptions {
+    /// Number of weakest-skill drills
+    pub num_drills: usize,
+    /// Whether to add trend and spaced-repetition reviews
+    pub include_reviews: bool,
+    /// Days of history the review trend is computed over
+    pub horizon_days: i64,
+    /// Leave mastered skills out of the drills (unless every skill is mastered)
+    pub exclude_mastered: bool,
+}
+
+impl Default for PlanOptions {
+    fn default() -> Self {
+        PlanOptions {
+            num_drills: DEFAULT_NUM_DRILLS,
+            include_reviews: true,
+            horizon_days: DEFAULT_TREND_HORIZON_DAYS,
+            exclude_mastered: true,
+        }
+    }
 }
 
-/// Build the plan: 2 weakest-skill drills + review any negative trend.
+impl PlanOptions {
+    /// Reject drill counts outside 1..=`skill_count` and non-positive horizons
+    pub fn validate(&self, skill_count: usize) -> Result<(), ZosError> {
+        if self.num_drills == 0 || self.num_drills > skill_count {
+            return Err(ZosError::new(
+                format!("num_drills must be between 1 and {} (the number of skills), got {}", skill_count, self.num_drills),
+                "validation"
+            ));
+        }
+        if self.horizon_days < 1 {
+            return Err(ZosError::new(
+                format!("horizon_days must be at least 1, got {}", self.horizon_days),
+                "validation"
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Build the standard plan: 2 weakest non-mastered skill drills + review any
+/// negative 7-day trend + review any skill whose spaced-repetition date has arrived.
 pub async fn generate_daily_plan() -> CurriculumPlan {
-    let skills = load_skill_vector().await;
-    let trends = compute_weekly_trends().await;
+    generate_daily_plan_with(&PlanOptions::default()).await
+}
 
-    // Weakest two skills
-    let mut weakest: Vec<_> = skills.skills.iter().collect();
-    weakest.sort_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal));
+/// Build a plan with the given drill count, reviews and trend horizon
+pub async fn generate_daily_plan_with(options: &PlanOptions) -> CurriculumPlan {
+    // In focus mode only the focus skills are drilled or reviewed
+    let focus = load_focus_skills().await;
+    let skills = load_skill_vector().await.restricted_to(&focus);
 
-    let mut tasks = vec![];
-    for (skill, value) in weakest.iter().take(2) {
-        tasks.push(TaskDirective::Adaptive {
-            skill: (*skill).clone(),
-            difficulty: (0.3_f32).max(1.0 - *value),
+    let mut review_skills: Vec<String> = vec![];
+    if options.include_reviews {
+        // Missing trend data shouldn't block plan generation
+        let trends = skill_trends(options.horizon_days).await.unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Failed to compute skill trends, planning without trend reviews");
+            HashMap::new()
         });
+        review_skills = trend_review_skills(&trends);
+
+        // Spaced repetition: fold in the latest sessions, then review anything due
+        match update_review_schedule().await {
+            Ok(schedule) => {
+                for skill in schedule.due_skills(Utc::now().timestamp()) {
+                    if !review_skills.contains(&skill) {
+                        review_skills.push(skill);
+                    }
+                }
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to update review schedule");
+            }
+        }
     }
 
-    // Any negative 7-day trend → review task
-    for (skill, trend) in trends {
-        if trend < -0.03 {
-            tasks.push(TaskDirective::Review { skill });
+    review_skills.retain(|skill| skills.skills.contains_key(skill));
+
+    let mut drill_pool = skills.skills.clone();
+    if options.exclude_mastered {
+        let sessions = load_all_sessions().await.unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Failed to load sessions, planning without mastery");
+            Vec::new()
+        });
+        let mastered = mastered_skills(&skills.skills, &sessions);
+        if mastered.len() < drill_pool.len() {
+            drill_pool.retain(|skill, _| !mastered.contains(skill));
         }
     }
 
+    build_plan(&drill_pool, options.num_drills, review_skills)
+}
+
+/// Skills whose trend is steep enough to need a review
+fn trend_review_skills(trends: &HashMap<String, f32>) -> Vec<String> {
+    trends.iter()
+        .filter(|(_, trend)| **trend < REVIEW_TREND_THRESHOLD)
+        .map(|(skill, _)| skill.clone())
+        .collect()
+}
+
+/// Assemble a plan from `num_drills` weakest-skill drills (a weak prerequisite
+/// is drilled in place of the skill that depends on it) followed by reviews
+pub fn build_plan(skills: &HashMap<String, f32>, num_drills: usize, review_skills: Vec<String>) -> CurriculumPlan {
+    let drills = prerequisites::select_drill_skills(
+        skills,
+        num_drills,
+        &prerequisites::prerequisite_graph(),
+        prerequisites::PREREQUISITE_THRESHOLD,
+    );
+
+    let mut tasks = vec![];
+    for (skill, value) in drills {
+        tasks.push(TaskDirective::Adaptive {
+            skill,
+            difficulty: target_difficulty(value),
+        });
+    }
+    for skill in review_skills {
+        tasks.push(TaskDirective::Review { skill });
+    }
+
     CurriculumPlan {
         tasks,
         generated_at: Utc::now().timestamp(),
         expires_at: (Utc::now() + Duration::hours(24)).timestamp(),
+        completed: 0,
     }
 }
 
+/// Load the review schedule, apply the latest session per skill and persist it
+async fn update_review_schedule() -> Result<schedule::ReviewSchedule, ZosError> {
+    let mut schedule = store::load_schedule().await?;
+    let sessions = load_all_sessions().await?;
+    schedule.update_from_sessions(&sessions);
+    store::save_schedule(&schedule).await?;
+    Ok(schedule)
+}
@@ -0,0 +1,89 @@
+use std::collections::{HashMap, HashSet};
+
+/// A prerequisite below this value is drilled before the skills that depend on it
+pub const PREREQUISITE_THRESHOLD: f32 = 0.4;
+
+/// Static skill dependency graph: skill -> skills it builds on
+pub fn prerequisite_graph() -> HashMap<String, Vec<String>> {
+    let edges: &[(&str, &[&str])] = &[
+        ("proof_strategy", &["logical_reasoning"]),
+        ("analysis_math", &["proof_strategy"]),
+        ("putnam_competition", &["analysis_math", "proof_strategy"]),
+        ("algorithms", &["logical_reasoning"]),
+        ("ml_theory", &["analysis_math"]),
+        ("rl_theory", &["ml_theory"]),
+        ("ai_research", &["ml_theory"]),
+        ("production_engineering", &["coding_debugging"]),
+    ];
+    edges.iter()
+        .map(|(skill, prereqs)| {
+            (skill.to_string(), prereqs.iter().map(|p| p.to_string()).collect())
+        })
+        .collect()
+}
+
+/// The weakest prerequisite of `skill` (direct or transitive) that is below
+/// `threshold`, if any. Ties go to the alphabetically first skill.
+pub fn weakest_unmet_prerequisite(
+    skill: &str,
+    skills: &HashMap<String, f32>,
+    graph: &HashMap<String, Vec<String>>,
+    threshold: f32,
+) -> Option<String> {
+    let mut seen: HashSet<&str> = HashSet::from([skill]);
+    let mut stack: Vec<&str> = vec![skill];
+    let mut weakest: Option<(&str, f32)> = None;
+    
+    while let Some(current) = stack.pop() {
+        for prereq in graph.get(current).into_iter().flatten() {
+            if !seen.insert(prereq.as_str()) {
+                continue;
+            }
+            stack.push(prereq.as_str());
+            let Some(&value) = skills.get(prereq) else { continue };
+            if value >= threshold {
+                continue;
+            }
+            let better = match weakest {
+                None => true,
+                Some((name, best)) => value < best || (value == best && prereq.as_str() < name),
+            };
+            if better {
+                weakest = Some((prereq.as_str(), value));
+            }
+        }
+    }
+    
+    weakest.map(|(name, _)| name.to_string())
+}
+
+/// Pick up to `count` skills to drill, weakest first, replacing any skill with
+/// its weakest unmet prerequisite. Each skill is drilled at most once.
+pub fn select_drill_skills(
+    skills: &HashMap<String, f32>,
+    count: usize,
+    graph: &HashMap<String, Vec<String>>,
+    threshold: f32,
+) -> Vec<(String, f32)> {
+    let mut weakest: Vec<(&String, &f32)> = skills.iter().collect();
+    weakest.sort_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal));
+    
+    let mut selected: Vec<(String, f32)> = Vec::new();
+    for (skill, value) in weakest {
+        if selected.len() >= count {
+            break;
+        }
+        let (drill, drill_value) = match weakest_unmet_prerequisite(skill, skills, graph, threshold) {
+            Some(prereq) => {
+                tracing::info!(skill = %skill, prerequisite = %prereq, "Drilling weak prerequisite first");
+                let prereq_value = skills[&prereq];
+                (prereq, prereq_value)
+            }
+            None => (skill.clone(), *value),
+        };
+        if !selected.iter().any(|(s, _)| *s == drill) {
+            selected.push((drill, drill_value));
+        }
+    }
+    selected
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use crate::sessions::SessionRecord;
+use crate::analytics::is_failed_session;
+
+const SECS_PER_DAY: i64 = 86_400;
+const DEFAULT_EASE: f32 = 2.5;
+const MIN_EASE: f32 = 1.3;
+
+/// SM-2 style review state for a single skill
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReviewItem {
+    /// Current review interval in days
+    pub interval_days: f32,
+    /// Multiplier applied to the interval after a successful review
+    pub ease: f32,
+    /// Consecutive successful reviews
+    pub repetitions: u32,
+    /// Unix timestamp when the skill is next due for review
+    pub next_review: i64,
+    /// Last session already folded into this item (avoids double counting)
+    #[serde(default)]
+    pub last_session_id: Option<String>,
+}
+
+impl ReviewItem {
+    fn new(now: i64) -> Self {
+        ReviewItem {
+            interval_days: 1.0,
+            ease: DEFAULT_EASE,
+            repetitions: 0,
+            next_review: now,
+            last_session_id: None,
+        }
+    }
+
+    /// Grow the interval on success, reset it on failure
+    pub fn record_outcome(&mut self, success: bool, at: i64) {
+        if success {
+            self.interval_days = match self.repetitions {
+                0 => 1.0,
+                1 => 6.0,
+                _ => self.interval_days * self.ease,
+            };
+            self.repetitions += 1;
+            self.ease += 0.1;
+        } else {
+            self.repetitions = 0;
+            self.interval_days = 1.0;
+            self.ease = (self.ease - 0.2).max(MIN_EASE);
+        }
+        self.next_review = at + (self.interval_days * SECS_PER_DAY as f32) as i64;
+    }
+}
+
+/// Per-skill spaced-repetition schedule, persisted next to the daily plan
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ReviewSchedule {
+    pub items: HashMap<String, ReviewItem>,
+}
+
+impl ReviewSchedule {
+    /// Record a review outcome for a skill
+    pub fn record_outcome(&mut self, skill: &str, success: bool, at: i64) {
+        self.items.entry(skill.to_string())
+            .or_insert_with(|| ReviewItem::new(at))
+            .record_outcome(success, at);
+    }
+
+    /// Fold the most recent session per skill into the schedule.
+    /// Sessions that were already applied are skipped.
+    pub fn update_from_sessions(&mut self, sessions: &[SessionRecord]) {
+        let mut latest: HashMap<&str, &SessionRecord> = HashMap::new();
+        for s in sessions {
+            match latest.get(s.skill.as_str()) {
+                Some(existing) if existing.timestamp >= s.timestamp => {}
+                _ => {
+                    latest.insert(s.skill.as_str(), s);
+                }
+            }
+        }
+
+        for (skill, record) in latest {
+            let already_applied = self.items.get(skill)
+                .and_then(|item| item.last_session_id.as_deref())
+                == Some(record.session_id.as_str());
+            if already_applied {
+                continue;
+            }
+            self.record_outcome(skill, !is_failed_session(record), record.timestamp);
+            if let Some(item) = self.items.get_mut(skill) {
+                item.last_session_id = Some(record.session_id.clone());
+            }
+        }
+    }
+
+    /// Remove `forgotten`'s contribution to its skill by replaying that
+    /// skill's `remaining` sessions, oldest first, into a fresh item (or
+    /// dropping the item if none remain). Returns whether the schedule changed.
+    pub fn forget_session(&mut self, forgotten: &SessionRecord, remaining: &[SessionRecord]) -> bool {
+        if !self.items.contains_key(&forgotten.skill) {
+            return false;
+        }
+        let mut history: Vec<&SessionRecord> = remaining.iter()
+            .filter(|s| s.skill == forgotten.skill && s.session_id != forgotten.session_id)
+            .collect();
+        history.sort_by_key(|s| s.timestamp);
+        
+        self.items.remove(&forgotten.skill);
+        for record in history {
+            self.record_outcome(&record.skill, !is_failed_session(record), record.timestamp);
+            if let Some(item) = self.items.get_mut(&record.skill) {
+                item.last_session_id = Some(record.session_id.clone());
+            }
+        }
+        true
+    }
+
+    /// Skills whose next review date has arrived, most overdue first
+    pub fn due_skills(&self, now: i64) -> Vec<String> {
+        let mut due: Vec<(&String, i64)> = self.items.iter()
+            .filter(|(_, item)| item.next_review <= now)
+            .map(|(skill, item)| (skill, item.next_review))
+            .collect();
+        due.sort_by_key(|(_, next)| *next);
+        due.into_iter().map(|(skill, _)| skill.clone()).collect()
+    }
+}
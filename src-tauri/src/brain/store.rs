@@ -1,48 +1,18 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use crate::brain::CurriculumPlan;
+use crate::brain::schedule::ReviewSchedule;
 use crate::error::ZosError;
 
 pub fn get_plan_path() -> PathBuf {
-    // Use platform-specific app data directory
-    #[cfg(target_os = "macos")]
-    {
-        if let Some(home) = std::env::var_os("HOME") {
-            let mut dir = PathBuf::from(home);
-            dir.push("Library/Application Support/com.zacnwo.zos");
-            dir.push("data");
-            dir.push("daily_plan.json");
-            return dir;
-        }
-    }
-    
-    #[cfg(target_os = "windows")]
-    {
-        if let Some(appdata) = std::env::var_os("APPDATA") {
-            let mut dir = PathBuf::from(appdata);
-            dir.push("com.zacnwo.zos");
-            dir.push("data");
-            dir.push("daily_plan.json");
-            return dir;
-        }
-    }
-    
-    #[cfg(target_os = "linux")]
-    {
-        if let Some(home) = std::env::var_os("HOME") {
-            let mut dir = PathBuf::from(home);
-            dir.push(".local/share/com.zacnwo.zos");
-            dir.push("data");
-            dir.push("daily_plan.json");
-            return dir;
-        }
-    }
-    
-    // Fallback
-    PathBuf::from("data/daily_plan.json")
+    crate::paths::data_dir().join("daily_plan.json")
 }
 
-/// Save curriculum plan asynchronously
+/// Save curriculum plan asynchronously. Once its last task has been taken
+/// the finished plan is also added to the history log.
 pub async fn save(plan: &CurriculumPlan) -> Result<(), ZosError> {
+    if plan.tasks.is_empty() && plan.completed > 0 {
+        append_plan_history_in(&get_plan_history_path(), plan).await?;
+    }
     let path = get_plan_path();
     if let Some(parent) = path.parent() {
         tokio::fs::create_dir_all(parent)
@@ -69,6 +39,20 @@ pub async fn save(plan: &CurriculumPlan) -> Result<(), ZosError> {
     Ok(())
 }
 
+/// Record a newly generated plan in the history log, then make it the active plan.
+/// An unfinished plan it replaces is logged again first, with how far it got.
+/// Use `save` for updates to the active plan (e.g. consumed tasks).
+pub async fn save_generated(plan: &CurriculumPlan) -> Result<(), ZosError> {
+    let history_path = get_plan_history_path();
+    if let Ok(Some(previous)) = load().await {
+        if !previous.tasks.is_empty() && previous.generated_at != plan.generated_at {
+            append_plan_history_in(&history_path, &previous).await?;
+        }
+    }
+    append_plan_history_in(&history_path, plan).await?;
+    save(plan).await
+}
+
 /// Load curriculum plan asynchronously
 pub async fn load() -> Result<Option<CurriculumPlan>, ZosError> {
     let path = get_plan_path();
@@ -93,6 +77,103 @@ pub async fn load() -> Result<Option<CurriculumPlan>, ZosError> {
     }
 }
 
+/// Every generated plan, one JSON object per line, next to the daily plan.
+/// A plan is logged again when it finishes or is replaced; the later line wins.
+pub fn get_plan_history_path() -> PathBuf {
+    get_plan_path().with_file_name("plan_history.jsonl")
+}
+
+/// Append a plan to the history log at `path`
+pub async fn append_plan_history_in(path: &Path, plan: &CurriculumPlan) -> Result<(), ZosError> {
+    crate::jsonl::append_line(path, plan, "plan history").await
+}
+
+/// The most recent `limit` plans from the history log, newest first
+pub async fn load_plan_history(limit: usize) -> Result<Vec<CurriculumPlan>, ZosError> {
+    load_plan_history_in(&get_plan_history_path(), limit).await
+}
+
+/// Like `load_plan_history`, reading from `path`. Unparseable lines are skipped,
+/// and a plan logged more than once is returned as of its latest line.
+pub async fn load_plan_history_in(path: &Path, limit: usize) -> Result<Vec<CurriculumPlan>, ZosError> {
+    let content = match tokio::fs::read_to_string(path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(ZosError::new(
+                format!("Failed to read plan history: {}", e),
+                "io"
+            ).with_context(format!("path: {:?}", path)));
+        }
+    };
+    
+    let mut seen = std::collections::HashSet::new();
+    let mut plans: Vec<CurriculumPlan> = content.lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<CurriculumPlan>(line).ok())
+        .filter(|plan| seen.insert(plan.generated_at))
+        .collect();
+    plans.sort_by_key(|plan| std::cmp::Reverse(plan.generated_at));
+    plans.truncate(limit);
+    Ok(plans)
+}
+
+/// Review schedule lives next to the daily plan
+pub fn get_schedule_path() -> PathBuf {
+    get_plan_path().with_file_name("review_schedule.json")
+}
+
+/// Save the spaced-repetition review schedule asynchronously
+pub async fn save_schedule(schedule: &ReviewSchedule) -> Result<(), ZosError> {
+    let path = get_schedule_path();
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| ZosError::new(
+                format!("Failed to create directory: {}", e),
+                "io"
+            ).with_context(format!("path: {:?}", parent)))?;
+    }
+    
+    let json = serde_json::to_string_pretty(schedule)
+        .map_err(|e| ZosError::new(
+            format!("Failed to serialize review schedule: {}", e),
+            "json_serialize"
+        ))?;
+    
+    tokio::fs::write(&path, json)
+        .await
+        .map_err(|e| ZosError::new(
+            format!("Failed to write review_schedule.json: {}", e),
+            "io"
+        ).with_context(format!("path: {:?}", path)))?;
+    
+    Ok(())
+}
+
+/// Load the review schedule asynchronously (empty if none saved yet)
+pub async fn load_schedule() -> Result<ReviewSchedule, ZosError> {
+    let path = get_schedule_path();
+    match tokio::fs::read_to_string(&path).await {
+        Ok(content) => {
+            serde_json::from_str(&content)
+                .map_err(|e| ZosError::new(
+                    format!("Failed to parse review_schedule.json: {}", e),
+                    "json_parse"
+                ).with_context(format!("path: {:?}", path)))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            Ok(ReviewSchedule::default())
+        }
+        Err(e) => {
+            Err(ZosError::new(
+                format!("Failed to read review_schedule.json: {}", e),
+                "io"
+            ).with_context(format!("path: {:?}", path)))
+        }
+    }
+}
+
 /// Synchronous version for backward compatibility (deprecated)
 #[deprecated(note = "Use load().await instead")]
 pub fn load_sync() -> Option<CurriculumPlan> {
@@ -1,40 +1,78 @@
 use std::hash::{Hash, Hasher};
 use std::collections::hash_map::DefaultHasher;
+use std::path::{Path, PathBuf};
 use serde::{Serialize, Deserialize};
 use crate::state::app::AppState;
 use crate::error::ZosError;
 
+/// Cached responses older than this are treated as expired (7 days)
+pub const RESPONSE_CACHE_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// How often the persisted response cache is flushed to disk (only when dirty)
+const PERSIST_INTERVAL_SECS: u64 = 30;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CachedResponse {
     pub data: String,
     pub timestamp: i64,
 }
 
-/// Generate a hash key from model name and prompt
-fn cache_key(model: &str, prompt: &str) -> u64 {
+impl CachedResponse {
+    /// Check whether this entry has outlived the cache TTL
+    pub fn is_expired(&self, now: i64) -> bool {
+        now - self.timestamp > RESPONSE_CACHE_TTL_SECS
+    }
+}
+
+/// On-disk form of a cache entry. The hashed key is stored alongside the
+/// response since the model+prompt preimage is not kept in memory.
+#[derive(Serialize, Deserialize)]
+struct PersistedEntry {
+    key: u64,
+    #[serde(flatten)]
+    response: CachedResponse,
+}
+
+/// The part of a prompt that identifies it for caching: the leading
+/// `system_prompt` is dropped (it is constant per task; empty if there is
+/// none) and whitespace runs are collapsed, so re-indenting the same proof
+/// still hits the cache
+pub fn normalize_prompt_for_key(prompt: &str, system_prompt: &str) -> String {
+    let user_part = prompt.strip_prefix(system_prompt).unwrap_or(prompt);
+    user_part.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Generate a hash key from model name and normalized prompt
+fn cache_key(model: &str, system_prompt: &str, prompt: &str) -> u64 {
     let mut hasher = DefaultHasher::new();
     model.hash(&mut hasher);
-    prompt.hash(&mut hasher);
+    normalize_prompt_for_key(prompt, system_prompt).hash(&mut hasher);
     hasher.finish()
 }
 
-/// Check cache and return if found
+/// Check cache and return if found. `prompt` starts with `system_prompt`
+/// (see `normalize_prompt_for_key`).
 pub fn get_cached<T: for<'de> Deserialize<'de>>(
     state: &AppState,
     model: &str,
+    system_prompt: &str,
     prompt: &str,
 ) -> Option<T> {
-    let key = cache_key(model, prompt);
+    let key = cache_key(model, system_prompt, prompt);
     let cache = state.response_cache.read();
     
-    if let Some(cached) = cache.peek(&key) {
+    if let Some(cached) = cache.peek(&key)
+        .filter(|c| !c.is_expired(chrono::Utc::now().timestamp())) {
         tracing::debug!(
             model = model,
             prompt_preview = &prompt[..prompt.len().min(50)],
             "Cache hit"
         );
         match serde_json::from_str::<T>(&cached.data) {
-            Ok(parsed) => return Some(parsed),
+            Ok(parsed) => {
+                state.metrics.record_cache_hit();
+                return Some(parsed);
+            }
             Err(e) => {
                 tracing::warn!(
                     model = model,
@@ -50,17 +88,19 @@ pub fn get_cached<T: for<'de> Deserialize<'de>>(
         prompt_preview = &prompt[..prompt.len().min(50)],
         "Cache miss"
     );
+    state.metrics.record_cache_miss();
     None
 }
 
-/// Store response in cache
+/// Store response in cache, keyed like `get_cached`
 pub fn cache_response<T: Serialize>(
     state: &AppState,
     model: &str,
+    system_prompt: &str,
     prompt: &str,
     response: &T,
 ) -> Result<(), ZosError> {
-    let key = cache_key(model, prompt);
+    let key = cache_key(model, system_prompt, prompt);
     let data = serde_json::to_string(response)
         .map_err(|e| ZosError::new(
             format!("Failed to serialize response for cache: {}", e),
@@ -74,6 +114,112 @@ pub fn cache_response<T: Serialize>(
     
     let mut cache = state.response_cache.write();
     cache.put(key, cached);
+    state.mark_response_cache_dirty();
+    Ok(())
+}
+
+pub fn response_cache_path() -> PathBuf {
+    crate::paths::data_dir().join("response_cache.json")
+}
+
+/// Write the current response cache contents to disk
+pub async fn save_response_cache(state: &AppState) -> Result<(), ZosError> {
+    save_response_cache_in(state, &response_cache_path()).await
+}
+
+/// Write the current response cache contents to `path`
+pub async fn save_response_cache_in(state: &AppState, path: &Path) -> Result<(), ZosError> {
+    // Snapshot entries oldest-first so reloading preserves LRU order
+    let entries: Vec<PersistedEntry> = {
+        let cache = state.response_cache.read();
+        cache.iter()
+            .rev()
+            .map(|(key, response)| PersistedEntry { key: *key, response: response.clone() })
+            .collect()
+    };
+    
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| ZosError::new(
+                format!("Failed to create directory: {}", e),
+                "io"
+            ).with_context(format!("path: {:?}", parent)))?;
+    }
+    
+    let json = serde_json::to_string(&entries)
+        .map_err(|e| ZosError::new(
+            format!("Failed to serialize response cache: {}", e),
+            "json_serialize"
+        ))?;
+    
+    tokio::fs::write(path, json)
+        .await
+        .map_err(|e| ZosError::new(
+            format!("Failed to write response_cache.json: {}", e),
+            "io"
+        ).with_context(format!("path: {:?}", path)))?;
+    
     Ok(())
 }
 
+/// Load persisted responses into the in-memory cache, dropping expired entries.
+/// Returns the number of entries restored.
+pub async fn load_response_cache(state: &AppState) -> Result<usize, ZosError> {
+    load_response_cache_in(state, &response_cache_path()).await
+}
+
+/// Load responses persisted at `path` into the in-memory cache
+pub async fn load_response_cache_in(state: &AppState, path: &Path) -> Result<usize, ZosError> {
+    let content = match tokio::fs::read_to_string(path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => {
+            return Err(ZosError::new(
+                format!("Failed to read response_cache.json: {}", e),
+                "io"
+            ).with_context(format!("path: {:?}", path)));
+        }
+    };
+    
+    let entries: Vec<PersistedEntry> = serde_json::from_str(&content)
+        .map_err(|e| ZosError::new(
+            format!("Failed to parse response_cache.json: {}", e),
+            "json_parse"
+        ).with_context(format!("path: {:?}", path)))?;
+    
+    let now = chrono::Utc::now().timestamp();
+    let total = entries.len();
+    let mut restored = 0;
+    {
+        let mut cache = state.response_cache.write();
+        for entry in entries.into_iter().filter(|e| !e.response.is_expired(now)) {
+            cache.put(entry.key, entry.response);
+            restored += 1;
+        }
+    }
+    
+    tracing::info!(
+        restored = restored,
+        expired = total - restored,
+        "Loaded persisted response cache"
+    );
+    Ok(restored)
+}
+
+/// Periodically flush the response cache to disk whenever it has changed.
+/// Writes are debounced to at most one per `PERSIST_INTERVAL_SECS`.
+pub fn start_response_cache_persistence(state: AppState) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(tokio::time::Duration::from_secs(PERSIST_INTERVAL_SECS)).await;
+            if state.take_response_cache_dirty() {
+                if let Err(e) = save_response_cache(&state).await {
+                    tracing::warn!(error = %e, "Failed to persist response cache");
+                    state.mark_response_cache_dirty();
+                }
+            }
+        }
+    });
+}
+
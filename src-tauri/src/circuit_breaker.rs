@@ -0,0 +1,84 @@
+use parking_lot::Mutex;
+use rand::Rng;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+/// Simple failure-counting circuit breaker.
+/// Opens after `failure_threshold` consecutive failures and stays open for
+/// `reset_timeout`, after which calls are allowed through again (half-open).
+pub struct CircuitBreaker {
+    reset_timeout: Duration,
+    failure_threshold: u32,
+    failures: AtomicU32,
+    opened_at: Mutex<Option<Instant>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(reset_timeout_secs: u64, failure_threshold: u32) -> Self {
+        Self {
+            reset_timeout: Duration::from_secs(reset_timeout_secs),
+            failure_threshold,
+            failures: AtomicU32::new(0),
+            opened_at: Mutex::new(None),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        match *self.opened_at.lock() {
+            Some(opened) => opened.elapsed() < self.reset_timeout,
+            None => false,
+        }
+    }
+
+    pub fn failure_count(&self) -> u32 {
+        self.failures.load(Ordering::Relaxed)
+    }
+
+    pub fn record_failure(&self) {
+        let failures = self.failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures >= self.failure_threshold {
+            *self.opened_at.lock() = Some(Instant::now());
+        }
+    }
+
+    pub fn record_success(&self) {
+        self.failures.store(0, Ordering::Relaxed);
+        *self.opened_at.lock() = None;
+    }
+}
+
+/// Exponential backoff: `base_delay_ms * 2^attempt`, capped at `max_delay_ms`.
+/// With jitter enabled the delay is drawn uniformly from `[0, computed]`
+/// ("full jitter") so concurrent retries don't fire in lockstep.
+#[derive(Debug, Clone, Copy)]
+pub struct ExponentialBackoff {
+    base_delay_ms: u64,
+    max_delay_ms: u64,
+    jitter: bool,
+}
+
+impl ExponentialBackoff {
+    /// Deterministic backoff (no jitter)
+    pub fn new(base_delay_ms: u64, max_delay_ms: u64) -> Self {
+        Self::with_jitter(base_delay_ms, max_delay_ms, false)
+    }
+
+    pub fn with_jitter(base_delay_ms: u64, max_delay_ms: u64, jitter: bool) -> Self {
+        Self { base_delay_ms, max_delay_ms, jitter }
+    }
+
+    /// Upper bound for the delay before the given attempt (0-based)
+    pub fn max_delay_for_attempt(&self, attempt: u32) -> u64 {
+        let factor = 2_u64.checked_pow(attempt).unwrap_or(u64::MAX);
+        self.base_delay_ms.saturating_mul(factor).min(self.max_delay_ms)
+    }
+
+    pub fn delay_for_attempt(&self, attempt: u32) -> u64 {
+        let computed = self.max_delay_for_attempt(attempt);
+        if self.jitter {
+            rand::thread_rng().gen_range(0..=computed)
+        } else {
+            computed
+        }
+    }
+}
@@ -1,13 +1,149 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
+use std::sync::OnceLock;
 use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use rand::{rngs::StdRng, SeedableRng};
+use crate::error::ZosError;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelConfig {
     pub proof_model: String,
     pub problem_model: String,
     pub general_model: String,
+    /// Persist the model response cache to disk so it survives restarts
+    #[serde(default)]
+    pub persist_response_cache: bool,
+    /// Race the primary and fallback models for proof analysis and use
+    /// whichever returns valid JSON first (costs an extra model call)
+    #[serde(default)]
+    pub race_proof_models: bool,
+    /// Longest proof (in characters) sent to the model as-is. Longer input is
+    /// trimmed to its head and tail; input over twice this is rejected.
+    #[serde(default = "default_max_input_chars")]
+    pub max_input_chars: usize,
+    /// Ollama server to talk to, e.g. a GPU box on the LAN.
+    /// Overridden by the `ZOS_OLLAMA_URL` environment variable.
+    #[serde(default = "default_ollama_base_url")]
+    pub ollama_base_url: String,
+    /// Problems attempted within this many days are not recommended again;
+    /// older ones become eligible for review
+    #[serde(default = "default_completed_exclusion_days")]
+    pub completed_exclusion_days: u32,
+    /// Extra attempts per model after a failed call (timeout, connection
+    /// error, empty response). 0 fails fast.
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Sampling options sent with each request, per task type
+    #[serde(default)]
+    pub sampling: SamplingConfig,
+    /// Constrain structured responses with a JSON schema (Ollama's `format`).
+    /// Needs Ollama 0.5+ and a model that follows it; off by default.
+    #[serde(default)]
+    pub structured_output: bool,
+    /// Load and save problems here instead of searching the usual locations.
+    /// Overridden by the `ZOS_PROBLEMS_DIR` environment variable.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub problems_dir: Option<String>,
+    /// After a skill is recommended it is passed over for this many
+    /// selections, unless it is the only option. 0 turns the cooldown off.
+    #[serde(default = "default_skill_cooldown")]
+    pub skill_cooldown: usize,
+    /// Seed for tie-breaking when picking skills and problems. Set it for
+    /// reproducible selection; unset (the default) picks at random.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selection_seed: Option<u64>,
+    /// What to do with a proof analysis issue whose `step_id` names no step
+    #[serde(default)]
+    pub orphan_issues: OrphanIssuePolicy,
+    /// Most response text (in bytes) read from a single model call. A model
+    /// stuck in a loop is cut off here instead of growing until the timeout.
+    #[serde(default = "default_max_response_bytes")]
+    pub max_response_bytes: usize,
+}
+
+/// Repair for Step 1 issues that point at a step the model never listed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrphanIssuePolicy {
+    /// Drop the issue, with a warning in the log
+    Drop,
+    /// Attach the issue to a synthetic "general" step
+    #[default]
+    General,
+}
+
+/// Ollama sampling options, sent as the request's `options`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SamplingOptions {
+    pub temperature: f32,
+    pub top_p: f32,
+    /// Most tokens to generate; -1 means no limit
+    pub num_predict: i32,
+}
+
+/// Sampling options per task type. Low temperature keeps proof analysis JSON
+/// stable; problem generation runs hotter for variety. Missing tables keep
+/// their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SamplingConfig {
+    pub proof_analysis: SamplingOptions,
+    pub problem_generation: SamplingOptions,
+    pub general: SamplingOptions,
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        SamplingConfig {
+            proof_analysis: SamplingOptions { temperature: 0.1, top_p: 0.9, num_predict: -1 },
+            problem_generation: SamplingOptions { temperature: 0.8, top_p: 0.95, num_predict: -1 },
+            general: SamplingOptions { temperature: 0.6, top_p: 0.9, num_predict: -1 },
+        }
+    }
+}
+
+/// About 5k tokens, leaving room for the prompt and response in the context window
+pub const DEFAULT_MAX_INPUT_CHARS: usize = 20_000;
+
+fn default_max_input_chars() -> usize {
+    DEFAULT_MAX_INPUT_CHARS
+}
+
+pub const DEFAULT_OLLAMA_BASE_URL: &str = "http://localhost:11434";
+/// Environment variable that overrides `ollama_base_url`
+pub const OLLAMA_URL_ENV: &str = "ZOS_OLLAMA_URL";
+
+/// Environment variable that overrides `problems_dir`
+pub const PROBLEMS_DIR_ENV: &str = "ZOS_PROBLEMS_DIR";
+
+fn default_ollama_base_url() -> String {
+    DEFAULT_OLLAMA_BASE_URL.to_string()
+}
+
+pub const DEFAULT_COMPLETED_EXCLUSION_DAYS: u32 = 30;
+
+fn default_completed_exclusion_days() -> u32 {
+    DEFAULT_COMPLETED_EXCLUSION_DAYS
+}
+
+pub const DEFAULT_MAX_RETRIES: u32 = 2;
+
+fn default_max_retries() -> u32 {
+    DEFAULT_MAX_RETRIES
+}
+
+pub const DEFAULT_SKILL_COOLDOWN: usize = 2;
+
+fn default_skill_cooldown() -> usize {
+    DEFAULT_SKILL_COOLDOWN
+}
+
+pub const DEFAULT_MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+
+fn default_max_response_bytes() -> usize {
+    DEFAULT_MAX_RESPONSE_BYTES
 }
 
 impl Default for ModelConfig {
@@ -16,44 +152,25 @@ impl Default for ModelConfig {
             proof_model: "deepseek-r1:7b".to_string(),
             problem_model: "qwen2-math:7b".to_string(),
             general_model: "qwen2.5:7b-instruct".to_string(),
+            persist_response_cache: false,
+            race_proof_models: false,
+            max_input_chars: DEFAULT_MAX_INPUT_CHARS,
+            ollama_base_url: default_ollama_base_url(),
+            completed_exclusion_days: DEFAULT_COMPLETED_EXCLUSION_DAYS,
+            max_retries: DEFAULT_MAX_RETRIES,
+            sampling: SamplingConfig::default(),
+            structured_output: false,
+            problems_dir: None,
+            skill_cooldown: DEFAULT_SKILL_COOLDOWN,
+            selection_seed: None,
+            orphan_issues: OrphanIssuePolicy::default(),
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
         }
     }
 }
 
-fn get_config_path() -> PathBuf {
-    // Use platform-specific app data directory
-    #[cfg(target_os = "macos")]
-    {
-        if let Some(home) = std::env::var_os("HOME") {
-            let mut dir = PathBuf::from(home);
-            dir.push("Library/Application Support/com.zacnwo.zos");
-            dir.push("models.toml");
-            return dir;
-        }
-    }
-    
-    #[cfg(target_os = "windows")]
-    {
-        if let Some(appdata) = std::env::var_os("APPDATA") {
-            let mut dir = PathBuf::from(appdata);
-            dir.push("com.zacnwo.zos");
-            dir.push("models.toml");
-            return dir;
-        }
-    }
-    
-    #[cfg(target_os = "linux")]
-    {
-        if let Some(home) = std::env::var_os("HOME") {
-            let mut dir = PathBuf::from(home);
-            dir.push(".local/share/com.zacnwo.zos");
-            dir.push("models.toml");
-            return dir;
-        }
-    }
-    
-    // Fallback
-    PathBuf::from("models.toml")
+pub(crate) fn get_config_path() -> PathBuf {
+    crate::paths::app_data_dir().join("models.toml")
 }
 
 fn load_model_config_internal() -> ModelConfig {
@@ -61,8 +178,15 @@ fn load_model_config_internal() -> ModelConfig {
     
     // Try to load from config file
     if let Ok(content) = fs::read_to_string(&config_path) {
-        if let Ok(config) = toml::from_str::<ModelConfig>(&content) {
+        if let Ok(mut config) = toml::from_str::<ModelConfig>(&content) {
             eprintln!("[Config] Loaded model config from: {:?}", config_path);
+            match validate_ollama_url(&config.ollama_base_url) {
+                Ok(url) => config.ollama_base_url = url,
+                Err(e) => {
+                    eprintln!("[Config] {}, using {}", e, DEFAULT_OLLAMA_BASE_URL);
+                    config.ollama_base_url = default_ollama_base_url();
+                }
+            }
             return config;
         } else {
             eprintln!("[Config] Failed to parse models.toml, using defaults");
@@ -75,16 +199,122 @@ fn load_model_config_internal() -> ModelConfig {
 }
 
 lazy_static! {
-    static ref MODEL_CONFIG: ModelConfig = load_model_config_internal();
+    static ref MODEL_CONFIG: RwLock<ModelConfig> = RwLock::new(load_model_config_internal());
+}
+
+/// Get a snapshot of the active model configuration.
+/// Callers hold their own copy, so a request that already read the config
+/// keeps using the models it started with even if the config changes mid-flight.
+pub fn get_model_config() -> ModelConfig {
+    MODEL_CONFIG.read().clone()
+}
+
+/// Check that `url` is an http(s) URL with a host, returning it without a trailing slash
+pub fn validate_ollama_url(url: &str) -> Result<String, ZosError> {
+    let trimmed = url.trim().trim_end_matches('/');
+    let parsed = reqwest::Url::parse(trimmed)
+        .map_err(|e| ZosError::new(
+            format!("Invalid Ollama URL '{}': {}", url, e),
+            "config"
+        ))?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return Err(ZosError::new(
+            format!("Invalid Ollama URL '{}': expected http(s)://host[:port]", url),
+            "config"
+        ));
+    }
+    Ok(trimmed.to_string())
+}
+
+/// Pick the Ollama base URL: a valid environment override wins over the configured value
+pub fn resolve_ollama_base_url(env_value: Option<&str>, configured: &str) -> String {
+    env_value
+        .and_then(|value| validate_ollama_url(value).ok())
+        .unwrap_or_else(|| configured.to_string())
+}
+
+/// `ZOS_OLLAMA_URL`, read and validated once
+fn ollama_url_from_env() -> Option<&'static str> {
+    static ENV_URL: OnceLock<Option<String>> = OnceLock::new();
+    ENV_URL.get_or_init(|| {
+        let value = std::env::var(OLLAMA_URL_ENV).ok()?;
+        match validate_ollama_url(&value) {
+            Ok(url) => Some(url),
+            Err(e) => {
+                eprintln!("[Config] Ignoring {}: {}", OLLAMA_URL_ENV, e);
+                None
+            }
+        }
+    }).as_deref()
+}
+
+/// Base URL of the Ollama server every model request goes to
+pub fn ollama_base_url() -> String {
+    resolve_ollama_base_url(ollama_url_from_env(), &MODEL_CONFIG.read().ollama_base_url)
+}
+
+/// Pick the problems directory override: a non-empty environment value wins
+/// over the configured one. None means search the default locations.
+pub fn resolve_problems_dir(env_value: Option<&str>, configured: Option<&str>) -> Option<PathBuf> {
+    env_value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .or_else(|| configured.map(str::trim).filter(|value| !value.is_empty()))
+        .map(PathBuf::from)
+}
+
+/// Problems directory set through `ZOS_PROBLEMS_DIR` or `problems_dir`, if any
+pub fn problems_dir_override() -> Option<PathBuf> {
+    let env_value = std::env::var(PROBLEMS_DIR_ENV).ok();
+    resolve_problems_dir(env_value.as_deref(), MODEL_CONFIG.read().problems_dir.as_deref())
+}
+
+/// Rng for skill and problem selection, seeded from `seed` if given and
+/// from entropy otherwise
+pub fn seeded_selection_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
 }
 
-/// Get the cached model configuration (loaded once at startup)
-pub fn get_model_config() -> &'static ModelConfig {
-    &MODEL_CONFIG
+/// A selection rng for the configured `selection_seed`. `AppState` keeps one
+/// and draws every selection from it, so a seeded run is repeatable as a
+/// whole rather than repeating the same pick.
+pub fn new_selection_rng() -> StdRng {
+    seeded_selection_rng(MODEL_CONFIG.read().selection_seed)
 }
 
 /// Legacy function for backward compatibility
 pub fn load_model_config() -> ModelConfig {
-    get_model_config().clone()
+    get_model_config()
 }
 
+/// Write the config to `models.toml` and make it the active configuration
+pub async fn set_model_config(mut config: ModelConfig) -> Result<(), ZosError> {
+    config.ollama_base_url = validate_ollama_url(&config.ollama_base_url)?;
+    let config_path = get_config_path();
+    
+    if let Some(parent) = config_path.parent() {
+        tokio::fs::create_dir_all(parent).await
+            .map_err(|e| ZosError::new(
+                format!("Failed to create config directory: {}", e),
+                "config"
+            ).with_context(format!("path: {:?}", parent)))?;
+    }
+    
+    let content = toml::to_string_pretty(&config)
+        .map_err(|e| ZosError::new(
+            format!("Failed to serialize model config: {}", e),
+            "config"
+        ))?;
+    tokio::fs::write(&config_path, content).await
+        .map_err(|e| ZosError::new(
+            format!("Failed to write models.toml: {}", e),
+            "config"
+        ).with_context(format!("path: {:?}", config_path)))?;
+    
+    *MODEL_CONFIG.write() = config;
+    tracing::info!(path = ?config_path, "Model config updated");
+    Ok(())
+}
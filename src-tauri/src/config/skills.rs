@@ -0,0 +1,155 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+
+/// How strongly skill values react to proof outcomes.
+/// Loaded from `skill_weights.toml` next to `models.toml`; missing keys keep their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkillWeights {
+    /// Penalty for minor issues (missing justification, wrong definition, ...)
+    pub minor_issue_penalty: f32,
+    /// Penalty for serious issues (incorrect logic, math gaps, code bugs, ...)
+    pub major_issue_penalty: f32,
+    /// Reward for a correct answer to a clarifying question
+    pub correct_answer_reward: f32,
+    /// Reward for a partially correct answer to a clarifying question
+    pub partial_answer_reward: f32,
+    /// Reward to the problem's own skill for a perfect proof
+    pub perfect_proof_reward: f32,
+    /// Reward to proof_strategy and logical_reasoning for a perfect proof
+    pub perfect_proof_secondary_reward: f32,
+}
+
+impl Default for SkillWeights {
+    fn default() -> Self {
+        SkillWeights {
+            minor_issue_penalty: 0.02,
+            major_issue_penalty: 0.03,
+            correct_answer_reward: 0.01,
+            partial_answer_reward: 0.005,
+            perfect_proof_reward: 0.02,
+            perfect_proof_secondary_reward: 0.01,
+        }
+    }
+}
+
+fn get_weights_path() -> PathBuf {
+    super::models::get_config_path().with_file_name("skill_weights.toml")
+}
+
+/// Read `path` as TOML, falling back to `T::default()` when the file is
+/// missing or invalid
+fn load_toml_or_default<T: DeserializeOwned + Default>(path: &Path) -> T {
+    match fs::read_to_string(path) {
+        Ok(content) => match toml::from_str::<T>(&content) {
+            Ok(value) => {
+                tracing::info!(path = ?path, "Loaded skill config");
+                value
+            }
+            Err(e) => {
+                tracing::warn!(path = ?path, error = %e, "Failed to parse skill config, using defaults");
+                T::default()
+            }
+        },
+        Err(_) => T::default(),
+    }
+}
+
+fn load_skill_weights_internal() -> SkillWeights {
+    load_toml_or_default(&get_weights_path())
+}
+
+/// Groups of related skills shown as one bar each on the dashboard.
+/// Loaded from `skill_domains.toml` next to `models.toml`, e.g.
+/// `[domains]` then `math = ["analysis_math", "putnam_competition"]`.
+/// Skills not listed belong to no domain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkillDomains {
+    pub domains: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for SkillDomains {
+    fn default() -> Self {
+        let groups: &[(&str, &[&str])] = &[
+            ("math", &["analysis_math", "putnam_competition"]),
+            ("reasoning", &["proof_strategy", "logical_reasoning"]),
+            ("engineering", &["coding_debugging", "production_engineering"]),
+            ("ml_rl", &["ml_theory", "rl_theory"]),
+        ];
+        SkillDomains {
+            domains: groups.iter()
+                .map(|(domain, skills)| (domain.to_string(), skills.iter().map(|s| s.to_string()).collect()))
+                .collect(),
+        }
+    }
+}
+
+fn get_domains_path() -> PathBuf {
+    super::models::get_config_path().with_file_name("skill_domains.toml")
+}
+
+/// Starting skill values for new users and after a progress reset.
+/// Loaded from `skill_defaults.toml` next to `models.toml`, e.g.
+/// `default_value = 0.6` then `[overrides]` with `algorithms = 0.8`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkillDefaults {
+    /// Starting value for every skill without an override
+    pub default_value: f32,
+    /// Starting value for specific skills
+    pub overrides: HashMap<String, f32>,
+}
+
+impl Default for SkillDefaults {
+    fn default() -> Self {
+        SkillDefaults {
+            default_value: 0.5,
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+impl SkillDefaults {
+    /// Starting value for `skill`, clamped to [0, 1]
+    pub fn initial_value(&self, skill: &str) -> f32 {
+        let value = self.overrides.get(skill).copied().unwrap_or(self.default_value);
+        if value.is_nan() { 0.5 } else { value.clamp(0.0, 1.0) }
+    }
+}
+
+fn get_defaults_path() -> PathBuf {
+    super::models::get_config_path().with_file_name("skill_defaults.toml")
+}
+
+lazy_static! {
+    static ref SKILL_WEIGHTS: SkillWeights = load_skill_weights_internal();
+    static ref SKILL_DOMAINS: SkillDomains = load_toml_or_default(&get_domains_path());
+    static ref SKILL_DEFAULTS: RwLock<SkillDefaults> = RwLock::new(load_toml_or_default(&get_defaults_path()));
+}
+
+/// The skill weights, loaded once at first use
+pub fn get_skill_weights() -> SkillWeights {
+    SKILL_WEIGHTS.clone()
+}
+
+/// The skill domain grouping
+pub fn get_skill_domains() -> SkillDomains {
+    SKILL_DOMAINS.clone()
+}
+
+/// Get a snapshot of the starting skill values
+pub fn get_skill_defaults() -> SkillDefaults {
+    SKILL_DEFAULTS.read().clone()
+}
+
+/// Re-read `skill_defaults.toml`, so a reset picks up edits made since startup
+pub fn reload_skill_defaults() -> SkillDefaults {
+    let defaults: SkillDefaults = load_toml_or_default(&get_defaults_path());
+    *SKILL_DEFAULTS.write() = defaults.clone();
+    defaults
+}
@@ -1,6 +1,45 @@
 use serde::{Serialize, Deserialize};
 use std::fmt;
 
+/// Model is not installed or Ollama is unreachable
+pub const STAGE_MODEL_UNAVAILABLE: &str = "model_availability";
+/// The Ollama server itself did not answer the pre-flight ping
+pub const STAGE_OLLAMA_UNREACHABLE: &str = "ollama_unreachable";
+/// Offline mode is on, so the request would have needed a model call
+pub const STAGE_OFFLINE: &str = "offline";
+/// A single model call failed (connection, HTTP error)
+pub const STAGE_MODEL_CALL: &str = "model_call";
+/// No JSON object could be found in the model output
+pub const STAGE_JSON_EXTRACT: &str = "json_extract";
+/// JSON was found but did not match the expected shape
+pub const STAGE_JSON_PARSE: &str = "json_parse";
+/// Model output was cut off
+pub const STAGE_TRUNCATED: &str = "truncated";
+/// A model kept failing until its retries ran out
+pub const STAGE_RETRY_EXHAUSTED: &str = "retry_exhausted";
+/// Operation exceeded its time budget
+pub const STAGE_TIMEOUT: &str = "timeout";
+/// No model could be routed for the request
+pub const STAGE_ROUTING: &str = "routing";
+/// User input is too long to send to the model
+pub const STAGE_INPUT_TOO_LARGE: &str = "input_too_large";
+/// The user cancelled the request while it was in flight
+pub const STAGE_CANCELLED: &str = "cancelled";
+/// The model kept generating past `max_response_bytes`, so reading was stopped
+pub const STAGE_RESPONSE_TOO_LARGE: &str = "response_too_large";
+/// The response took so long that it was probably cut off
+pub const STAGE_TIMEOUT_TRUNCATION: &str = "timeout_truncation";
+/// The fallback model could not be called to repair a response
+pub const STAGE_JSON_REPAIR: &str = "json_repair";
+/// The fallback model reported the response it was repairing as cut off
+pub const STAGE_TRUNCATED_DETECTED: &str = "truncated_detected";
+/// The repaired response held no JSON object
+pub const STAGE_JSON_REPAIR_EXTRACT: &str = "json_repair_extract";
+/// The repaired JSON did not match the expected shape
+pub const STAGE_JSON_REPAIR_PARSE: &str = "json_repair_parse";
+/// A model result could not be stored in the response cache
+pub const STAGE_CACHE: &str = "cache";
+
 /// Unified error type for the entire zOS codebase.
 /// All functions should return Result<T, ZosError> instead of String errors.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,6 +50,9 @@ pub struct ZosError {
     pub retry_succeeded: bool,
     pub context: Option<String>,
     pub source: Option<String>,
+    /// Whether retrying the same request may succeed (see `is_retryable`)
+    #[serde(default)]
+    pub retryable: bool,
 }
 
 impl ZosError {
@@ -23,9 +65,17 @@ impl ZosError {
             retry_succeeded: false,
             context: None,
             source: None,
+            retryable: is_retryable_stage(stage),
         }
     }
 
+    /// Whether retrying the same request may succeed. Transient failures
+    /// (timeouts, bad or truncated output) are retryable; a missing model,
+    /// routing, config and I/O errors need the user to fix something first.
+    pub fn is_retryable(&self) -> bool {
+        self.retryable
+    }
+
     /// Add model context to the error
     pub fn with_model<S: Into<String>>(mut self, model: S) -> Self {
         self.model = Some(model.into());
@@ -44,6 +94,16 @@ impl ZosError {
         self
     }
 
+    /// Tag the error with the request id its logs were recorded under,
+    /// appended to any existing context
+    pub fn with_request_id(mut self, request_id: &str) -> Self {
+        self.context = Some(match self.context.take() {
+            Some(context) => format!("{}; request_id: {}", context, request_id),
+            None => format!("request_id: {}", request_id),
+        });
+        self
+    }
+
     /// Add source error information
     pub fn with_source<S: Into<String>>(mut self, source: S) -> Self {
         self.source = Some(source.into());
@@ -51,6 +111,19 @@ impl ZosError {
     }
 }
 
+fn is_retryable_stage(stage: &str) -> bool {
+    matches!(
+        stage,
+        STAGE_MODEL_CALL
+            | STAGE_JSON_EXTRACT
+            | STAGE_JSON_PARSE
+            | STAGE_TRUNCATED
+            | STAGE_RETRY_EXHAUSTED
+            | STAGE_TIMEOUT
+            | STAGE_TIMEOUT_TRUNCATION
+    )
+}
+
 impl fmt::Display for ZosError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "[{}] {}", self.stage, self.message)?;
@@ -75,6 +148,9 @@ impl std::error::Error for ZosError {
 
 impl From<anyhow::Error> for ZosError {
     fn from(err: anyhow::Error) -> Self {
+        if let Some(zos) = err.downcast_ref::<ZosError>() {
+            return zos.clone();
+        }
         ZosError::new(
             err.to_string(),
             "unknown"
@@ -95,7 +171,7 @@ impl From<serde_json::Error> for ZosError {
     fn from(err: serde_json::Error) -> Self {
         ZosError::new(
             format!("JSON error: {}", err),
-            "json_parse"
+            STAGE_JSON_PARSE
         ).with_source("serde_json")
     }
 }
@@ -104,7 +180,7 @@ impl From<tokio::time::error::Elapsed> for ZosError {
     fn from(_: tokio::time::error::Elapsed) -> Self {
         ZosError::new(
             "Operation timed out",
-            "timeout"
+            STAGE_TIMEOUT
         ).with_source("tokio::time")
     }
 }
@@ -0,0 +1,64 @@
+use std::path::Path;
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use crate::error::ZosError;
+
+/// Append `value` as one line to the JSON Lines log at `path`, creating its
+/// directory if needed. `log_name` names the log in error messages.
+pub async fn append_line<T: Serialize>(path: &Path, value: &T, log_name: &str) -> Result<(), ZosError> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| ZosError::new(
+                format!("Failed to create directory: {}", e),
+                "io"
+            ).with_context(format!("path: {:?}", parent)))?;
+    }
+    
+    let mut line = serde_json::to_string(value)
+        .map_err(|e| ZosError::new(
+            format!("Failed to serialize {} entry: {}", log_name, e),
+            "json_serialize"
+        ))?;
+    line.push('\n');
+    
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+        .map_err(|e| ZosError::new(
+            format!("Failed to open {}: {}", log_name, e),
+            "io"
+        ).with_context(format!("path: {:?}", path)))?;
+    // Flush so the line is on disk before the next append or read
+    let write = async {
+        file.write_all(line.as_bytes()).await?;
+        file.flush().await
+    };
+    write.await
+        .map_err(|e| ZosError::new(
+            format!("Failed to write {}: {}", log_name, e),
+            "io"
+        ).with_context(format!("path: {:?}", path)))
+}
+
+/// Replace the log at `path` with `values`, one per line
+pub async fn write_lines<T: Serialize>(path: &Path, values: &[T], log_name: &str) -> Result<(), ZosError> {
+    let mut content = String::new();
+    for value in values {
+        let line = serde_json::to_string(value)
+            .map_err(|e| ZosError::new(
+                format!("Failed to serialize {} entry: {}", log_name, e),
+                "json_serialize"
+            ))?;
+        content.push_str(&line);
+        content.push('\n');
+    }
+    tokio::fs::write(path, content)
+        .await
+        .map_err(|e| ZosError::new(
+            format!("Failed to write {}: {}", log_name, e),
+            "io"
+        ).with_context(format!("path: {:?}", path)))
+}
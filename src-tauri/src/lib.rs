@@ -9,18 +9,94 @@ mod config;
 mod models;
 mod error;
 mod logging;
+mod paths;
+mod jsonl;
 mod cache;
 mod state;
 mod metrics;
+mod analytics;
+mod status;
+mod shutdown;
+mod circuit_breaker;
 
+// Unit test modules (paths are relative to src/)
 #[cfg(test)]
-mod tests {
-    // Re-export test modules
-    #[path = "../tests/error_handling_test.rs"]
-    mod error_handling_test;
-    #[path = "../tests/json_extraction_test.rs"]
-    mod json_extraction_test;
-}
+#[path = "../tests/error_handling_test.rs"]
+mod error_handling_test;
+#[cfg(test)]
+#[path = "../tests/json_extraction_test.rs"]
+mod json_extraction_test;
+
+#[cfg(test)]
+#[path = "../tests/json_extraction_prop_test.rs"]
+mod json_extraction_prop_test;
+#[cfg(test)]
+#[path = "../tests/trend_test.rs"]
+mod trend_test;
+#[cfg(test)]
+#[path = "../tests/review_schedule_test.rs"]
+mod review_schedule_test;
+#[cfg(test)]
+#[path = "../tests/selector_test.rs"]
+mod selector_test;
+#[cfg(test)]
+#[path = "../tests/app_state_test.rs"]
+mod app_state_test;
+#[cfg(test)]
+#[path = "../tests/circuit_breaker_test.rs"]
+mod circuit_breaker_test;
+#[cfg(test)]
+#[path = "../tests/registry_test.rs"]
+mod registry_test;
+#[cfg(test)]
+#[path = "../tests/problem_test.rs"]
+mod problem_test;
+#[cfg(test)]
+#[path = "../tests/session_summary_test.rs"]
+mod session_summary_test;
+#[cfg(test)]
+#[path = "../tests/sessions_test.rs"]
+mod sessions_test;
+#[cfg(test)]
+#[path = "../tests/metrics_test.rs"]
+mod metrics_test;
+#[cfg(test)]
+#[path = "../tests/skill_weights_test.rs"]
+mod skill_weights_test;
+#[cfg(test)]
+#[path = "../tests/prerequisites_test.rs"]
+mod prerequisites_test;
+#[cfg(test)]
+#[path = "../tests/transfer_test.rs"]
+mod transfer_test;
+#[cfg(test)]
+#[path = "../tests/cache_test.rs"]
+mod cache_test;
+
+#[cfg(test)]
+#[path = "../tests/logging_test.rs"]
+mod logging_test;
+
+#[cfg(test)]
+#[path = "../tests/proof_test.rs"]
+mod proof_test;
+
+#[cfg(test)]
+#[path = "../tests/ollama_host_test.rs"]
+mod ollama_host_test;
+
+#[cfg(test)]
+#[path = "../tests/proof_state_test.rs"]
+mod proof_state_test;
+#[cfg(test)]
+#[path = "../tests/plan_test.rs"]
+mod plan_test;
+#[cfg(test)]
+#[path = "../tests/mastery_test.rs"]
+mod mastery_test;
+#[cfg(test)]
+#[path = "../tests/paths_test.rs"]
+mod paths_test;
 
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -55,7 +131,7 @@ pub fn run() {
             Ok(Some(plan)) => {
                 if plan.is_expired() {
                     let new_plan = brain::generate_daily_plan().await;
-                    if let Err(e) = brain::store::save(&new_plan).await {
+                    if let Err(e) = brain::store::save_generated(&new_plan).await {
                         tracing::warn!(error = %e, "Failed to save daily plan");
                     }
                 }
@@ -63,7 +139,7 @@ pub fn run() {
             Ok(None) => {
                 // No plan exists, generate one
                 let new_plan = brain::generate_daily_plan().await;
-                if let Err(e) = brain::store::save(&new_plan).await {
+                if let Err(e) = brain::store::save_generated(&new_plan).await {
                     tracing::warn!(error = %e, "Failed to save daily plan");
                 }
             }
@@ -72,6 +148,14 @@ pub fn run() {
             }
         }
         
+        // Restore the persisted response cache and keep it flushed (opt-in)
+        if config::models::get_model_config().persist_response_cache {
+            if let Err(e) = cache::load_response_cache(&app_state).await {
+                tracing::warn!(error = %e, "Failed to load persisted response cache");
+            }
+            cache::start_response_cache_persistence(app_state.clone());
+        }
+        
         // Warm up models in background (non-blocking)
         tokio::spawn(async {
             models::warmup::warmup_models().await;
@@ -81,6 +165,12 @@ pub fn run() {
     // Store AppState in Tauri's managed state
     let app_state_arc = std::sync::Arc::new(app_state);
     
+    // Keep the problem cache topped up in the background
+    {
+        let _runtime = rt.enter();
+        problems::cache::start_problem_prefetch(app_state_arc.clone());
+    }
+    
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .manage(app_state_arc.clone())
@@ -90,18 +180,78 @@ pub fn run() {
             routes::get_recommended_problem,
             routes::precompute_next_problem,
             routes::get_problems_by_topic,
+            routes::get_problems_by_difficulty,
+            routes::repair_problem_topics,
             routes::get_problem_by_id,
             routes::get_skills,
             routes::update_skills_from_issues,
             routes::save_session_record,
             routes::refresh_daily_plan,
+            routes::refresh_daily_plan_with,
             routes::get_daily_plan,
-            routes::submit_problem_attempt
+            routes::get_mastered_skills,
+            routes::get_plan_history,
+            routes::get_plan_progress,
+            routes::submit_problem_attempt,
+            routes::analyze_problem_attempt,
+            routes::get_model_health,
+            routes::list_ollama_models,
+            routes::get_model_config,
+            routes::set_model_config,
+            routes::general_query,
+            routes::get_analytics_data,
+            routes::get_session_history,
+            routes::get_recent_failures,
+            routes::get_skill_drift,
+            routes::get_session_summary,
+            routes::get_metrics_text,
+            routes::set_offline_mode,
+            routes::get_problems_by_tag,
+            routes::set_skill_value,
+            routes::undo_last_skill_update,
+            routes::import_problems,
+            routes::export_problems,
+            routes::get_session_by_id,
+            routes::delete_session,
+            routes::get_log_path,
+            routes::set_log_level,
+            routes::preview_step1_prompt,
+            routes::preview_step2_prompt,
+            routes::http_diagnostics,
+            routes::get_last_call_throughput,
+            routes::get_model_latency_percentiles,
+            routes::cancel_request,
+            routes::get_proof_state,
+            routes::reset_proof_state,
+            routes::get_hint,
+            routes::reveal_solution,
+            routes::recompute_skills_from_sessions,
+            routes::get_problem_cache,
+            routes::clear_problem_cache,
+            routes::remove_cached_problem,
+            routes::rate_problem,
+            routes::get_proof_diff,
+            routes::migrate_sessions,
+            routes::generate_weekly_report,
+            routes::get_skills_detailed,
+            routes::get_domain_scores,
+            routes::start_calibration,
+            routes::submit_calibration,
+            routes::set_focus_skills,
+            routes::quick_analyze,
+            routes::get_app_status,
+            routes::reset_all_progress
         ])
-        .run(tauri::generate_context!())
+        .build(tauri::generate_context!())
         .map_err(|e| {
             tracing::error!(error = %e, "Failed to run Tauri application");
             e
         })
-        .expect("error while running tauri application");
+        .expect("error while running tauri application")
+        .run(move |_app, event| {
+            // Flush caches before the process exits
+            if let tauri::RunEvent::Exit = event {
+                rt.block_on(shutdown::on_shutdown(&app_state_arc));
+            }
+        });
 }
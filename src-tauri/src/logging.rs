@@ -1,28 +1,120 @@
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+use crate::error::ZosError;
+
+/// Number of daily log files kept before the oldest is deleted
+const MAX_LOG_FILES: usize = 7;
+
+/// Keeps the non-blocking file writer flushing for the life of the app
+static LOG_GUARD: OnceLock<WorkerGuard> = OnceLock::new();
+
+/// Levels accepted by `set_log_level`
+pub const LOG_LEVELS: [&str; 5] = ["error", "warn", "info", "debug", "trace"];
+
+/// Handle for swapping the active filter at runtime
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// Short random id attached to a top-level command's span, so all log lines
+/// for one request can be grepped together
+pub fn new_request_id() -> String {
+    format!("req_{:08x}", rand::random::<u32>())
+}
+
+/// Normalize a level name, rejecting anything outside `LOG_LEVELS`
+pub fn parse_log_level(level: &str) -> Result<&'static str, ZosError> {
+    let normalized = level.trim().to_lowercase();
+    LOG_LEVELS.iter()
+        .find(|l| **l == normalized)
+        .copied()
+        .ok_or_else(|| ZosError::new(
+            format!("Unknown log level '{}', expected one of: {}", level, LOG_LEVELS.join(", ")),
+            "validation",
+        ))
+}
+
+/// Replace the active log filter, e.g. to turn on debug output while
+/// chasing an intermittent failure without relaunching with RUST_LOG
+pub fn set_log_level(level: &str) -> Result<(), ZosError> {
+    let level = parse_log_level(level)?;
+    let handle = FILTER_HANDLE.get()
+        .ok_or_else(|| ZosError::new("Logging has not been initialized", "config"))?;
+    handle.reload(EnvFilter::new(level))
+        .map_err(|e| ZosError::new(format!("Failed to change log level: {}", e), "config"))?;
+    tracing::info!(level = level, "Log level changed");
+    Ok(())
+}
+
+/// Directory the rotating log files are written to
+pub fn log_dir() -> PathBuf {
+    crate::paths::log_dir()
+}
+
 /// Initialize structured logging with tracing
-/// This should be called once at application startup
+/// This should be called once at application startup.
+/// Logs go to a daily-rotated JSON file under `log_dir()` (last 7 days kept),
+/// and also to stdout in debug builds.
 pub fn init_logging() {
+    use tracing_appender::rolling::{RollingFileAppender, Rotation};
     use tracing_subscriber::fmt;
     use tracing_subscriber::prelude::*;
-    use tracing_subscriber::EnvFilter;
 
     let filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new("info"));
+    let (filter, filter_handle) = reload::Layer::new(filter);
+    let _ = FILTER_HANDLE.set(filter_handle);
 
-    let subscriber = tracing_subscriber::registry()
-        .with(filter)
-        .with(
-            fmt::layer()
+    let dir = log_dir();
+    let file_appender = std::fs::create_dir_all(&dir)
+        .map_err(|e| e.to_string())
+        .and_then(|_| {
+            RollingFileAppender::builder()
+                .rotation(Rotation::DAILY)
+                .filename_prefix("zos")
+                .filename_suffix("log")
+                .max_log_files(MAX_LOG_FILES)
+                .build(&dir)
+                .map_err(|e| e.to_string())
+        });
+    let (file_layer, file_error) = match file_appender {
+        Ok(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let _ = LOG_GUARD.set(guard);
+            let layer = fmt::layer()
+                .with_writer(writer)
+                .with_ansi(false)
                 .with_target(true)
                 .with_thread_ids(true)
                 .with_file(true)
                 .with_line_number(true)
-                .json() // JSON output for structured logging
-        );
+                .json();
+            (Some(layer), None)
+        }
+        Err(e) => (None, Some(e)),
+    };
+
+    let stdout_layer = cfg!(debug_assertions).then(|| {
+        fmt::layer()
+            .with_target(true)
+            .with_thread_ids(true)
+            .with_file(true)
+            .with_line_number(true)
+            .json() // JSON output for structured logging
+    });
+
+    let subscriber = tracing_subscriber::registry()
+        .with(filter)
+        .with(file_layer)
+        .with(stdout_layer);
 
     tracing::subscriber::set_global_default(subscriber)
         .expect("Failed to set global tracing subscriber");
     
-    tracing::info!("Structured logging initialized");
+    match file_error {
+        None => tracing::info!(log_dir = ?dir, "Structured logging initialized"),
+        Some(e) => tracing::warn!(log_dir = ?dir, error = %e, "Could not open log file, logging to stdout only"),
+    }
 }
 
 /// Legacy logging functions for backward compatibility
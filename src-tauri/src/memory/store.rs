@@ -1,4 +1,5 @@
 use crate::skills::model::SkillVector;
+use crate::skills::store::{append_skill_audit, SkillAuditEntry};
 use crate::state::app::AppState;
 use crate::error::ZosError;
 
@@ -41,6 +42,87 @@ where
     Ok(())
 }
 
+/// Revert the most recent skill update and persist the restored vector
+pub async fn undo_last_skill_update(state: &AppState) -> Result<SkillVector, ZosError> {
+    let restored = state.undo_skill_update()
+        .ok_or_else(|| ZosError::new("No skill update to undo", "state"))?;
+    crate::skills::store::save_skill_vector(&restored).await?;
+    tracing::info!("Reverted last skill update");
+    Ok(restored)
+}
+
+/// Rebuild the skill vector by replaying every stored session with the current
+/// weights, then persist it. The previous vector stays available to undo.
+pub async fn recompute_skills_from_sessions(state: &AppState) -> Result<SkillVector, ZosError> {
+    let sessions = crate::sessions::load_all_sessions().await?;
+    let replayed = SkillVector::from_sessions(&sessions, &crate::config::skills::get_skill_weights());
+    update_skills(state, |skills| *skills = replayed.clone()).await?;
+    tracing::info!(sessions = sessions.len(), "Recomputed skills from session history");
+    Ok(replayed)
+}
+
+/// Manually override a skill value (clamped to [0, 1]), persist it, and
+/// record the change in the audit log. Unknown skill names are rejected.
+pub async fn set_skill_value(state: &AppState, skill: &str, value: f32) -> Result<SkillAuditEntry, ZosError> {
+    if !value.is_finite() {
+        return Err(ZosError::new(
+            format!("Skill value must be a number between 0 and 1, got {}", value),
+            "validation"
+        ));
+    }
+    
+    let skills = get_skills(state).await?;
+    let old_value = match skills.skills.get(skill) {
+        Some(v) => *v,
+        None => {
+            let mut known: Vec<&str> = skills.skills.keys().map(|k| k.as_str()).collect();
+            known.sort();
+            return Err(ZosError::new(
+                format!("Unknown skill '{}'", skill),
+                "validation"
+            ).with_context(format!("known skills: {}", known.join(", "))));
+        }
+    };
+    
+    let new_value = value.clamp(0.0, 1.0);
+    update_skills(state, |s| {
+        s.skills.insert(skill.to_string(), new_value);
+    }).await?;
+    
+    let entry = SkillAuditEntry {
+        skill: skill.to_string(),
+        old_value,
+        new_value,
+        timestamp: chrono::Utc::now().timestamp(),
+    };
+    append_skill_audit(&entry).await?;
+    tracing::info!(skill = %skill, old_value = old_value, new_value = new_value, "Skill value set manually");
+    
+    Ok(entry)
+}
+
+/// Restrict recommendations and plans to `focus` (focus mode) and persist
+/// it; an empty list turns focus mode off. Unknown skill names are rejected.
+/// Returns the stored focus, sorted and without duplicates.
+pub async fn set_focus_skills(state: &AppState, focus: &[String]) -> Result<Vec<String>, ZosError> {
+    let skills = get_skills(state).await?;
+    if let Some(unknown) = focus.iter().find(|skill| !skills.skills.contains_key(*skill)) {
+        let mut known: Vec<&str> = skills.skills.keys().map(|k| k.as_str()).collect();
+        known.sort();
+        return Err(ZosError::new(
+            format!("Unknown skill '{}'", unknown),
+            "validation"
+        ).with_context(format!("known skills: {}", known.join(", "))));
+    }
+    
+    let mut focus = focus.to_vec();
+    focus.sort();
+    focus.dedup();
+    crate::skills::store::save_focus_skills(&focus).await?;
+    tracing::info!(focus = ?focus, "Focus skills set");
+    Ok(focus)
+}
+
 /// Synchronous versions for backward compatibility (deprecated)
 /// These will be removed once all callers are migrated to async
 #[deprecated(note = "Use get_skills(state).await instead")]
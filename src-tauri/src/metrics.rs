@@ -1,5 +1,6 @@
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use serde::{Serialize, Deserialize};
 
 /// Prometheus-style metrics for observability
 /// All metrics are atomic counters for thread-safety
@@ -19,6 +20,8 @@ pub struct Metrics {
     pub errors_total: Arc<AtomicU64>,
     /// Session state transitions
     pub session_state_transitions: Arc<AtomicU64>,
+    /// JSON extractions answered from the extraction cache
+    pub extraction_cache_hit_count: Arc<AtomicU64>,
 }
 
 impl Metrics {
@@ -60,4 +63,100 @@ impl Metrics {
     pub fn record_state_transition(&self) {
         self.session_state_transitions.fetch_add(1, Ordering::Relaxed);
     }
+
+    /// Record a JSON extraction served from the extraction cache
+    pub fn record_extraction_cache_hit(&self) {
+        self.extraction_cache_hit_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Render all counters in the Prometheus text exposition format
+    pub fn metrics_text(&self) -> String {
+        let counters: [(&str, &str, &AtomicU64); 8] = [
+            ("zos_model_latency_ms_total", "Sum of model call latency in milliseconds", &self.model_latency_ms),
+            ("zos_routing_time_ms_total", "Sum of routing decision time in milliseconds", &self.routing_time_ms),
+            ("zos_cache_hits_total", "Response cache hits", &self.cache_hit_count),
+            ("zos_cache_misses_total", "Response cache misses", &self.cache_miss_count),
+            ("zos_fallbacks_total", "Queries that fell back from the primary model", &self.fallback_count),
+            ("zos_errors_total", "Errors raised across all stages", &self.errors_total),
+            ("zos_session_state_transitions_total", "Proof session state transitions", &self.session_state_transitions),
+            ("zos_extraction_cache_hits_total", "JSON extractions served from cache", &self.extraction_cache_hit_count),
+        ];
+
+        let mut out = String::new();
+        for (name, help, value) in counters {
+            out.push_str(&format!("# HELP {} {}\n", name, help));
+            out.push_str(&format!("# TYPE {} counter\n", name));
+            out.push_str(&format!("{} {}\n", name, value.load(Ordering::Relaxed)));
+        }
+        out
+    }
+}
+
+/// Upper bounds (ms) of the latency histogram buckets. Anything slower than
+/// the last bound lands in a final overflow bucket.
+pub const LATENCY_BUCKETS_MS: [u64; 14] = [
+    100, 250, 500, 1_000, 2_000, 3_000, 5_000, 7_500, 10_000, 15_000, 20_000, 30_000, 45_000, 60_000,
+];
+
+/// Fixed-size latency histogram: memory stays constant however many calls are recorded
+#[derive(Debug, Clone, Default)]
+pub struct LatencyHistogram {
+    counts: [u64; LATENCY_BUCKETS_MS.len() + 1],
+    total: u64,
+    max_ms: u64,
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, ms: u64) {
+        let bucket = LATENCY_BUCKETS_MS.iter()
+            .position(|bound| ms <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.counts[bucket] += 1;
+        self.total += 1;
+        self.max_ms = self.max_ms.max(ms);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.total
+    }
+
+    /// Upper bound of the bucket holding the `p`-th percentile (0-100), or the
+    /// slowest recorded call if that falls in the overflow bucket.
+    /// None when nothing has been recorded.
+    pub fn percentile(&self, p: f64) -> Option<u64> {
+        if self.total == 0 {
+            return None;
+        }
+        let rank = ((p / 100.0) * self.total as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (i, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(LATENCY_BUCKETS_MS.get(i).copied().unwrap_or(self.max_ms).min(self.max_ms));
+            }
+        }
+        Some(self.max_ms)
+    }
+}
+
+/// Latency percentiles for one model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyPercentiles {
+    pub model: String,
+    pub count: u64,
+    pub p50_ms: u64,
+    pub p90_ms: u64,
+    pub p99_ms: u64,
+}
+
+impl LatencyPercentiles {
+    pub fn from_histogram(model: &str, histogram: &LatencyHistogram) -> Option<Self> {
+        Some(LatencyPercentiles {
+            model: model.to_string(),
+            count: histogram.count(),
+            p50_ms: histogram.percentile(50.0)?,
+            p90_ms: histogram.percentile(90.0)?,
+            p99_ms: histogram.percentile(99.0)?,
+        })
+    }
 }
@@ -1,12 +1,17 @@
 use reqwest::Client;
 use anyhow::{Result, Context};
-use crate::error::ZosError;
+use crate::error::{ZosError, STAGE_MODEL_UNAVAILABLE, STAGE_OLLAMA_UNREACHABLE};
 use crate::logging::{log_info, log_warn, log_error};
 use tokio::time::{timeout, Duration};
 use std::sync::OnceLock;
+use crate::config::models::ollama_base_url;
+use crate::state::app::AppState;
+use std::future::Future;
 
-const OLLAMA_BASE_URL: &str = "http://localhost:11434";
 const MODEL_CHECK_TIMEOUT: u64 = 3; // 3 seconds max for availability check
+const OLLAMA_PING_TIMEOUT_MS: u64 = 1000; // pre-flight ping before any pull/retry
+/// How long a model confirmed as installed is trusted without asking Ollama again
+pub const AVAILABILITY_TTL: Duration = Duration::from_secs(30);
 
 /// Reusable HTTP client for availability checks
 static AVAILABILITY_CLIENT: OnceLock<Client> = OnceLock::new();
@@ -47,28 +52,38 @@ pub async fn model_exists_in_ollama(model: &str) -> bool {
     }
 }
 
-async fn check_model_availability(model: &str) -> Result<bool> {
+/// A model installed in Ollama, as reported by `/api/tags`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct OllamaModelInfo {
+    pub name: String,
+    /// Size on disk in bytes
+    #[serde(default)]
+    pub size: u64,
+}
+
+/// Fetch the list of models installed in Ollama
+pub async fn list_installed_models() -> Result<Vec<OllamaModelInfo>> {
+    list_installed_models_at(&ollama_base_url()).await
+}
+
+/// Fetch the list of models installed on the Ollama server at `base_url`
+pub(crate) async fn list_installed_models_at(base_url: &str) -> Result<Vec<OllamaModelInfo>> {
     let client = get_availability_client();
     
-    // Try to list models and check if ours is in the list
-    let response = client
-        .get(&format!("{}/api/tags", OLLAMA_BASE_URL))
-        .send()
-        .await
+    let response = timeout(
+        Duration::from_secs(MODEL_CHECK_TIMEOUT),
+        client.get(format!("{}/api/tags", base_url)).send()
+    ).await
+        .context("Timed out connecting to Ollama API")?
         .context("Failed to connect to Ollama API")?;
     
     if !response.status().is_success() {
-        return Ok(false);
+        anyhow::bail!("Ollama returned error status: {}", response.status());
     }
     
     #[derive(serde::Deserialize)]
     struct ModelsResponse {
-        models: Vec<ModelInfo>,
-    }
-    
-    #[derive(serde::Deserialize)]
-    struct ModelInfo {
-        name: String,
+        models: Vec<OllamaModelInfo>,
     }
     
     let text = response.text().await
@@ -76,22 +91,78 @@ async fn check_model_availability(model: &str) -> Result<bool> {
     let models: ModelsResponse = serde_json::from_str(&text)
         .context("Failed to parse Ollama models list")?;
     
+    Ok(models.models)
+}
+
+async fn check_model_availability(model: &str) -> Result<bool> {
+    let models = list_installed_models().await?;
+    
     // Check if model exists (exact match or prefix match)
-    let exists = models.models.iter().any(|m| {
+    let exists = models.iter().any(|m| {
         m.name == model || m.name.starts_with(&format!("{}:", model))
     });
     
     Ok(exists)
 }
 
-/// Ensure a model is loaded/available, with optional preloading
-pub async fn ensure_model_loaded(model: &str) -> Result<(), ZosError> {
-    if model_exists_in_ollama(model).await {
+/// Fast pre-flight check that the Ollama server at `base_url` is answering.
+/// Fails with `STAGE_OLLAMA_UNREACHABLE` within a second when it is not running,
+/// so callers can give up instead of retrying against a dead endpoint.
+pub async fn ensure_ollama_reachable(base_url: &str) -> Result<(), ZosError> {
+    let client = get_availability_client();
+    let ping = timeout(
+        Duration::from_millis(OLLAMA_PING_TIMEOUT_MS),
+        client.get(format!("{}/api/tags", base_url)).send()
+    ).await;
+    
+    let reason = match ping {
+        Ok(Ok(response)) if response.status().is_success() => return Ok(()),
+        Ok(Ok(response)) => format!("returned status {}", response.status()),
+        Ok(Err(e)) => e.to_string(),
+        Err(_) => format!("no response within {}ms", OLLAMA_PING_TIMEOUT_MS),
+    };
+    tracing::warn!(url = %base_url, reason = %reason, "Ollama is unreachable");
+    Err(ZosError::new(
+        format!("Ollama is not reachable at {}", base_url),
+        STAGE_OLLAMA_UNREACHABLE
+    ).with_context(reason))
+}
+
+/// Ensure a model is loaded/available, skipping the check while a recent one
+/// in `state` confirmed the model (see `AVAILABILITY_TTL`)
+pub async fn ensure_model_available(state: &AppState, model: &str) -> Result<(), ZosError> {
+    cached_availability(state, model, AVAILABILITY_TTL, || check_model_loaded(model)).await
+}
+
+/// Run `check` unless `model` was confirmed within `ttl`. `check` returns
+/// whether the model is confirmed installed; only that is remembered, so a
+/// model that was just sent for pulling is checked again next time.
+pub async fn cached_availability<F, Fut>(state: &AppState, model: &str, ttl: Duration, check: F) -> Result<(), ZosError>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<bool, ZosError>>,
+{
+    if state.model_available_within(model, ttl) {
         return Ok(());
     }
+    if check().await? {
+        state.record_model_available(model);
+    }
+    Ok(())
+}
+
+/// Ensure a model is loaded/available: true if the model is installed,
+/// false if a pull was started instead
+async fn check_model_loaded(model: &str) -> Result<bool, ZosError> {
+    ensure_ollama_reachable(&ollama_base_url()).await
+        .map_err(|e| e.with_model(model.to_string()))?;
+    
+    if model_exists_in_ollama(model).await {
+        return Ok(true);
+    }
     
     // Try to pull the model (this is async and may take a while)
-    log_info(&format!("[Availability] Attempting to pull model '{}'", model));
+    tracing::info!(model = %model, "Attempting to pull model");
     
     let pull_result = timeout(
         Duration::from_secs(30), // Give it 30 seconds to start pulling
@@ -100,19 +171,19 @@ pub async fn ensure_model_loaded(model: &str) -> Result<(), ZosError> {
     
     match pull_result {
         Ok(Ok(_)) => {
-            log_info(&format!("[Availability] Successfully pulled model '{}'", model));
-            Ok(())
+            tracing::info!(model = %model, "Successfully pulled model");
+            Ok(false)
         }
         Ok(Err(e)) => {
             Err(ZosError::new(
                 format!("Failed to load model '{}': {}", model, e),
-                "model_availability"
+                STAGE_MODEL_UNAVAILABLE
             ).with_model(model.to_string()))
         }
         Err(_) => {
             Err(ZosError::new(
                 format!("Timeout waiting for model '{}' to load", model),
-                "model_availability"
+                STAGE_MODEL_UNAVAILABLE
             ).with_model(model.to_string()))
         }
     }
@@ -122,7 +193,7 @@ async fn pull_model(model: &str) -> Result<()> {
     let client = get_availability_client();
     
     let response = client
-        .post(&format!("{}/api/pull", OLLAMA_BASE_URL))
+        .post(format!("{}/api/pull", ollama_base_url()))
         .json(&serde_json::json!({
             "name": model
         }))
@@ -1,4 +1,4 @@
-use serde::de::DeserializeOwned;
+use crate::config::models::SamplingOptions;
 use crate::models::deepseek::DeepSeekModel;
 use crate::models::qwen_math::QwenMathModel;
 use crate::models::qwen_instruct::QwenInstructModel;
@@ -12,36 +12,19 @@ pub enum LocalModel {
 }
 
 impl LocalModel {
-    pub fn name(&self) -> &'static str {
+    pub async fn call_text(&self, prompt: &str, options: &SamplingOptions) -> anyhow::Result<String> {
         match self {
-            LocalModel::DeepSeek(m) => m.name(),
-            LocalModel::QwenMath(m) => m.name(),
-            LocalModel::QwenInstruct(m) => m.name(),
+            LocalModel::DeepSeek(m) => m.call_text(prompt, options).await,
+            LocalModel::QwenMath(m) => m.call_text(prompt, options).await,
+            LocalModel::QwenInstruct(m) => m.call_text(prompt, options).await,
         }
     }
 
-    pub async fn call_json<T: DeserializeOwned>(&self, prompt: &str) -> anyhow::Result<T> {
+    pub async fn healthcheck(&self) -> bool {
         match self {
-            LocalModel::DeepSeek(m) => m.call_json(prompt).await,
-            LocalModel::QwenMath(m) => m.call_json(prompt).await,
-            LocalModel::QwenInstruct(m) => m.call_json(prompt).await,
-        }
-    }
-    
-
-    pub async fn call_text(&self, prompt: &str) -> anyhow::Result<String> {
-        match self {
-            LocalModel::DeepSeek(m) => m.call_text(prompt).await,
-            LocalModel::QwenMath(m) => m.call_text(prompt).await,
-            LocalModel::QwenInstruct(m) => m.call_text(prompt).await,
-        }
-    }
-
-    pub fn healthcheck(&self) -> bool {
-        match self {
-            LocalModel::DeepSeek(m) => m.healthcheck(),
-            LocalModel::QwenMath(m) => m.healthcheck(),
-            LocalModel::QwenInstruct(m) => m.healthcheck(),
+            LocalModel::DeepSeek(m) => m.healthcheck().await,
+            LocalModel::QwenMath(m) => m.healthcheck().await,
+            LocalModel::QwenInstruct(m) => m.healthcheck().await,
         }
     }
 }
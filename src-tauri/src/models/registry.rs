@@ -4,38 +4,43 @@ use crate::models::base::LocalModel;
 use crate::models::deepseek::DeepSeekModel;
 use crate::models::qwen_math::QwenMathModel;
 use crate::models::qwen_instruct::QwenInstructModel;
-use crate::config::models::get_model_config;
+use crate::config::models::{get_model_config, ModelConfig};
+use parking_lot::RwLock;
 
-lazy_static! {
-    pub static ref MODEL_REGISTRY: HashMap<String, LocalModel> = {
-        let mut m = HashMap::new();
-        let config = get_model_config();
-        
-        // Register models based on config
-        m.insert(
-            config.proof_model.clone(),
-            LocalModel::DeepSeek(DeepSeekModel::new("deepseek-r1:7b"))
-        );
-        m.insert(
-            config.problem_model.clone(),
-            LocalModel::QwenMath(QwenMathModel::new("qwen2-math:7b"))
-        );
-        m.insert(
-            config.general_model.clone(),
-            LocalModel::QwenInstruct(QwenInstructModel::new("qwen2.5:7b-instruct"))
-        );
-        
-        // Also register common aliases
-        m.insert("deepseek-r1:7b".to_string(), LocalModel::DeepSeek(DeepSeekModel::new("deepseek-r1:7b")));
-        m.insert("qwen2-math:7b".to_string(), LocalModel::QwenMath(QwenMathModel::new("qwen2-math:7bh")));
-        m.insert("qwen2.5:7b-instruct".to_string(), LocalModel::QwenInstruct(QwenInstructModel::new("qwen2.5:7b-instruct")));
-        
-        m
+fn build_registry(config: &ModelConfig) -> HashMap<String, LocalModel> {
+    let mut m = HashMap::new();
+    // Each model is keyed by the name it calls Ollama with
+    let mut register = |name: &str, model: fn(String) -> LocalModel| {
+        m.insert(name.to_string(), model(name.to_string()));
     };
+    
+    // Register models based on config
+    register(&config.proof_model, |name| LocalModel::DeepSeek(DeepSeekModel::new(name)));
+    register(&config.problem_model, |name| LocalModel::QwenMath(QwenMathModel::new(name)));
+    register(&config.general_model, |name| LocalModel::QwenInstruct(QwenInstructModel::new(name)));
+    
+    // Also register common aliases
+    register("deepseek-r1:7b", |name| LocalModel::DeepSeek(DeepSeekModel::new(name)));
+    register("qwen2-math:7b", |name| LocalModel::QwenMath(QwenMathModel::new(name)));
+    register("qwen2.5:7b-instruct", |name| LocalModel::QwenInstruct(QwenInstructModel::new(name)));
+    
+    m
+}
+
+lazy_static! {
+    pub static ref MODEL_REGISTRY: RwLock<HashMap<String, LocalModel>> =
+        RwLock::new(build_registry(&get_model_config()));
+}
+
+/// Rebuild the registry from the active model config.
+/// Requests that already resolved a `LocalModel` keep their (cloned) instance.
+pub fn refresh_registry() {
+    let registry = build_registry(&get_model_config());
+    *MODEL_REGISTRY.write() = registry;
 }
 
 pub fn get_model(name: &str) -> Option<LocalModel> {
-    MODEL_REGISTRY.get(name).cloned()
+    MODEL_REGISTRY.read().get(name).cloned()
 }
 
 /// Check if a model exists in Ollama by calling the API
@@ -43,11 +48,11 @@ pub fn model_exists_in_ollama(model: &str) -> bool {
     // Try a simple healthcheck by making a minimal request
     // For now, we'll assume models exist if they're in the registry
     // TODO: Implement actual Ollama API check
-    MODEL_REGISTRY.contains_key(model)
+    MODEL_REGISTRY.read().contains_key(model)
 }
 
 /// Get all available model names
 pub fn get_available_models() -> Vec<String> {
-    MODEL_REGISTRY.keys().cloned().collect()
+    MODEL_REGISTRY.read().keys().cloned().collect()
 }
 
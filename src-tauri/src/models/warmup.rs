@@ -2,7 +2,11 @@
 use crate::config::models::get_model_config;
 use crate::models::availability::model_exists_in_ollama;
 use crate::logging::log_info;
-use tokio::time::Instant;
+use crate::pipelines::ollama::preload_model;
+use tokio::time::{Duration, Instant};
+
+/// Upper bound on loading a single model's weights during warm-up
+const WARMUP_LOAD_TIMEOUT_SECS: u64 = 60;
 
 /// Warm up all configured models with a lightweight ping
 pub async fn warmup_models() {
@@ -27,21 +31,32 @@ pub async fn warmup_models() {
     }
 }
 
-/// Warm up a single model with a lightweight check
+/// Warm up a single model: check it exists, then load its weights so the
+/// first real request doesn't pay the cold-start cost. Best-effort and time-boxed.
 async fn warmup_single_model(model: &str) -> bool {
     let start = Instant::now();
     
-    // Just check if model exists (lightweight operation)
     let exists = model_exists_in_ollama(model).await;
+    let exists_ms = start.elapsed().as_millis() as u64;
     
-    let elapsed_ms = start.elapsed().as_millis() as u64;
-    
-    if exists {
-        log_info(&format!("[Warmup] Warmed up model '{}' in {}ms", model, elapsed_ms));
-    } else {
-        log_info(&format!("[Warmup] Model '{}' not available (checked in {}ms)", model, elapsed_ms));
+    if !exists {
+        tracing::info!(model = model, exists_ms = exists_ms, "[Warmup] Model not available");
+        return false;
+    }
+    tracing::info!(model = model, exists_ms = exists_ms, "[Warmup] Model exists, loading weights");
+    
+    let load_start = Instant::now();
+    let loaded = preload_model(model, Duration::from_secs(WARMUP_LOAD_TIMEOUT_SECS)).await;
+    let load_ms = load_start.elapsed().as_millis() as u64;
+    
+    match loaded {
+        Ok(()) => {
+            tracing::info!(model = model, exists_ms = exists_ms, load_ms = load_ms, "[Warmup] Model loaded");
+            true
+        }
+        Err(e) => {
+            tracing::warn!(model = model, load_ms = load_ms, error = %e, "[Warmup] Model exists but failed to load");
+            false
+        }
     }
-    
-    exists
 }
-
@@ -0,0 +1,85 @@
+use std::path::{Component, Path, PathBuf};
+use crate::config::models::problems_dir_override;
+
+/// Bundle identifier, used as the app data folder name
+pub const APP_IDENTIFIER: &str = "com.zacnwo.zos";
+/// Environment variable that moves the whole app data root
+pub const DATA_ROOT_ENV: &str = "ZOS_DATA_DIR";
+
+/// Platform app data folder for zOS, if the platform's base folder is known
+fn platform_app_data_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "macos")]
+    {
+        if let Some(home) = std::env::var_os("HOME") {
+            return Some(PathBuf::from(home).join("Library/Application Support").join(APP_IDENTIFIER));
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        if let Some(appdata) = std::env::var_os("APPDATA") {
+            return Some(PathBuf::from(appdata).join(APP_IDENTIFIER));
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(home) = std::env::var_os("HOME") {
+            return Some(PathBuf::from(home).join(".local/share").join(APP_IDENTIFIER));
+        }
+    }
+
+    None
+}
+
+/// Pick the app data root: a non-empty environment override wins over the
+/// platform folder. With neither, paths are relative to the working directory.
+pub fn resolve_app_data_dir(env_value: Option<&str>, platform: Option<PathBuf>) -> PathBuf {
+    env_value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or(platform)
+        .unwrap_or_default()
+}
+
+/// Root of everything zOS stores: `models.toml`, `skills.json`, `logs/`,
+/// `problems/` and `data/`
+pub fn app_data_dir() -> PathBuf {
+    let env_value = std::env::var(DATA_ROOT_ENV).ok();
+    resolve_app_data_dir(env_value.as_deref(), platform_app_data_dir())
+}
+
+/// Sessions, plans, caches and other generated state
+pub fn data_dir() -> PathBuf {
+    app_data_dir().join("data")
+}
+
+/// Problem bank, unless `ZOS_PROBLEMS_DIR` / `problems_dir` points elsewhere
+pub fn problems_dir() -> PathBuf {
+    problems_dir_override().unwrap_or_else(|| app_data_dir().join("problems"))
+}
+
+/// Rotating log files
+pub fn log_dir() -> PathBuf {
+    app_data_dir().join("logs")
+}
+
+/// `requested` as a path inside `root`: relative paths are taken from `root`,
+/// absolute ones must already be under it. None for anything that could land
+/// outside it, such as a `..` component, or for `root` itself.
+pub fn path_within(root: &Path, requested: &str) -> Option<PathBuf> {
+    let requested = Path::new(requested.trim());
+    if requested.components().any(|c| c == Component::ParentDir) {
+        return None;
+    }
+    let path = if requested.is_absolute() {
+        if root.as_os_str().is_empty() || !requested.starts_with(root) {
+            return None;
+        }
+        requested.to_path_buf()
+    } else {
+        root.join(requested)
+    };
+    (path.file_name().is_some() && path != root).then_some(path)
+}
@@ -3,3 +3,4 @@ pub mod router;
 pub mod ollama;
 pub mod ollama_utils;
 pub mod perf;
+pub mod schema;
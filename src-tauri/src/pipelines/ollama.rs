@@ -1,11 +1,12 @@
 use reqwest::Client;
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use anyhow::{Result, Context};
-use crate::pipelines::ollama_utils;
 use crate::pipelines::perf;
 use tokio::time::{timeout, Duration};
 use crate::logging::{log_model_call, log_timeout};
 use std::sync::OnceLock;
+use crate::config::models::{get_model_config, ollama_base_url, SamplingOptions};
+use crate::error::{ZosError, STAGE_MODEL_CALL, STAGE_MODEL_UNAVAILABLE, STAGE_RESPONSE_TOO_LARGE};
 
 const DEFAULT_TIMEOUT_SECS: u64 = 60; // 60 seconds default timeout
 
@@ -23,11 +24,73 @@ fn get_http_client() -> &'static Client {
     })
 }
 
+/// Rough token count for a response (about 4 characters per token)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Response size and generation speed of one model call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelThroughput {
+    pub model: String,
+    pub response_chars: usize,
+    pub estimated_tokens: usize,
+    /// Whole call, from sending the request to the end of the streamed response
+    pub latency_ms: u64,
+    pub tokens_per_second: f64,
+}
+
+impl ModelThroughput {
+    pub fn new(model: &str, response: &str, latency_ms: u64) -> Self {
+        let estimated_tokens = estimate_tokens(response);
+        let tokens_per_second = if latency_ms == 0 {
+            0.0
+        } else {
+            estimated_tokens as f64 * 1000.0 / latency_ms as f64
+        };
+        ModelThroughput {
+            model: model.to_string(),
+            response_chars: response.chars().count(),
+            estimated_tokens,
+            latency_ms,
+            tokens_per_second,
+        }
+    }
+}
+
+/// Requests sent by `http_diagnostics`
+pub const DIAGNOSTIC_PINGS: usize = 3;
+
+/// Timing of one diagnostic `/api/tags` request through the shared client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpPing {
+    /// Until response headers arrived; includes TCP setup unless a pooled
+    /// connection was reused, so later pings should be much faster than the first
+    pub connect_ms: f64,
+    /// Reading the response body
+    pub read_ms: f64,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+}
+
+/// Sequential ping timings against the Ollama server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpDiagnostics {
+    pub base_url: String,
+    pub pings: Vec<HttpPing>,
+}
+
 #[derive(Serialize)]
 struct OllamaRequest {
     model: String,
     prompt: String,
     stream: bool,
+    /// Left out to use the model's own defaults
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<SamplingOptions>,
+    /// JSON schema the response must follow
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<serde_json::Value>,
 }
 
 #[derive(Deserialize)]
@@ -37,15 +100,144 @@ struct OllamaResponse {
     done: bool,
 }
 
-/// Call an Ollama model and return the raw response text (with timeout)
-pub async fn call_ollama_model(model: &str, prompt: &str) -> Result<String> {
-    call_ollama_model_with_timeout(model, prompt, Duration::from_secs(DEFAULT_TIMEOUT_SECS)).await
+/// Shape of the line Ollama sends instead of a response when generation
+/// fails, e.g. `{"error": "model 'x' not found"}`
+#[derive(Deserialize)]
+struct OllamaError {
+    error: String,
+}
+
+/// Turn an Ollama error line into a `ZosError`. Missing models are not worth
+/// retrying; anything else is treated like any other failed call.
+fn ollama_error(model: &str, message: &str) -> ZosError {
+    let stage = if message.contains("not found") { STAGE_MODEL_UNAVAILABLE } else { STAGE_MODEL_CALL };
+    ZosError::new(format!("Ollama error for model '{}': {}", model, message), stage)
+        .with_model(model.to_string())
+}
+
+/// Collects the text of a streamed generate response, one chunk at a time,
+/// and stops once it grows past `max_bytes`
+pub(crate) struct StreamCollector<'a> {
+    model: &'a str,
+    max_bytes: usize,
+    /// Bytes of a line whose newline hasn't arrived yet
+    pending: Vec<u8>,
+    text: String,
+    done: bool,
+}
+
+impl<'a> StreamCollector<'a> {
+    pub(crate) fn new(model: &'a str, max_bytes: usize) -> Self {
+        StreamCollector { model, max_bytes, pending: Vec::new(), text: String::new(), done: false }
+    }
+
+    /// Add a chunk of the stream. Returns true once Ollama has sent its final line.
+    pub(crate) fn push(&mut self, chunk: &[u8]) -> Result<bool, ZosError> {
+        self.pending.extend_from_slice(chunk);
+        while !self.done {
+            let Some(newline) = self.pending.iter().position(|&b| b == b'\n') else {
+                break;
+            };
+            let line: Vec<u8> = self.pending.drain(..=newline).collect();
+            self.read_line(&line)?;
+        }
+        if self.text.len() > self.max_bytes || self.pending.len() > self.max_bytes {
+            return Err(ZosError::new(
+                format!("Model '{}' response exceeded {} bytes, stopped reading", self.model, self.max_bytes),
+                STAGE_RESPONSE_TOO_LARGE
+            ).with_model(self.model.to_string()));
+        }
+        Ok(self.done)
+    }
+
+    /// The response text, once the stream has ended
+    pub(crate) fn finish(mut self) -> Result<String, ZosError> {
+        if !self.done {
+            let rest = std::mem::take(&mut self.pending);
+            self.read_line(&rest)?;
+        }
+        Ok(self.text)
+    }
+
+    // One JSON object per line
+    fn read_line(&mut self, line: &[u8]) -> Result<(), ZosError> {
+        let Ok(line) = std::str::from_utf8(line) else {
+            return Ok(());
+        };
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+        if let Ok(res) = serde_json::from_str::<OllamaResponse>(line) {
+            self.text.push_str(&res.response);
+            self.done = res.done;
+        } else if let Ok(err) = serde_json::from_str::<OllamaError>(line) {
+            return Err(ollama_error(self.model, &err.error));
+        }
+        Ok(())
+    }
 }
 
-/// Call an Ollama model with a custom timeout
-pub async fn call_ollama_model_with_timeout(
+/// Call an Ollama model with explicit sampling options and, if given, a JSON
+/// schema constraining the response (default timeout)
+#[tracing::instrument(skip_all, fields(model = %model))]
+pub async fn call_ollama_model_with_options(
+    model: &str,
+    prompt: &str,
+    options: &SamplingOptions,
+    format: Option<&serde_json::Value>,
+) -> Result<String> {
+    call_with_schema_at(&ollama_base_url(), model, prompt, Some(options), format, Duration::from_secs(DEFAULT_TIMEOUT_SECS)).await
+}
+
+/// Whether `error` came from a call running out of time: our own deadline
+/// (`Elapsed`) or reqwest's
+pub fn is_timeout(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause.is::<tokio::time::error::Elapsed>()
+            || cause.downcast_ref::<reqwest::Error>().is_some_and(reqwest::Error::is_timeout)
+    })
+}
+
+/// Whether Ollama answered `error` with 400 Bad Request, which is how it
+/// rejects a request it can't serve as sent (e.g. an unsupported `format`)
+pub fn is_bad_request(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause.downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.status() == Some(reqwest::StatusCode::BAD_REQUEST))
+    })
+}
+
+/// Call with `format` set to `schema`. If Ollama rejects the request (older
+/// versions don't accept a schema `format`), try once more without it; any
+/// other failure is returned as is.
+pub(crate) async fn call_with_schema_at(
+    base_url: &str,
+    model: &str,
+    prompt: &str,
+    options: Option<&SamplingOptions>,
+    schema: Option<&serde_json::Value>,
+    timeout_duration: Duration
+) -> Result<String> {
+    let Some(schema) = schema else {
+        return call_ollama_model_at(base_url, model, prompt, options, None, timeout_duration).await;
+    };
+    match call_ollama_model_at(base_url, model, prompt, options, Some(schema), timeout_duration).await {
+        Err(e) if is_bad_request(&e) => {
+            tracing::warn!(model = %model, error = %e, "Schema-constrained call failed, retrying without a schema");
+            call_ollama_model_at(base_url, model, prompt, options, None, timeout_duration).await
+        }
+        result => result,
+    }
+}
+
+/// Call a model on the Ollama server at `base_url`, with sampling `options`
+/// and a response schema (`format`) if given
+pub(crate) async fn call_ollama_model_at(
+    base_url: &str,
     model: &str, 
     prompt: &str, 
+    options: Option<&SamplingOptions>,
+    format: Option<&serde_json::Value>,
     timeout_duration: Duration
 ) -> Result<String> {
     let _perf = perf::PerfTimer::new("ollama_call");
@@ -55,43 +247,45 @@ pub async fn call_ollama_model_with_timeout(
         let client = get_http_client();
         let request_start = std::time::Instant::now();
 
-        let response = client
-            .post("http://localhost:11434/api/generate")
+        let mut response = client
+            .post(format!("{}/api/generate", base_url))
             .json(&OllamaRequest {
                 model: model.to_string(),
                 prompt: prompt.to_string(),
                 stream: true, // Enable streaming for better UX
+                options: options.cloned(),
+                format: format.cloned(),
             })
             .send()
             .await
             .with_context(|| format!("Failed to connect to Ollama API for model '{}'", model))?;
+        if let Err(status_error) = response.error_for_status_ref() {
+            // Other statuses carry an error line the stream reader reports
+            if response.status() == reqwest::StatusCode::BAD_REQUEST {
+                let body = response.text().await.unwrap_or_default();
+                let message = serde_json::from_str::<OllamaError>(&body).map(|e| e.error).unwrap_or(body);
+                return Err(anyhow::Error::new(status_error)
+                    .context(format!("Ollama rejected the request for model '{}': {}", model, message)));
+            }
+        }
 
         let connect_ms = request_start.elapsed().as_millis() as u64;
         perf::log_perf_with_context("ollama_connect", connect_ms, model);
 
-        // For now, read the full response (streaming can be enhanced later)
+        // Read the stream as it arrives, so a runaway generation is cut off
+        // at `max_response_bytes` rather than buffered whole
         let read_start = std::time::Instant::now();
-        let text = response.text().await
-            .with_context(|| format!("Failed to read response from model '{}'", model))?;
-        let read_ms = read_start.elapsed().as_millis() as u64;
-        perf::log_perf_with_context("ollama_read", read_ms, model);
-        
-        // Parse streaming response (one JSON object per line)
-        let parse_start = std::time::Instant::now();
-        let mut full_response = String::new();
-        for line in text.lines() {
-            if line.trim().is_empty() {
-                continue;
-            }
-            if let Ok(res) = serde_json::from_str::<OllamaResponse>(line) {
-                full_response.push_str(&res.response);
-                if res.done {
-                    break;
-                }
+        let mut collector = StreamCollector::new(model, get_model_config().max_response_bytes);
+        while let Some(chunk) = response.chunk().await
+            .with_context(|| format!("Failed to read response from model '{}'", model))?
+        {
+            if collector.push(&chunk)? {
+                break;
             }
         }
-        let parse_ms = parse_start.elapsed().as_millis() as u64;
-        perf::log_perf_with_context("ollama_parse_stream", parse_ms, model);
+        let full_response = collector.finish()?;
+        let read_ms = read_start.elapsed().as_millis() as u64;
+        perf::log_perf_with_context("ollama_read", read_ms, model);
         
         if full_response.is_empty() {
             anyhow::bail!("Model '{}' returned empty response", model);
@@ -106,6 +300,14 @@ pub async fn call_ollama_model_with_timeout(
         Ok(Ok(response)) => {
             perf::log_perf_with_context("ollama_call", latency_ms, model);
             log_model_call(model, "call", true, Some(latency_ms));
+            let throughput = ModelThroughput::new(model, &response, latency_ms);
+            tracing::info!(
+                model = %model,
+                response_chars = throughput.response_chars,
+                estimated_tokens = throughput.estimated_tokens,
+                tokens_per_second = throughput.tokens_per_second,
+                "Model call throughput"
+            );
             Ok(response)
         }
         Ok(Err(e)) => {
@@ -113,42 +315,83 @@ pub async fn call_ollama_model_with_timeout(
             log_model_call(model, "call", false, Some(latency_ms));
             Err(e)
         }
-        Err(_) => {
+        Err(elapsed) => {
             perf::log_perf_with_context("ollama_call_timeout", latency_ms, model);
             log_timeout(model, timeout_duration.as_secs());
-            anyhow::bail!("Model '{}' call timed out after {}s", model, timeout_duration.as_secs())
+            Err(anyhow::Error::new(elapsed)
+                .context(format!("Model '{}' call timed out after {}s", model, timeout_duration.as_secs())))
         }
     }
 }
 
-/// Call an Ollama model and parse the response as JSON into a typed struct (with timeout)
-pub async fn call_ollama_json<T: DeserializeOwned>(model: &str, prompt: &str) -> Result<T> {
-    call_ollama_json_with_timeout(model, prompt, Duration::from_secs(DEFAULT_TIMEOUT_SECS)).await
+/// Send `count` sequential `/api/tags` requests through the pooled client and
+/// time each one, to tell connection setup apart from model inference
+pub async fn http_diagnostics(count: usize) -> HttpDiagnostics {
+    http_diagnostics_at(&ollama_base_url(), count).await
 }
 
-/// Call an Ollama model and parse JSON with custom timeout
-pub async fn call_ollama_json_with_timeout<T: DeserializeOwned>(
-    model: &str, 
-    prompt: &str,
-    timeout_duration: Duration
-) -> Result<T> {
-    let _perf = perf::PerfTimer::new("ollama_json");
-    let raw_response = call_ollama_model_with_timeout(model, prompt, timeout_duration).await?;
+pub(crate) async fn http_diagnostics_at(base_url: &str, count: usize) -> HttpDiagnostics {
+    let client = get_http_client();
+    let url = format!("{}/api/tags", base_url);
+    let mut pings = Vec::with_capacity(count);
     
-    // Extract JSON from the response (handle markdown, code blocks, etc.)
-    let extract_start = std::time::Instant::now();
-    let json_str = ollama_utils::extract_json(&raw_response)
-        .with_context(|| format!("Failed to extract JSON from model '{}' response", model))?;
-    let extract_ms = extract_start.elapsed().as_millis() as u64;
-    perf::log_perf_with_context("json_extract", extract_ms, model);
+    for _ in 0..count {
+        let start = std::time::Instant::now();
+        let ping = match client.get(&url).send().await {
+            Ok(response) => {
+                let connect_ms = start.elapsed().as_secs_f64() * 1000.0;
+                let status = response.status().as_u16();
+                let read_start = std::time::Instant::now();
+                let body = response.bytes().await;
+                HttpPing {
+                    connect_ms,
+                    read_ms: read_start.elapsed().as_secs_f64() * 1000.0,
+                    status: Some(status),
+                    error: body.err().map(|e| e.to_string()),
+                }
+            }
+            Err(e) => HttpPing {
+                connect_ms: start.elapsed().as_secs_f64() * 1000.0,
+                read_ms: 0.0,
+                status: None,
+                error: Some(e.to_string()),
+            },
+        };
+        perf::log_perf_with_context("http_diagnostic_ping", ping.connect_ms as u64, base_url);
+        pings.push(ping);
+    }
+    
+    HttpDiagnostics { base_url: base_url.to_string(), pings }
+}
+
+/// Load a model's weights into memory without generating anything.
+/// Ollama treats a generate request with an empty prompt as a preload.
+pub async fn preload_model(model: &str, timeout_duration: Duration) -> Result<()> {
+    let client = get_http_client();
+    
+    let response = timeout(timeout_duration, client
+        .post(format!("{}/api/generate", ollama_base_url()))
+        .json(&OllamaRequest {
+            model: model.to_string(),
+            prompt: String::new(),
+            stream: false,
+            options: None,
+            format: None,
+        })
+        .send())
+        .await
+        .with_context(|| format!("Preloading model '{}' timed out after {}s", model, timeout_duration.as_secs()))?
+        .with_context(|| format!("Failed to connect to Ollama API for model '{}'", model))?;
     
-    // Parse the JSON
-    let parse_start = std::time::Instant::now();
-    let parsed: T = serde_json::from_str(&json_str)
-        .with_context(|| format!("Model '{}' returned invalid JSON. Raw: {}", model, json_str))?;
-    let parse_ms = parse_start.elapsed().as_millis() as u64;
-    perf::log_perf_with_context("json_parse", parse_ms, model);
+    if !response.status().is_success() {
+        anyhow::bail!("Ollama returned error status {} while preloading '{}'", response.status(), model);
+    }
     
-    Ok(parsed)
+    Ok(())
 }
 
+/// Parse a schema-constrained response as-is. Skips the sanitizer, which
+/// would otherwise rewrite LaTeX and whitespace inside valid JSON strings.
+pub fn parse_structured_response<T: DeserializeOwned>(raw: &str) -> Option<T> {
+    serde_json::from_str(raw.trim()).ok()
+}
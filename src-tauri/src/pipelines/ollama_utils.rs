@@ -1,3 +1,7 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use crate::state::app::AppState;
+
 /// Utility functions for cleaning and parsing Ollama responses
 
 /// Check if raw output appears to be truncated
@@ -41,9 +45,43 @@ pub fn is_truncated(raw: &str) -> bool {
     brace_count != 0 || bracket_count != 0 || in_string
 }
 
+const THINK_OPEN: &str = "<think>";
+const THINK_CLOSE: &str = "</think>";
+
+/// Remove `<think>...</think>` reasoning blocks (emitted by deepseek-r1).
+/// An unclosed trailing `<think>` drops the rest of the text. A stray
+/// `</think>` with no opening tag is removed on its own, keeping the text
+/// on both sides of it.
+pub fn strip_think_blocks(raw: &str) -> String {
+    let mut text = raw;
+    let mut prefix = "";
+    if let Some(close) = text.find(THINK_CLOSE) {
+        if text[..close].find(THINK_OPEN).is_none() {
+            prefix = &text[..close];
+            text = &text[close + THINK_CLOSE.len()..];
+        }
+    }
+    
+    let mut result = String::with_capacity(raw.len());
+    result.push_str(prefix);
+    while let Some(open) = text.find(THINK_OPEN) {
+        result.push_str(&text[..open]);
+        let after_open = &text[open + THINK_OPEN.len()..];
+        match after_open.find(THINK_CLOSE) {
+            Some(close) => text = &after_open[close + THINK_CLOSE.len()..],
+            None => {
+                text = "";
+                break;
+            }
+        }
+    }
+    result.push_str(text);
+    result
+}
+
 /// Sanitize raw model output before JSON extraction
 pub fn sanitize_raw_output(raw: &str) -> String {
-    let mut sanitized = raw.to_string();
+    let mut sanitized = strip_think_blocks(raw);
     
     // Remove markdown code fences
     sanitized = sanitized.replace("```json", "");
@@ -181,11 +219,24 @@ pub fn remove_trailing_commas(json: &str) -> String {
     let mut result = String::with_capacity(json.len());
     let chars: Vec<char> = json.chars().collect();
     let mut i = 0;
+    let mut in_string = false;
+    let mut escape_next = false;
     
     while i < chars.len() {
         let ch = chars[i];
         
-        if ch == ',' {
+        // Commas inside string values are content, never trailing commas
+        if in_string {
+            if escape_next {
+                escape_next = false;
+            } else if ch == '\\' {
+                escape_next = true;
+            } else if ch == '"' {
+                in_string = false;
+            }
+        } else if ch == '"' {
+            in_string = true;
+        } else if ch == ',' {
             // Look ahead to see if this is a trailing comma
             let mut j = i + 1;
             // Skip whitespace
@@ -783,3 +834,70 @@ pub fn extract_json(text: &str) -> anyhow::Result<String> {
     )
 }
 
+/// `extract_json`, remembering results in `state.extraction_cache` so the
+/// same raw text (a re-parse, or reprocessed cached output) isn't scanned
+/// again. Failed extractions are not cached.
+pub fn extract_json_cached(state: &AppState, text: &str) -> anyhow::Result<String> {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    let key = hasher.finish();
+
+    if let Some((raw, json)) = state.extraction_cache.lock().get(&key) {
+        if raw == text {
+            state.metrics.record_extraction_cache_hit();
+            return Ok(json.clone());
+        }
+    }
+    let json = extract_json(text)?;
+    state.extraction_cache.lock().put(key, (text.to_string(), json.clone()));
+    Ok(json)
+}
+
+/// Ways to close off a JSON object that was cut short, longest first: each
+/// backs off to the end of a complete value and closes the brackets still
+/// open there. Only candidates that parse are returned, and nothing is
+/// returned when the object is complete (`extract_json` handles those).
+pub fn close_truncated_json(text: &str) -> Vec<String> {
+    let start = match text.find('{') {
+        Some(start) => start,
+        None => return Vec::new(),
+    };
+    let body = &text[start..];
+
+    // (end of a complete value, closers for the containers open there)
+    let mut cut_points: Vec<(usize, String)> = Vec::new();
+    let mut open: Vec<char> = Vec::new();
+    let mut in_string = false;
+    let mut escape_next = false;
+
+    for (i, ch) in body.char_indices() {
+        if escape_next {
+            escape_next = false;
+            continue;
+        }
+        match ch {
+            '\\' if in_string => escape_next = true,
+            '"' => in_string = !in_string,
+            '{' | '[' if !in_string => {
+                open.push(if ch == '{' { '}' } else { ']' });
+                cut_points.push((i + 1, open.iter().rev().collect()));
+            }
+            '}' | ']' if !in_string => {
+                open.pop();
+                if open.is_empty() {
+                    return Vec::new();
+                }
+                cut_points.push((i + 1, open.iter().rev().collect()));
+            }
+            ',' if !in_string => cut_points.push((i, open.iter().rev().collect())),
+            _ => {}
+        }
+    }
+
+    cut_points
+        .into_iter()
+        .rev()
+        .map(|(end, closers)| format!("{}{}", body[..end].trim_end(), closers))
+        .filter(|candidate| serde_json::from_str::<serde_json::Value>(candidate).is_ok())
+        .collect()
+}
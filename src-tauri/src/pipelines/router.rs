@@ -1,9 +1,15 @@
-use crate::config::models::get_model_config;
+use crate::config::models::{get_model_config, SamplingConfig, SamplingOptions};
+use crate::pipelines::schema::OutputSchema;
 use crate::models::registry::{get_model, get_available_models};
 use crate::models::base::LocalModel;
-use crate::models::availability::ensure_model_loaded;
-use crate::error::ZosError;
+use crate::models::availability::ensure_model_available;
+use crate::error::{
+    ZosError, STAGE_CACHE, STAGE_JSON_EXTRACT, STAGE_JSON_PARSE, STAGE_JSON_REPAIR, STAGE_JSON_REPAIR_EXTRACT,
+    STAGE_JSON_REPAIR_PARSE, STAGE_MODEL_CALL, STAGE_OFFLINE, STAGE_OLLAMA_UNREACHABLE, STAGE_RETRY_EXHAUSTED,
+    STAGE_ROUTING, STAGE_TIMEOUT, STAGE_TIMEOUT_TRUNCATION, STAGE_TRUNCATED, STAGE_TRUNCATED_DETECTED,
+};
 use crate::cache::{get_cached, cache_response};
+use crate::circuit_breaker::ExponentialBackoff;
 use crate::state::app::AppState;
 use chrono::Utc;
 use tokio::time::Instant;
@@ -15,6 +21,17 @@ pub enum TaskType {
     General,
 }
 
+impl TaskType {
+    /// The configured sampling options for this task
+    pub fn sampling(self, config: &SamplingConfig) -> &SamplingOptions {
+        match self {
+            TaskType::ProofAnalysis => &config.proof_analysis,
+            TaskType::ProblemGeneration => &config.problem_generation,
+            TaskType::General => &config.general,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RouteDecision {
     pub selected: String,
@@ -123,10 +140,29 @@ pub fn get_model_for_task(task: TaskType) -> Option<LocalModel> {
     get_model(&decision.selected)
 }
 
-/// Unified query function with retry, fallback, caching, and timeouts
-pub async fn zos_query<T: serde::de::DeserializeOwned + serde::Serialize>(
+/// Unified query function with retry, fallback, caching, and timeouts.
+/// `prompt` is sent as is; its leading `system_prompt` (empty if it has
+/// none) is left out of the cache key.
+#[tracing::instrument(skip_all, fields(task = ?task))]
+pub async fn zos_query<T: serde::de::DeserializeOwned + serde::Serialize + OutputSchema>(
+    state: &AppState,
+    task: TaskType,
+    system_prompt: &str,
+    prompt: String,
+) -> Result<T, ZosError> {
+    // Errors are counted here rather than in the `From` conversions, which
+    // have no access to the app state's metrics
+    let result = zos_query_inner(state, task, system_prompt, prompt).await;
+    if result.is_err() {
+        state.metrics.record_error();
+    }
+    result
+}
+
+async fn zos_query_inner<T: serde::de::DeserializeOwned + serde::Serialize + OutputSchema>(
     state: &AppState,
     task: TaskType,
+    system_prompt: &str,
     prompt: String,
 ) -> Result<T, ZosError> {
     use crate::pipelines::perf;
@@ -136,8 +172,10 @@ pub async fn zos_query<T: serde::de::DeserializeOwned + serde::Serialize>(
     let decision = model_for_task(task);
     let routing_ms = routing_start.elapsed().as_millis() as u64;
     perf::log_perf("routing", routing_ms);
+    state.metrics.record_routing_time(routing_ms);
     
     let primary_model = decision.selected.clone();
+    let mut fallback_used = FallbackOnce::new(state);
     
     tracing::debug!(
         task = ?task,
@@ -146,7 +184,7 @@ pub async fn zos_query<T: serde::de::DeserializeOwned + serde::Serialize>(
     );
     
     // Check cache first
-    if let Some(cached) = get_cached::<T>(state, &primary_model, &prompt) {
+    if let Some(cached) = get_cached::<T>(state, &primary_model, system_prompt, &prompt) {
         let latency_ms = query_start.elapsed().as_millis() as u64;
         tracing::info!(
             task = ?task,
@@ -157,8 +195,16 @@ pub async fn zos_query<T: serde::de::DeserializeOwned + serde::Serialize>(
         return Ok(cached);
     }
     
+    if state.is_offline() {
+        return Err(offline_error(task));
+    }
+    
     // Ensure model is available
-    if let Err(e) = ensure_model_loaded(&primary_model).await {
+    if let Err(e) = ensure_model_available(state, &primary_model).await {
+        // No point trying the fallback when Ollama itself is down
+        if e.stage == STAGE_OLLAMA_UNREACHABLE {
+            return Err(e);
+        }
         // Try fallback
         if let Some(fallback_model) = decision.fallback.clone() {
             tracing::warn!(
@@ -166,41 +212,66 @@ pub async fn zos_query<T: serde::de::DeserializeOwned + serde::Serialize>(
                 fallback = %fallback_model,
                 "Primary model unavailable, trying fallback"
             );
-            if ensure_model_loaded(&fallback_model).await.is_ok() {
+            fallback_used.record();
+            if ensure_model_available(state, &fallback_model).await.is_ok() {
                 match try_model_with_retry::<T>(state, &fallback_model, &prompt, task, query_start).await {
                     Ok(result) => {
-                        cache_response(state, &fallback_model, &prompt, &result)
+                        cache_response(state, &fallback_model, system_prompt, &prompt, &result)
                             .map_err(|e| ZosError::new(
                                 format!("Failed to cache response: {}", e),
-                                "cache"
+                                STAGE_CACHE
                             ))?;
                         return Ok(result);
                     }
-                    Err((err, _)) => return Err(err.with_retry(false)),
+                    Err((err, _)) => return Err(fallback_exhausted(&primary_model, &fallback_model, err)),
                 }
             }
         }
         return Err(e);
     }
     
+    // Opt-in race mode: run primary and fallback concurrently, first valid JSON wins
+    if matches!(task, TaskType::ProofAnalysis) && get_model_config().race_proof_models {
+        if let Some(fallback_model) = decision.fallback.clone() {
+            let (result, winner) = race_models::<T>(state, &primary_model, &fallback_model, &prompt, task, query_start).await?;
+            if winner == fallback_model {
+                fallback_used.record();
+            }
+            cache_response(state, &winner, system_prompt, &prompt, &result)
+                .map_err(|e| ZosError::new(
+                    format!("Failed to cache response: {}", e),
+                    STAGE_CACHE
+                ))?;
+            return Ok(result);
+        }
+    }
+    
     // Try primary model with retry
     match try_model_with_retry::<T>(state, &primary_model, &prompt, task, query_start).await {
         Ok(result) => {
             // Cache the result
-            cache_response(state, &primary_model, &prompt, &result)
+            cache_response(state, &primary_model, system_prompt, &prompt, &result)
                 .map_err(|e| ZosError::new(
                     format!("Failed to cache response: {}", e),
-                    "cache"
+                    STAGE_CACHE
                 ))?;
             Ok(result)
         }
         Err((e, raw_response)) => {
+            // A cut-off response is regenerated below; what it got through
+            // is kept in case that fails too
+            let partial = truncated_partial::<T>(&e, raw_response.as_deref());
+            
+            // Repair and regeneration below count as a single fallback
+            if decision.fallback.is_some() {
+                fallback_used.record();
+            }
+            
             // If we have a raw response and JSON extraction failed, try repair with fallback
-            // BUT skip repair if truncated, timeout, or too large (regenerate instead)
+            // BUT skip repair if truncated or timed out (regenerate instead)
             if let (Some(raw), Some(fallback_model)) = (raw_response, decision.fallback.clone()) {
-                // Skip repair for truncation, timeout, or size issues
-                let should_repair = matches!(e.stage.as_str(), "json_extract" | "json_parse")
-                    && !matches!(e.stage.as_str(), "truncated" | "timeout_truncation" | "output_too_large");
+                // Skip repair for truncation or timeouts
+                let should_repair = matches!(e.stage.as_str(), STAGE_JSON_EXTRACT | STAGE_JSON_PARSE);
                 
                 if should_repair {
                     tracing::warn!(
@@ -209,13 +280,13 @@ pub async fn zos_query<T: serde::de::DeserializeOwned + serde::Serialize>(
                         raw_response_length = raw.len(),
                         "Primary model JSON extraction failed, attempting repair with fallback"
                     );
-                    if ensure_model_loaded(&fallback_model).await.is_ok() {
-                        match repair_json_with_fallback::<T>(state, &fallback_model, &raw, &prompt).await {
+                    if ensure_model_available(state, &fallback_model).await.is_ok() {
+                        match repair_json_with_fallback::<T>(state, &fallback_model, task, &raw, &prompt).await {
                             Ok(result) => {
-                                cache_response(state, &fallback_model, &prompt, &result)
+                                cache_response(state, &fallback_model, system_prompt, &prompt, &result)
                                     .map_err(|e| ZosError::new(
                                         format!("Failed to cache response: {}", e),
-                                        "cache"
+                                        STAGE_CACHE
                                     ))?;
                                 tracing::info!(
                                     primary = %primary_model,
@@ -226,7 +297,7 @@ pub async fn zos_query<T: serde::de::DeserializeOwned + serde::Serialize>(
                             }
                             Err(repair_err) => {
                                 // Check if repair detected truncation
-                                if repair_err.stage == "truncated_detected" {
+                                if repair_err.stage == STAGE_TRUNCATED_DETECTED {
                                     tracing::warn!(
                                         primary = %primary_model,
                                         fallback = %fallback_model,
@@ -250,213 +321,415 @@ pub async fn zos_query<T: serde::de::DeserializeOwned + serde::Serialize>(
                         primary = %primary_model,
                         fallback = %fallback_model,
                         error_stage = %e.stage,
-                        "Skipping repair (truncation/size/timeout), regenerating with fallback"
+                        "Skipping repair (truncation/timeout), regenerating with fallback"
                     );
                 }
             }
             
             // Standard fallback: try fallback model with original prompt
-            if let Some(fallback_model) = decision.fallback.clone() {
+            let outcome = if let Some(fallback_model) = decision.fallback.clone() {
                 tracing::warn!(
                     primary = %primary_model,
                     fallback = %fallback_model,
                     "Primary model failed, trying fallback with original prompt"
                 );
-                if ensure_model_loaded(&fallback_model).await.is_ok() {
+                if ensure_model_available(state, &fallback_model).await.is_ok() {
                     match try_model_with_retry::<T>(state, &fallback_model, &prompt, task, query_start).await {
                         Ok(result) => {
-                            cache_response(state, &fallback_model, &prompt, &result)
+                            cache_response(state, &fallback_model, system_prompt, &prompt, &result)
                                 .map_err(|e| ZosError::new(
                                     format!("Failed to cache response: {}", e),
-                                    "cache"
+                                    STAGE_CACHE
                                 ))?;
                             Ok(result)
                         }
-                        Err((fallback_err, _)) => Err(fallback_err.with_retry(false)),
+                        Err((fallback_err, _)) => Err(fallback_exhausted(&primary_model, &fallback_model, fallback_err)),
                     }
                 } else {
                     Err(e.with_retry(false))
                 }
             } else {
                 Err(e.with_retry(false))
+            };
+            
+            or_partial(outcome, partial, &primary_model)
+        }
+    }
+}
+
+/// Counts a query's use of its fallback model in the metrics at most once,
+/// however many fallback paths the query goes through
+struct FallbackOnce<'a> {
+    state: &'a AppState,
+    recorded: bool,
+}
+
+impl<'a> FallbackOnce<'a> {
+    fn new(state: &'a AppState) -> Self {
+        FallbackOnce { state, recorded: false }
+    }
+
+    fn record(&mut self) {
+        if !self.recorded {
+            self.recorded = true;
+            self.state.metrics.record_fallback();
+        }
+    }
+}
+
+/// Best partial result from the raw response behind `error`, if the
+/// response was truncated and `T` accepts partial results
+pub fn truncated_partial<T: OutputSchema>(error: &ZosError, raw_response: Option<&str>) -> Option<T> {
+    use crate::pipelines::ollama_utils;
+    
+    if error.stage != STAGE_TRUNCATED {
+        return None;
+    }
+    let sanitized = ollama_utils::sanitize_raw_output(raw_response?);
+    ollama_utils::close_truncated_json(&sanitized)
+        .into_iter()
+        .filter_map(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+        .find_map(T::from_partial)
+}
+
+/// `outcome` of the fallback paths, or `partial` (see `truncated_partial`)
+/// if they failed too. Partial results are returned but never cached.
+pub fn or_partial<T>(outcome: Result<T, ZosError>, partial: Option<T>, primary_model: &str) -> Result<T, ZosError> {
+    match (outcome, partial) {
+        (Err(err), Some(result)) => {
+            tracing::warn!(
+                primary = %primary_model,
+                error = %err,
+                "Returning partial result salvaged from truncated response"
+            );
+            Ok(result)
+        }
+        (outcome, _) => outcome,
+    }
+}
+
+/// Error for a request that needs a model while offline mode is on
+fn offline_error(task: TaskType) -> ZosError {
+    ZosError::new("Offline mode is on, so the model cannot be called", STAGE_OFFLINE)
+        .with_context(format!("task: {:?}", task))
+}
+
+/// Error for when both the primary and fallback models failed, as
+/// `STAGE_RETRY_EXHAUSTED` so callers can tell it from a single failure.
+/// Names both models in the message and keeps the last failure's stage in
+/// the context.
+pub fn fallback_exhausted(primary: &str, fallback: &str, last_err: ZosError) -> ZosError {
+    let last_stage = format!("last failure: {}", last_err.stage);
+    ZosError::new(
+        format!("Primary model '{}' and fallback '{}' both failed: {}", primary, fallback, last_err.message),
+        STAGE_RETRY_EXHAUSTED
+    )
+    .with_model(fallback.to_string())
+    .with_context(match last_err.context {
+        Some(context) => format!("{}; {}", last_stage, context),
+        None => last_stage,
+    })
+}
+
+/// Run the primary and fallback models concurrently and return the first valid
+/// result along with the model that produced it. If one side fails, wait for the
+/// other; the losing future is dropped, which cancels its in-flight request.
+async fn race_models<T: serde::de::DeserializeOwned + OutputSchema>(
+    state: &AppState,
+    primary_model: &str,
+    fallback_model: &str,
+    prompt: &str,
+    task: TaskType,
+    query_start: Instant,
+) -> Result<(T, String), ZosError> {
+    let primary = try_model_with_retry::<T>(state, primary_model, prompt, task, query_start);
+    let fallback = async {
+        ensure_model_available(state, fallback_model).await.map_err(|e| (e, None))?;
+        try_model_with_retry::<T>(state, fallback_model, prompt, task, query_start).await
+    };
+    tokio::pin!(primary);
+    tokio::pin!(fallback);
+    
+    tracing::info!(primary = %primary_model, fallback = %fallback_model, "Racing primary and fallback models");
+    
+    tokio::select! {
+        result = &mut primary => match result {
+            Ok(value) => Ok((value, primary_model.to_string())),
+            Err((e, _)) => {
+                tracing::warn!(primary = %primary_model, error = %e, "Primary lost race with error, waiting for fallback");
+                fallback.await
+                    .map(|value| (value, fallback_model.to_string()))
+                    .map_err(|(err, _)| fallback_exhausted(primary_model, fallback_model, err))
+            }
+        },
+        result = &mut fallback => match result {
+            Ok(value) => Ok((value, fallback_model.to_string())),
+            Err((e, _)) => {
+                tracing::warn!(fallback = %fallback_model, error = %e, "Fallback failed during race, waiting for primary");
+                primary.await
+                    .map(|value| (value, primary_model.to_string()))
+                    .map_err(|(err, _)| fallback_exhausted(primary_model, fallback_model, err))
+            }
+        },
+    }
+}
+
+/// Free-form text query: routes like `zos_query` but returns the model's raw text
+/// instead of parsing JSON. Falls back once to the fallback model on failure.
+/// Responses are not cached, since follow-up questions are rarely repeated verbatim.
+pub async fn zos_query_text(
+    state: &AppState,
+    task: TaskType,
+    prompt: String,
+) -> Result<String, ZosError> {
+    if state.is_offline() {
+        return Err(offline_error(task));
+    }
+    
+    let query_start = Instant::now();
+    let decision = model_for_task(task);
+    
+    let mut candidates = vec![decision.selected.clone()];
+    candidates.extend(decision.fallback.clone());
+    
+    let sampling = get_model_config().sampling;
+    let mut fallback_used = FallbackOnce::new(state);
+    let mut last_err = None;
+    for (i, model_name) in candidates.into_iter().enumerate() {
+        if i > 0 {
+            fallback_used.record();
+        }
+        let model = match get_model(&model_name) {
+            Some(model) => model,
+            None => {
+                last_err = Some(ZosError::new(
+                    format!("Model '{}' not found in registry", model_name),
+                    STAGE_ROUTING
+                ).with_model(model_name.clone()));
+                continue;
+            }
+        };
+        
+        if let Err(e) = ensure_model_available(state, &model_name).await {
+            tracing::warn!(task = ?task, model = %model_name, error = %e, "Model unavailable for text query");
+            let unreachable = e.stage == STAGE_OLLAMA_UNREACHABLE;
+            last_err = Some(e);
+            if unreachable {
+                break;
+            }
+            continue;
+        }
+        
+        let call_start = Instant::now();
+        match model.call_text(&prompt, task.sampling(&sampling)).await {
+            Ok(text) => {
+                state.record_call_throughput(&model_name, &text, call_start.elapsed().as_millis() as u64);
+                let latency_ms = query_start.elapsed().as_millis() as u64;
+                state.record_routing_success(latency_ms);
+                tracing::info!(task = ?task, model = %model_name, latency_ms = latency_ms, "Text query succeeded");
+                return Ok(text.trim().to_string());
+            }
+            Err(e) => {
+                state.record_routing_failure();
+                state.invalidate_model_availability(&model_name);
+                tracing::warn!(task = ?task, model = %model_name, error = %e, "Text query failed");
+                last_err = Some(ZosError::new(
+                    format!("Model '{}' failed to respond: {}", model_name, e),
+                    STAGE_MODEL_CALL
+                ).with_model(model_name.clone()));
             }
         }
     }
+    
+    state.metrics.record_error();
+    Err(last_err.unwrap_or_else(|| ZosError::new("No model available for text query", STAGE_ROUTING)))
 }
 
-/// Try a model with exponential backoff retry
+/// Whether a failed attempt is worth sending to the same model again.
+/// Failed calls (timeouts, connection errors, empty responses) are transient.
+/// Once a response arrived, bad JSON is left to the repair and fallback path:
+/// re-asking a deterministic model the same prompt rarely changes its shape.
+pub fn should_retry_attempt(stage: &str) -> bool {
+    matches!(stage, STAGE_MODEL_CALL | STAGE_TIMEOUT)
+}
+
+/// Stage for an error returned by the Ollama call itself
+pub fn call_failure_stage(error: &anyhow::Error) -> &'static str {
+    if crate::pipelines::ollama::is_timeout(error) {
+        STAGE_TIMEOUT
+    } else {
+        STAGE_MODEL_CALL
+    }
+}
+
+/// Try a model, retrying transient call failures with exponential backoff
+/// (`max_retries` from the model config)
 /// Returns Ok(result) on success, or Err with raw_response context for JSON extraction failures
-async fn try_model_with_retry<T: serde::de::DeserializeOwned>(
+#[tracing::instrument(skip_all, fields(model = %model_name))]
+async fn try_model_with_retry<T: serde::de::DeserializeOwned + OutputSchema>(
     state: &AppState,
     model_name: &str,
     prompt: &str,
-    _task: TaskType,
+    task: TaskType,
     _query_start: Instant,
 ) -> Result<T, (ZosError, Option<String>)> {
-    use crate::pipelines::ollama;
-    use crate::pipelines::ollama_utils;
-    
     // Verify model exists in registry
     let _model = get_model(model_name)
         .ok_or_else(|| (ZosError::new(
             format!("Model '{}' not found in registry", model_name),
-            "routing"
+            STAGE_ROUTING
         ).with_model(model_name.to_string()), None))?;
 
-    let max_retries = 2;
-
-    for attempt in 0..=max_retries {
-        let attempt_start = Instant::now();
+    let config = get_model_config();
+    let max_retries = config.max_retries;
+    let sampling = task.sampling(&config.sampling);
+    // Full jitter so concurrent queries don't retry against Ollama in lockstep
+    let backoff = ExponentialBackoff::with_jitter(100, 5000, true);
 
-        // Get raw response first
-        let raw_response = match ollama::call_ollama_model(model_name, prompt).await {
-            Ok(resp) => resp,
-            Err(e) => {
-                if attempt < max_retries {
-                    // Simple exponential backoff: 100ms * 2^attempt, max 5s
-                    let delay_ms = (100 * 2_u64.pow(attempt)).min(5000);
-                    tracing::warn!(
+    let mut attempt = 0;
+    loop {
+        match attempt_model_once::<T>(state, model_name, prompt, sampling).await {
+            Ok((result, latency_ms)) => {
+                if attempt > 0 {
+                    tracing::info!(
                         model = model_name,
-                        error = %e,
-                        attempt = attempt + 1,
-                        max_retries = max_retries + 1,
-                        delay_ms = delay_ms,
-                        "Model call failed, retrying with backoff"
+                        latency_ms = latency_ms,
+                        attempt = attempt,
+                        "Model call succeeded after retry"
                     );
-                    state.record_routing_failure();
-                    tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
-                    continue;
                 } else {
+                    tracing::info!(
+                        model = model_name,
+                        latency_ms = latency_ms,
+                        "Model call succeeded"
+                    );
+                }
+                state.record_routing_success(latency_ms);
+                return Ok(result);
+            }
+            Err((e, raw_response)) => {
+                if !should_retry_attempt(&e.stage) {
+                    tracing::warn!(model = model_name, stage = %e.stage, error = %e.message, "Model attempt failed, not retrying");
+                    return Err((e, raw_response));
+                }
+                if attempt >= max_retries {
                     return Err((ZosError::new(
-                        format!("Model '{}' failed to respond after {} attempts: {}", model_name, max_retries + 1, e),
-                        "model_call"
-                    ).with_model(model_name.to_string()).with_retry(true), None));
+                        format!("Model '{}' failed to respond after {} attempts: {}", model_name, attempt + 1, e.message),
+                        STAGE_RETRY_EXHAUSTED
+                    ).with_model(model_name.to_string()).with_retry(true), raw_response));
                 }
+                let delay_ms = backoff.delay_for_attempt(attempt);
+                tracing::warn!(
+                    model = model_name,
+                    error = %e.message,
+                    attempt = attempt + 1,
+                    max_retries = max_retries + 1,
+                    delay_ms = delay_ms,
+                    "Model call failed, retrying with backoff"
+                );
+                state.record_routing_failure();
+                tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
+                attempt += 1;
             }
-        };
-        
-        let latency_ms = attempt_start.elapsed().as_millis() as u64;
-        
-        // Max-latency watchdog: if > 60s, treat as truncation
-        // Allows time for detailed proofs that may take longer to parse
-        if latency_ms > 60000 {
-            tracing::warn!(
-                model = model_name,
-                latency_ms = latency_ms,
-                "Latency exceeded 60s, treating as truncation"
-            );
-            return Err((ZosError::new(
-                format!("Model '{}' response took {}ms (truncation suspected)", model_name, latency_ms),
-                "timeout_truncation"
-            ).with_model(model_name.to_string()).with_retry(true), Some(raw_response)));
         }
-        
-        // Max-output-size check: if > 40k bytes, treat as invalid
-        if raw_response.len() > 40_000 {
-            tracing::warn!(
-                model = model_name,
-                output_size = raw_response.len(),
-                "Output size exceeded 40k bytes, treating as invalid"
-            );
-            return Err((ZosError::new(
-                format!("Model '{}' output too large ({} bytes)", model_name, raw_response.len()),
-                "output_too_large"
-            ).with_model(model_name.to_string()).with_retry(true), Some(raw_response)));
+    }
+}
+
+/// One call to `model_name` plus output checks and JSON extraction.
+/// Returns the parsed result and the attempt latency, or the error with the
+/// raw response when one was received (for repair).
+async fn attempt_model_once<T: serde::de::DeserializeOwned + OutputSchema>(
+    state: &AppState,
+    model_name: &str,
+    prompt: &str,
+    sampling: &SamplingOptions,
+) -> Result<(T, u64), (ZosError, Option<String>)> {
+    use crate::pipelines::ollama;
+    use crate::pipelines::ollama_utils;
+
+    let attempt_start = Instant::now();
+
+    // Get raw response first
+    let schema = if get_model_config().structured_output { T::output_schema() } else { None };
+    let call_result = ollama::call_ollama_model_with_options(model_name, prompt, sampling, schema.as_ref()).await;
+    let call_ms = attempt_start.elapsed().as_millis() as u64;
+    // Failed calls count too: timeouts are exactly the tail we want to see
+    state.record_model_latency(model_name, call_ms);
+    match &call_result {
+        Ok(response) => state.record_call_throughput(model_name, response, call_ms),
+        // Check again next time instead of trusting a stale confirmation
+        Err(_) => state.invalidate_model_availability(model_name),
+    }
+    let raw_response = call_result.map_err(|e| (e.downcast_ref::<ZosError>().cloned().unwrap_or_else(|| ZosError::new(
+        format!("Model '{}' call failed: {}", model_name, e),
+        call_failure_stage(&e)
+    ).with_model(model_name.to_string())), None))?;
+    
+    let latency_ms = attempt_start.elapsed().as_millis() as u64;
+    
+    // Max-latency watchdog: if > 60s, treat as truncation
+    // Allows time for detailed proofs that may take longer to parse
+    if latency_ms > 60000 {
+        tracing::warn!(
+            model = model_name,
+            latency_ms = latency_ms,
+            "Latency exceeded 60s, treating as truncation"
+        );
+        return Err((ZosError::new(
+            format!("Model '{}' response took {}ms (truncation suspected)", model_name, latency_ms),
+            STAGE_TIMEOUT_TRUNCATION
+        ).with_model(model_name.to_string()).with_retry(true), Some(raw_response)));
+    }
+    
+    // Schema-constrained output should already be valid; anything else goes
+    // through the truncation check and extraction below
+    if schema.is_some() {
+        if let Some(result) = ollama::parse_structured_response::<T>(&raw_response) {
+            return Ok((result, latency_ms));
         }
-        
-        // Sanitize raw output before extraction
-        let sanitized = ollama_utils::sanitize_raw_output(&raw_response);
-        
-        // Truncation check: if truncated, skip repair and regenerate
-        if ollama_utils::is_truncated(&sanitized) {
-            tracing::warn!(
-                model = model_name,
-                "Output appears truncated, skipping repair"
-            );
+        tracing::warn!(model = model_name, "Schema-constrained response did not parse, falling back to extraction");
+    }
+    
+    // Sanitize raw output before extraction
+    let sanitized = ollama_utils::sanitize_raw_output(&raw_response);
+    
+    // Truncation check: if truncated, skip repair and regenerate
+    if ollama_utils::is_truncated(&sanitized) {
+        tracing::warn!(
+            model = model_name,
+            "Output appears truncated, skipping repair"
+        );
+        return Err((ZosError::new(
+            format!("Model '{}' output appears truncated", model_name),
+            STAGE_TRUNCATED
+        ).with_model(model_name.to_string()).with_retry(true), Some(raw_response)));
+    }
+    
+    // Try to extract and parse JSON from sanitized output; failures carry the
+    // raw response for repair
+    let json_str = match ollama_utils::extract_json_cached(state, &sanitized) {
+        Ok(json_str) => json_str,
+        Err(extract_err) => {
             return Err((ZosError::new(
-                format!("Model '{}' output appears truncated", model_name),
-                "truncated"
+                format!("Model '{}' failed to extract JSON: {}", model_name, extract_err),
+                STAGE_JSON_EXTRACT
             ).with_model(model_name.to_string()).with_retry(true), Some(raw_response)));
         }
-        
-        // Try to extract and parse JSON from sanitized output
-        match ollama_utils::extract_json(&sanitized) {
-            Ok(json_str) => {
-                match serde_json::from_str::<T>(&json_str) {
-                    Ok(result) => {
-                        let latency_ms = attempt_start.elapsed().as_millis() as u64;
-                        if attempt > 0 {
-                            tracing::info!(
-                                model = model_name,
-                                latency_ms = latency_ms,
-                                attempt = attempt,
-                                "Model call succeeded after retry"
-                            );
-                        } else {
-                            tracing::info!(
-                                model = model_name,
-                                latency_ms = latency_ms,
-                                "Model call succeeded"
-                            );
-                        }
-                        state.record_routing_success(latency_ms);
-                        return Ok(result);
-                    }
-                    Err(parse_err) => {
-                        let error_msg = format!("Model '{}' returned invalid JSON: {}", model_name, parse_err);
-                        if attempt < max_retries {
-                            // Simple exponential backoff: 100ms * 2^attempt, max 5s
-                            let delay_ms = (100 * 2_u64.pow(attempt)).min(5000);
-                            tracing::warn!(
-                                model = model_name,
-                                error = %parse_err,
-                                attempt = attempt + 1,
-                                max_retries = max_retries + 1,
-                                delay_ms = delay_ms,
-                                "JSON parsing failed, retrying"
-                            );
-                            state.record_routing_failure();
-                            tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
-                            continue;
-                        } else {
-                            // Return error with raw response for repair
-                            return Err((ZosError::new(error_msg, "json_parse").with_model(model_name.to_string()).with_retry(true), Some(raw_response)));
-                        }
-                    }
-                }
-            }
-            Err(extract_err) => {
-                let error_msg = format!("Model '{}' failed to extract JSON: {}", model_name, extract_err);
-                if attempt < max_retries {
-                    // Simple exponential backoff: 100ms * 2^attempt, max 5s
-                    let delay_ms = (100 * 2_u64.pow(attempt)).min(5000);
-                    tracing::warn!(
-                        model = model_name,
-                        error = %extract_err,
-                        attempt = attempt + 1,
-                        max_retries = max_retries + 1,
-                        delay_ms = delay_ms,
-                        "JSON extraction failed, retrying"
-                    );
-                    state.record_routing_failure();
-                    tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
-                    continue;
-                } else {
-                    // Return error with raw response for repair
-                    return Err((ZosError::new(error_msg, "json_extract").with_model(model_name.to_string()).with_retry(true), Some(raw_response)));
-                }
-            }
-        }
+    };
+    match serde_json::from_str::<T>(&json_str) {
+        Ok(result) => Ok((result, attempt_start.elapsed().as_millis() as u64)),
+        Err(parse_err) => Err((ZosError::new(
+            format!("Model '{}' returned invalid JSON: {}", model_name, parse_err),
+            STAGE_JSON_PARSE
+        ).with_model(model_name.to_string()).with_retry(true), Some(raw_response))),
     }
-    
-    // Should never reach here, but compiler needs it
-    unreachable!()
 }
 
 /// Attempt to repair/extract JSON from a raw model response using a fallback model
 async fn repair_json_with_fallback<T: serde::de::DeserializeOwned>(
-    _state: &AppState,
+    state: &AppState,
     fallback_model_name: &str,
+    task: TaskType,
     raw_response: &str,
     _original_prompt: &str,
 ) -> Result<T, ZosError> {
@@ -538,11 +811,16 @@ Malformed JSON:
         "Attempting to repair JSON with fallback model"
     );
     
-    let repaired_raw = ollama::call_ollama_model(fallback_model_name, &repair_prompt).await
+    // Same sampling as the task itself, so a repair is no hotter than the
+    // call it repairs
+    let sampling = task.sampling(&get_model_config().sampling).clone();
+    let call_start = Instant::now();
+    let repaired_raw = ollama::call_ollama_model_with_options(fallback_model_name, &repair_prompt, &sampling, None).await
         .map_err(|e| ZosError::new(
             format!("Fallback model '{}' failed to repair JSON: {}", fallback_model_name, e),
-            "json_repair"
+            STAGE_JSON_REPAIR
         ))?;
+    state.record_call_throughput(fallback_model_name, &repaired_raw, call_start.elapsed().as_millis() as u64);
     
     let sanitized_repaired = ollama_utils::sanitize_raw_output(&repaired_raw);
     
@@ -550,20 +828,20 @@ Malformed JSON:
     if sanitized_repaired.trim() == "\"__TRUNCATED__\"" || sanitized_repaired.trim() == "__TRUNCATED__" {
         return Err(ZosError::new(
             "Fallback model detected truncation",
-            "truncated_detected"
+            STAGE_TRUNCATED_DETECTED
         ));
     }
     
     // Try to extract JSON from the repair attempt
-    let json_str = ollama_utils::extract_json(&sanitized_repaired)
+    let json_str = ollama_utils::extract_json_cached(state, &sanitized_repaired)
         .map_err(|e| ZosError::new(
             format!("Failed to extract JSON from repair attempt: {}", e),
-            "json_repair_extract"
+            STAGE_JSON_REPAIR_EXTRACT
         ))?;
     
     serde_json::from_str::<T>(&json_str)
         .map_err(|e| ZosError::new(
             format!("Repaired JSON is invalid: {}", e),
-            "json_repair_parse"
+            STAGE_JSON_REPAIR_PARSE
         ))
 }
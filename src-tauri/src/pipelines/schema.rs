@@ -0,0 +1,85 @@
+use schemars::gen::SchemaSettings;
+use schemars::schema::{InstanceType, Schema, SchemaObject};
+use schemars::JsonSchema;
+use serde_json::{json, Value};
+use crate::pipelines::proof::{Step1Response, Step2Response};
+use crate::problems::problem::Problem;
+
+/// JSON schema for a model response type, sent as Ollama's `format` when
+/// `structured_output` is enabled so the model can only emit matching JSON.
+/// Types without a schema (the default) use plain extraction.
+pub trait OutputSchema {
+    fn output_schema() -> Option<Value> {
+        None
+    }
+
+    /// Build a result from a response that was cut off, given the object
+    /// closed off after its last complete value. Types that can use an
+    /// incomplete result return it marked as partial; the default declines.
+    fn from_partial(_value: Value) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+}
+
+/// `T`'s schema, derived from its definition. Subschemas are inlined
+/// rather than referenced, since Ollama's grammar conversion works best on a
+/// single self-contained object.
+fn derived_schema<T: JsonSchema>() -> Option<Value> {
+    let generator = SchemaSettings::draft07()
+        .with(|settings| {
+            settings.inline_subschemas = true;
+            settings.meta_schema = None;
+        })
+        .into_generator();
+    serde_json::to_value(generator.into_root_schema_for::<T>()).ok()
+}
+
+/// Schema for `QuestionEvaluation::assessment`: one of the fixed grades
+pub(crate) fn assessment_schema(_: &mut schemars::gen::SchemaGenerator) -> Schema {
+    SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        enum_values: Some(
+            ["correct", "partially_correct", "incorrect", "unclear"].into_iter().map(Value::from).collect()
+        ),
+        ..Default::default()
+    }
+    .into()
+}
+
+impl OutputSchema for Value {}
+
+impl OutputSchema for Step1Response {
+    fn output_schema() -> Option<Value> {
+        derived_schema::<Self>()
+    }
+
+    /// Keeps the steps and issues parsed before the cut; fields the cut
+    /// removed entirely come back empty
+    fn from_partial(value: Value) -> Option<Self> {
+        let mut object = match value {
+            Value::Object(object) => object,
+            _ => return None,
+        };
+        for key in ["steps", "issues", "questions"] {
+            object.entry(key).or_insert_with(|| json!([]));
+        }
+        object.entry("summary").or_insert_with(|| json!(""));
+        object.insert("partial".to_string(), Value::Bool(true));
+        serde_json::from_value(Value::Object(object)).ok()
+    }
+}
+
+impl OutputSchema for Step2Response {
+    fn output_schema() -> Option<Value> {
+        derived_schema::<Self>()
+    }
+}
+
+impl OutputSchema for Problem {
+    fn output_schema() -> Option<Value> {
+        derived_schema::<Self>()
+    }
+}
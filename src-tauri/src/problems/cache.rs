@@ -1,14 +1,75 @@
 use serde::{Serialize, Deserialize};
 use std::clone::Clone;
 use std::sync::Arc;
-use parking_lot::Mutex;
 use tokio::time::{sleep, Duration};
 use crate::skills::store::load_skill_vector;
-use crate::problems::{problem::Problem, generator};
+use crate::problems::{problem::{Problem, MAX_DIFFICULTY, MIN_DIFFICULTY}, generator, selector::target_difficulty};
 use crate::error::ZosError;
+use crate::state::app::AppState;
+
+/// Queue file name in the app data dir
+const CACHE_FILE: &str = "problems_cache.json";
+/// Below this the prefetch loop refills right away
+pub const MIN_CACHE: usize = 12;
+/// Refill level while the app is idle
+pub const TARGET_CACHE: usize = 20;
+/// Backpressure: most problems generated per loop iteration
+pub const MAX_PREFETCH_PER_ITERATION: usize = 3;
+/// How long without user activity counts as idle
+pub const IDLE_AFTER: Duration = Duration::from_secs(120);
+
+/// Gap between the easier, same and harder bands kept per skill
+pub const BAND_STEP: f32 = 0.2;
+
+const DEPLETED_SLEEP: Duration = Duration::from_secs(5);
+const PARTIAL_SLEEP: Duration = Duration::from_secs(30);
+const FULL_SLEEP: Duration = Duration::from_secs(180);
+
+/// Where the problem cache is stored
+pub fn cache_path() -> std::path::PathBuf {
+    crate::paths::data_dir().join(CACHE_FILE)
+}
+
+/// The (skill, difficulty) pairs to generate next: missing bands for the
+/// weakest skills first, at most `budget` of them
+pub fn prefetch_targets(cache: &ProblemCache, weakest: &[(String, f32)], budget: usize) -> Vec<(String, f32)> {
+    weakest.iter()
+        .flat_map(|(skill, value)| {
+            cache.missing_bands(skill, target_difficulty(*value))
+                .into_iter()
+                .map(move |diff| (skill.clone(), diff))
+        })
+        .take(budget)
+        .collect()
+}
+
+/// What one prefetch iteration should do: how many problems to generate and
+/// how long to sleep afterwards. Refills to `MIN_CACHE` always and to
+/// `TARGET_CACHE` when idle, never more than `MAX_PREFETCH_PER_ITERATION`
+/// at a time; sleeps briefly while depleted and long once full.
+pub fn prefetch_step(queue_len: usize, idle: bool) -> (usize, Duration) {
+    let level = if idle { TARGET_CACHE } else { MIN_CACHE };
+    let to_generate = level.saturating_sub(queue_len).min(MAX_PREFETCH_PER_ITERATION);
+    let pause = if queue_len < MIN_CACHE {
+        DEPLETED_SLEEP
+    } else if queue_len < TARGET_CACHE {
+        PARTIAL_SLEEP
+    } else {
+        FULL_SLEEP
+    };
+    (to_generate, pause)
+}
 
-const CACHE_PATH: &str = "data/problems_cache.json";
-const MIN_CACHE: usize = 12;
+/// Easier, same and harder difficulties around `target`, like the precomputed
+/// problems in `AppState`
+pub fn band_difficulties(target: f32) -> [f32; 3] {
+    let target = target.clamp(MIN_DIFFICULTY, MAX_DIFFICULTY);
+    [
+        (target - BAND_STEP).max(MIN_DIFFICULTY),
+        target,
+        (target + BAND_STEP).min(MAX_DIFFICULTY),
+    ]
+}
 
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub struct ProblemCache {
@@ -16,215 +77,165 @@ pub struct ProblemCache {
 }
 
 impl ProblemCache {
-    /// Load cache asynchronously
-    pub async fn load_async() -> Self {
-        // Try platform-specific paths
-        let possible_paths = vec![
-            std::path::Path::new(CACHE_PATH),
-            std::path::Path::new("../data/problems_cache.json"),
-            std::path::Path::new("./data/problems_cache.json"),
-        ];
-
-        for path in possible_paths {
-            match tokio::fs::read_to_string(path).await {
-                Ok(content) => {
-                    match serde_json::from_str::<ProblemCache>(&content) {
-                        Ok(cache) => return cache,
-                        Err(e) => {
-                            tracing::warn!(
-                                path = ?path,
-                                error = %e,
-                                "Failed to parse cache file"
-                            );
-                        }
-                    }
-                }
-                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
-                    continue;
-                }
-                Err(e) => {
-                    tracing::debug!(
-                        path = ?path,
-                        error = %e,
-                        "Failed to read cache file"
-                    );
+    /// Bands around `target` with no queued problem for `skill`. A problem
+    /// covers a band when it is within half a step of it; each problem covers
+    /// one band at most.
+    pub fn missing_bands(&self, skill: &str, target: f32) -> Vec<f32> {
+        let mut unused: Vec<f32> = self.queue.iter()
+            .filter(|p| p.topic == skill)
+            .map(|p| p.difficulty)
+            .collect();
+        let mut missing = Vec::new();
+        for band in band_difficulties(target) {
+            let covering = unused.iter()
+                .position(|d| (d - band).abs() <= BAND_STEP / 2.0 + f32::EPSILON);
+            match covering {
+                Some(i) => {
+                    unused.swap_remove(i);
                 }
+                None => missing.push(band),
             }
         }
+        missing
+    }
 
-        Self::default()
+    /// Remove and return the eligible problem for `skill` closest to `target`
+    pub fn take_closest<F>(&mut self, skill: &str, target: f32, eligible: F) -> Option<Problem>
+    where
+        F: Fn(&Problem) -> bool,
+    {
+        let pos = self.queue.iter()
+            .enumerate()
+            .filter(|(_, p)| p.topic == skill && eligible(p))
+            .min_by(|(_, a), (_, b)| {
+                (a.difficulty - target).abs()
+                    .partial_cmp(&(b.difficulty - target).abs())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .map(|(i, _)| i)?;
+        Some(self.queue.remove(pos))
     }
 
-    /// Save cache asynchronously
-    pub async fn save_async(&self) -> Result<(), ZosError> {
-        // Try to save to data directory
-        let possible_paths = vec![
-            std::path::Path::new("data"),
-            std::path::Path::new("../data"),
-            std::path::Path::new("./data"),
-        ];
+    /// Load the cache from the app data dir
+    pub async fn load_async() -> Self {
+        Self::load_in(&cache_path()).await
+    }
+
+    /// Load the cache at `path`; empty if it is missing or unreadable
+    pub async fn load_in(path: &std::path::Path) -> Self {
+        match tokio::fs::read_to_string(path).await {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                tracing::warn!(path = ?path, error = %e, "Failed to parse cache file");
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
 
+    /// Save the cache to `path`, creating its directory if needed
+    pub async fn save_in(&self, path: &std::path::Path) -> Result<(), ZosError> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| ZosError::new(
+                    format!("Failed to create directory: {}", e),
+                    "io"
+                ).with_context(format!("path: {:?}", parent)))?;
+        }
         let json = serde_json::to_string_pretty(self)
             .map_err(|e| ZosError::new(
                 format!("Failed to serialize cache: {}", e),
                 "json_serialize"
             ))?;
-
-        for base_path in possible_paths {
-            if tokio::fs::create_dir_all(base_path).await.is_err() {
-                continue;
-            }
-            let file_path = base_path.join("problems_cache.json");
-            if tokio::fs::write(&file_path, &json).await.is_ok() {
-                return Ok(());
-            }
-        }
-
-        // Fallback: try current directory
-        tokio::fs::write(CACHE_PATH, json)
+        tokio::fs::write(path, json)
             .await
             .map_err(|e| ZosError::new(
                 format!("Failed to write cache file: {}", e),
                 "io"
-            ).with_context(format!("path: {}", CACHE_PATH)))?;
-        Ok(())
+            ).with_context(format!("path: {:?}", path)))
     }
 
-    /// Synchronous load for backward compatibility (deprecated)
-    #[deprecated(note = "Use load_async().await instead")]
-    pub fn load() -> Self {
-        let possible_paths = vec![
-            std::path::Path::new(CACHE_PATH),
-            std::path::Path::new("../data/problems_cache.json"),
-            std::path::Path::new("./data/problems_cache.json"),
-        ];
-
-        for path in possible_paths {
-            if let Ok(content) = std::fs::read_to_string(path) {
-                if let Ok(cache) = serde_json::from_str::<ProblemCache>(&content) {
-                    return cache;
-                }
-            }
-        }
-
-        Self::default()
+    /// Drop the queued problem with `problem_id`, returning whether it was there
+    pub fn remove(&mut self, problem_id: &str) -> bool {
+        let before = self.queue.len();
+        self.queue.retain(|p| p.id != problem_id);
+        self.queue.len() != before
     }
 
-    /// Synchronous save for backward compatibility (deprecated)
-    #[deprecated(note = "Use save_async().await instead")]
-    pub fn save(&self) -> Result<(), ZosError> {
-        let possible_paths = vec![
-            std::path::Path::new("data"),
-            std::path::Path::new("../data"),
-            std::path::Path::new("./data"),
-        ];
-
-        let json = serde_json::to_string_pretty(self)
-            .map_err(|e| ZosError::new(
-                format!("Failed to serialize cache: {}", e),
-                "json_serialize"
-            ))?;
-
-        for base_path in possible_paths {
-            if std::fs::create_dir_all(base_path).is_err() {
-                continue;
-            }
-            let file_path = base_path.join("problems_cache.json");
-            if std::fs::write(&file_path, &json).is_ok() {
-                return Ok(());
-            }
-        }
-
-        std::fs::write(CACHE_PATH, json)
-            .map_err(|e| ZosError::new(
-                format!("Failed to write cache file: {}", e),
-                "io"
-            ).with_context(format!("path: {}", CACHE_PATH)))
+    /// Save the cache to the app data dir
+    pub async fn save_async(&self) -> Result<(), ZosError> {
+        self.save_in(&cache_path()).await
     }
 }
 
-pub async fn start_problem_prefetch(cache: Arc<Mutex<ProblemCache>>, state: Arc<crate::state::app::AppState>) {
+/// Keep the problem cache on disk topped up in the background. The file is
+/// the source of truth: the routes take and remove problems from it, so each
+/// step re-reads it rather than holding a copy across model calls, and
+/// changes it only under `problem_cache_lock`.
+pub fn start_problem_prefetch(state: Arc<AppState>) {
     tokio::spawn(async move {
         loop {
-            let needs_more = {
-                let guard = cache.lock();
-                guard.queue.len() < MIN_CACHE
-            };
+            let idle = state.idle_for() >= IDLE_AFTER;
+            let (to_generate, mut pause) = prefetch_step(ProblemCache::load_async().await.queue.len(), idle);
+            let level = if idle { TARGET_CACHE } else { MIN_CACHE };
             
-            if needs_more {
+            if to_generate > 0 && !state.is_offline() {
                 let skills = load_skill_vector().await;
-                let weakest = skills.weakest_n(2);
+                let weakest = state.with_selection_rng(|rng| skills.weakest_n_with(skills.skills.len(), rng));
+                // Fill the easier/same/harder bands of the weakest skills first
+                let targets = prefetch_targets(&ProblemCache::load_async().await, &weakest, to_generate);
                 
-                for (skill, value) in weakest {
-                    let diff = (0.3_f32).max(1.0 - value);
-                    
-                    // Generate new problem for this skill (outside mutex)
-                    let generated = generator::generate_problem(&state, &skill, diff).await;
-                    
-                    // Process generated problem and update cache
-                    let needs_save = {
-                        let mut guard = cache.lock();
-                        if guard.queue.len() >= MIN_CACHE {
-                            false // Don't save, we're done
-                        } else {
-                            match generated {
-                                Ok(problem) => {
-                                    guard.queue.push(problem);
-                                    true
-                                }
+                for (skill, diff) in targets {
+                    let generated = match generator::generate_problem(&state, &skill, diff).await {
+                        Ok(problem) => vec![problem],
+                        Err(e) => {
+                            tracing::warn!(
+                                skill = %skill,
+                                error = %e,
+                                "Failed to generate problem, trying fallback"
+                            );
+                            // Fallback to loading existing problems if generation fails
+                            match Problem::load_all() {
+                                Ok(all_problems) => all_problems.into_iter()
+                                    .filter(|p| p.topic == skill)
+                                    .take(2)
+                                    .collect(),
                                 Err(e) => {
                                     tracing::warn!(
                                         skill = %skill,
                                         error = %e,
-                                        "Failed to generate problem, trying fallback"
+                                        "Failed to load problems for fallback"
                                     );
-                                    // Fallback to loading existing problems if generation fails
-                                    match Problem::load_all() {
-                                        Ok(all_problems) => {
-                                            let matching: Vec<Problem> = all_problems.iter()
-                                                .filter(|p| p.topic == skill)
-                                                .cloned()
-                                                .collect();
-                                            
-                                            let mut added = false;
-                                            for problem in matching.into_iter().take(2) {
-                                                if guard.queue.len() >= MIN_CACHE {
-                                                    break;
-                                                }
-                                                guard.queue.push(problem);
-                                                added = true;
-                                            }
-                                            added
-                                        }
-                                        Err(e) => {
-                                            tracing::warn!(
-                                                skill = %skill,
-                                                error = %e,
-                                                "Failed to load problems for fallback"
-                                            );
-                                            false
-                                        }
-                                    }
+                                    Vec::new()
                                 }
                             }
                         }
                     };
                     
-                    // Save cache asynchronously (outside lock scope)
-                    if needs_save {
-                        let cache_clone = {
-                            let guard = cache.lock();
-                            guard.clone()
-                        }; // Guard is dropped here
-                        if let Err(e) = cache_clone.save_async().await {
+                    // Re-read after the model call so problems taken meanwhile stay
+                    // taken; the lock keeps them taken until this save is done
+                    let _cache_guard = state.problem_cache_lock.lock().await;
+                    let mut cache = ProblemCache::load_async().await;
+                    let before = cache.queue.len();
+                    for problem in generated {
+                        if cache.queue.len() >= level {
+                            break;
+                        }
+                        cache.queue.push(problem);
+                    }
+                    if cache.queue.len() != before {
+                        if let Err(e) = cache.save_async().await {
                             tracing::warn!(error = %e, "Failed to save problem cache");
                         }
                     }
                 }
+                
+                // Sleep according to where the refill left the queue
+                pause = prefetch_step(ProblemCache::load_async().await.queue.len(), idle).1;
             }
             
-            sleep(Duration::from_secs(60)).await;
+            tracing::debug!(idle = idle, pause_secs = pause.as_secs(), "Problem prefetch sleeping");
+            sleep(pause).await;
         }
     });
 }
-
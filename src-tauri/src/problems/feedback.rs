@@ -0,0 +1,127 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::error::ZosError;
+
+/// Statements are stored cut to this many characters; enough to describe the pattern
+pub const MAX_STORED_STATEMENT_CHARS: usize = 200;
+/// Low-rated statements quoted in the generation prompt
+pub const MAX_AVOID_PATTERNS: usize = 3;
+/// Ratings the log holds before it is compacted down to each problem's
+/// latest rating, newest first
+pub const MAX_STORED_RATINGS: usize = 200;
+
+/// Feedback on one generated problem: -1 (bad or too easy), 0 (fine), +1 (good).
+/// One JSON object per line in `problem_feedback.jsonl`; a later rating of the
+/// same problem replaces earlier ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProblemRating {
+    pub problem_id: String,
+    pub topic: String,
+    pub quality: i8,
+    pub statement: String,
+    pub timestamp: i64,
+}
+
+impl ProblemRating {
+    /// Validate `quality` and build a rating with a shortened statement
+    pub fn new(problem_id: String, topic: String, quality: i8, statement: &str, timestamp: i64) -> Result<Self, ZosError> {
+        if !(-1..=1).contains(&quality) {
+            return Err(ZosError::new(
+                format!("Quality must be -1, 0 or 1, got {}", quality),
+                "validation"
+            ));
+        }
+        Ok(ProblemRating {
+            problem_id,
+            topic,
+            quality,
+            statement: statement.chars().take(MAX_STORED_STATEMENT_CHARS).collect(),
+            timestamp,
+        })
+    }
+}
+
+pub fn get_feedback_path() -> PathBuf {
+    crate::paths::data_dir().join("problem_feedback.jsonl")
+}
+
+/// Append a rating to the feedback log
+pub async fn append_rating(rating: &ProblemRating) -> Result<(), ZosError> {
+    append_rating_in(&get_feedback_path(), rating).await
+}
+
+/// Append a rating to the feedback log at `path`, compacting the log once it
+/// grows past `MAX_STORED_RATINGS`
+pub async fn append_rating_in(path: &Path, rating: &ProblemRating) -> Result<(), ZosError> {
+    crate::jsonl::append_line(path, rating, "problem feedback").await?;
+    let ratings = load_ratings_in(path).await?;
+    if ratings.len() > MAX_STORED_RATINGS {
+        let kept = compact_ratings(ratings, MAX_STORED_RATINGS);
+        crate::jsonl::write_lines(path, &kept, "problem feedback").await?;
+    }
+    Ok(())
+}
+
+/// Each problem's latest rating, keeping the newest `max` of them, oldest first
+pub fn compact_ratings(ratings: Vec<ProblemRating>, max: usize) -> Vec<ProblemRating> {
+    let mut seen = HashSet::new();
+    let mut kept: Vec<ProblemRating> = ratings.into_iter()
+        .rev()
+        .filter(|r| seen.insert(r.problem_id.clone()))
+        .take(max)
+        .collect();
+    kept.reverse();
+    kept
+}
+
+/// All ratings, oldest first
+pub async fn load_ratings() -> Result<Vec<ProblemRating>, ZosError> {
+    load_ratings_in(&get_feedback_path()).await
+}
+
+/// Like `load_ratings`, reading from `path`. Unparseable lines are skipped.
+pub async fn load_ratings_in(path: &Path) -> Result<Vec<ProblemRating>, ZosError> {
+    let content = match tokio::fs::read_to_string(path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(ZosError::new(
+                format!("Failed to read problem feedback: {}", e),
+                "io"
+            ).with_context(format!("path: {:?}", path)));
+        }
+    };
+    
+    Ok(content.lines()
+        .filter_map(|line| serde_json::from_str::<ProblemRating>(line).ok())
+        .collect())
+}
+
+/// Statements of the most recently down-rated problems for `topic`, newest
+/// first. Only each problem's latest rating counts.
+pub fn avoid_patterns(ratings: &[ProblemRating], topic: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    ratings.iter()
+        .rev()
+        .filter(|r| seen.insert(r.problem_id.as_str()))
+        .filter(|r| r.quality < 0 && r.topic == topic)
+        .take(MAX_AVOID_PATTERNS)
+        .map(|r| r.statement.clone())
+        .collect()
+}
+
+/// Prompt note listing statements not to imitate; empty when there are none
+pub fn avoid_note(patterns: &[String]) -> String {
+    if patterns.is_empty() {
+        return String::new();
+    }
+    let mut note = String::from("Avoid problems like these, which were rated too easy or low quality:\n");
+    for pattern in patterns {
+        note.push_str("- ");
+        note.push_str(pattern);
+        note.push('\n');
+    }
+    note.push('\n');
+    note
+}
@@ -4,7 +4,9 @@ use std::fs;
 use std::path::PathBuf;
 use chrono::Utc;
 use crate::problems::problem::Problem;
+use crate::problems::selector::normalize_tag;
 use crate::pipelines::router::TaskType;
+use crate::config::models::problems_dir_override;
 
 pub fn hash_statement(statement: &str) -> String {
     let mut hasher = Sha256::new();
@@ -13,39 +15,18 @@ pub fn hash_statement(statement: &str) -> String {
 }
 
 fn get_all_existing_statements() -> Vec<String> {
+    // The same bank and autogen folder new problems are written to
+    let dir = crate::paths::problems_dir();
+    let autogen = dir.join("autogen");
+    statement_hashes_in(&[dir, autogen])
+}
+
+/// Statement hashes of every problem file directly inside `dirs`
+fn statement_hashes_in(dirs: &[PathBuf]) -> Vec<String> {
     let mut hashes = Vec::new();
     
-    // Check problems directory
-    let possible_paths = vec![
-        std::path::Path::new("problems"),
-        std::path::Path::new("../problems"),
-        std::path::Path::new("./problems"),
-    ];
-    
-    for problems_dir in possible_paths {
-        if let Ok(entries) = fs::read_dir(problems_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                    if let Ok(content) = fs::read_to_string(&path) {
-                        if let Ok(problem) = serde_json::from_str::<Problem>(&content) {
-                            hashes.push(hash_statement(&problem.statement));
-                        }
-                    }
-                }
-            }
-        }
-    }
-    
-    // Check autogen directory
-    let autogen_paths = vec![
-        std::path::Path::new("problems/autogen"),
-        std::path::Path::new("../problems/autogen"),
-        std::path::Path::new("./problems/autogen"),
-    ];
-    
-    for autogen_dir in autogen_paths {
-        if let Ok(entries) = fs::read_dir(autogen_dir) {
+    for dir in dirs {
+        if let Ok(entries) = fs::read_dir(dir) {
             for entry in entries.flatten() {
                 let path = entry.path();
                 if path.extension().and_then(|s| s.to_str()) == Some("json") {
@@ -63,55 +44,14 @@ fn get_all_existing_statements() -> Vec<String> {
 }
 
 fn get_autogen_dir() -> PathBuf {
-    // FIRST: Try app data directory (production - same logic as Problem::load_all)
-    #[cfg(target_os = "macos")]
-    {
-        if let Some(home) = std::env::var_os("HOME") {
-            let mut dir = PathBuf::from(home);
-            dir.push("Library/Application Support/com.zacnwo.zos");
-            dir.push("problems");
-            dir.push("autogen");
-            // Try to create if it doesn't exist
-            if let Ok(_) = fs::create_dir_all(&dir) {
-                return dir;
-            }
-            // If it already exists, use it
-            if dir.exists() {
-                return dir;
-            }
-        }
+    if let Some(dir) = problems_dir_override() {
+        return dir.join("autogen");
     }
-    
-    #[cfg(target_os = "windows")]
-    {
-        if let Some(appdata) = std::env::var_os("APPDATA") {
-            let mut dir = PathBuf::from(appdata);
-            dir.push("com.zacnwo.zos");
-            dir.push("problems");
-            dir.push("autogen");
-            if let Ok(_) = fs::create_dir_all(&dir) {
-                return dir;
-            }
-            if dir.exists() {
-                return dir;
-            }
-        }
-    }
-    
-    #[cfg(target_os = "linux")]
-    {
-        if let Some(home) = std::env::var_os("HOME") {
-            let mut dir = PathBuf::from(home);
-            dir.push(".local/share/com.zacnwo.zos");
-            dir.push("problems");
-            dir.push("autogen");
-            if let Ok(_) = fs::create_dir_all(&dir) {
-                return dir;
-            }
-            if dir.exists() {
-                return dir;
-            }
-        }
+
+    // FIRST: Try app data directory (production - same logic as Problem::load_all)
+    let dir = crate::paths::problems_dir().join("autogen");
+    if fs::create_dir_all(&dir).is_ok() || dir.exists() {
+        return dir;
     }
     
     // FALLBACK: Development paths (for dev mode)
@@ -142,21 +82,27 @@ pub async fn generate_problem(state: &crate::state::app::AppState, skill: &str,
         "hard"
     };
     
+    // Steer away from statements rated -1 for this skill
+    let ratings = crate::problems::feedback::load_ratings().await.unwrap_or_default();
+    let avoid = crate::problems::feedback::avoid_note(&crate::problems::feedback::avoid_patterns(&ratings, skill));
+    
     let prompt = format!(
         r#"Generate a {difficulty_str} problem for {skill}. Return ONLY valid JSON:
 
-{{"id": "autogen_<unique_id>", "topic": "{skill}", "difficulty": {diff}, "statement": "...", "solution_sketch": "..."}}
+{{"id": "autogen_<unique_id>", "topic": "{skill}", "difficulty": {diff}, "statement": "...", "solution_sketch": "...", "tags": ["..."]}}
+
+Example: {{"id": "autogen_1234567890_logical_reasoning", "topic": "logical_reasoning", "difficulty": 0.5, "statement": "Prove X", "solution_sketch": "Use method Y", "tags": ["induction", "contradiction"]}}
 
-Example: {{"id": "autogen_1234567890_logical_reasoning", "topic": "logical_reasoning", "difficulty": 0.5, "statement": "Prove X", "solution_sketch": "Use method Y"}}
+"tags" lists 1-3 short lowercase proof techniques the problem exercises.
 
-Use plain text (no LaTeX). Return ONLY JSON, no markdown, no explanations."#
+{avoid}Use plain text (no LaTeX). Return ONLY JSON, no markdown, no explanations."#
     );
     
     // Use unified query system with caching, retry, and fallback
     use crate::pipelines::router::zos_query;
     use crate::error::ZosError;
     
-    let mut problem: Problem = zos_query::<Problem>(state, TaskType::ProblemGeneration, prompt.clone())
+    let mut problem: Problem = zos_query::<Problem>(state, TaskType::ProblemGeneration, "", prompt.clone())
         .await
         .map_err(|e: ZosError| anyhow::anyhow!("Failed to generate problem: {}", e.message))?;
     
@@ -170,6 +116,15 @@ Use plain text (no LaTeX). Return ONLY JSON, no markdown, no explanations."#
     problem.topic = skill.to_string();
     problem.difficulty = diff;
     
+    // Normalize tags: lowercase, no blanks or duplicates
+    let mut tags: Vec<String> = Vec::new();
+    for tag in problem.tags.iter().map(|t| normalize_tag(t)) {
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    problem.tags = tags;
+    
     // Check for duplicates
     let dup_check_start = std::time::Instant::now();
     let statement_hash = hash_statement(&problem.statement);
@@ -2,4 +2,6 @@ pub mod selector;
 pub mod problem;
 pub mod cache;
 pub mod generator;
+pub mod transfer;
+pub mod feedback;
 
@@ -1,13 +1,29 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize, Deserializer};
+use crate::error::ZosError;
+use crate::config::models::problems_dir_override;
+use crate::problems::selector::{closest_known_topic, normalize_topic};
+use crate::skills::model::KNOWN_SKILLS;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Loaded difficulties are clamped into this range; values outside it break
+/// difficulty annealing and the selector's difficulty bands
+pub const MIN_DIFFICULTY: f32 = 0.1;
+pub const MAX_DIFFICULTY: f32 = 1.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Problem {
     pub id: String,
     pub topic: String,
+    #[schemars(range(min = 0.0, max = 1.0))]
     pub difficulty: f32,
     pub statement: String,
+    /// Also deserializes from structured forms, but a plain string is what
+    /// the rest of the app shows, so that is what the schema asks for
     #[serde(deserialize_with = "deserialize_solution_sketch")]
     pub solution_sketch: String,
+    /// Free-form technique tags (e.g. "induction", "epsilon-delta")
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 // Custom deserializer that handles both string and structured formats
@@ -40,25 +56,7 @@ where
         {
             let mut parts = Vec::new();
             while let Some(item) = seq.next_element::<serde_json::Value>()? {
-                if let Some(obj) = item.as_object() {
-                    // Handle object with step1, step2, etc.
-                    let mut steps: Vec<String> = obj
-                        .iter()
-                        .filter_map(|(k, v)| {
-                            if let Some(s) = v.as_str() {
-                                Some(format!("{}: {}", k, s))
-                            } else {
-                                None
-                            }
-                        })
-                        .collect();
-                    steps.sort(); // Sort by key for consistent output
-                    parts.extend(steps);
-                } else if let Some(s) = item.as_str() {
-                    parts.push(s.to_string());
-                } else {
-                    parts.push(item.to_string());
-                }
+                flatten_sketch_value(None, &item, &mut parts);
             }
             Ok(parts.join("\n"))
         }
@@ -67,20 +65,11 @@ where
         where
             A: de::MapAccess<'de>,
         {
-            let obj: serde_json::Map<String, serde_json::Value> = 
+            let obj: serde_json::Value = 
                 Deserialize::deserialize(de::value::MapAccessDeserializer::new(map))?;
             
-            let mut parts: Vec<String> = obj
-                .iter()
-                .filter_map(|(k, v)| {
-                    if let Some(s) = v.as_str() {
-                        Some(format!("{}: {}", k, s))
-                    } else {
-                        None
-                    }
-                })
-                .collect();
-            parts.sort();
+            let mut parts = Vec::new();
+            flatten_sketch_value(None, &obj, &mut parts);
             Ok(parts.join("\n"))
         }
     }
@@ -88,41 +77,92 @@ where
     deserializer.deserialize_any(SolutionSketchVisitor)
 }
 
-impl Problem {
-    pub fn load_all() -> Result<Vec<Problem>, Box<dyn std::error::Error>> {
-        // Build list of possible paths to check
-        let mut possible_paths = Vec::new();
-        
-        // 1. FIRST: Try app data directory (where problems should be after initialization)
-        #[cfg(target_os = "macos")]
-        {
-            if let Some(home) = std::env::var_os("HOME") {
-                let mut dir = std::path::PathBuf::from(home);
-                dir.push("Library/Application Support/com.zacnwo.zos");
-                dir.push("problems");
-                possible_paths.push(dir);
+/// Flatten a structured sketch into lines, recursing into nested objects and
+/// arrays so no content is dropped. Nested keys are joined with '.'
+/// (`step.a: x`) and object keys are visited in sorted order for stable output.
+fn flatten_sketch_value(prefix: Option<&str>, value: &serde_json::Value, out: &mut Vec<String>) {
+    use serde_json::Value;
+    
+    match value {
+        Value::Null => {}
+        Value::Array(items) => {
+            for item in items {
+                flatten_sketch_value(prefix, item, out);
             }
         }
-        
-        #[cfg(target_os = "windows")]
-        {
-            if let Some(appdata) = std::env::var_os("APPDATA") {
-                let mut dir = std::path::PathBuf::from(appdata);
-                dir.push("com.zacnwo.zos");
-                dir.push("problems");
-                possible_paths.push(dir);
+        Value::Object(obj) => {
+            let mut keys: Vec<&String> = obj.keys().collect();
+            keys.sort();
+            for key in keys {
+                let path = match prefix {
+                    Some(p) => format!("{}.{}", p, key),
+                    None => key.clone(),
+                };
+                flatten_sketch_value(Some(&path), &obj[key], out);
             }
         }
-        
-        #[cfg(target_os = "linux")]
-        {
-            if let Some(home) = std::env::var_os("HOME") {
-                let mut dir = std::path::PathBuf::from(home);
-                dir.push(".local/share/com.zacnwo.zos");
-                dir.push("problems");
-                possible_paths.push(dir);
-            }
+        leaf => {
+            let text = match leaf {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            out.push(match prefix {
+                Some(p) => format!("{}: {}", p, text),
+                None => text,
+            });
         }
+    }
+}
+
+/// A topic reassignment made (or proposed) by `Problem::repair_orphan_topics`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopicRepair {
+    pub problem_id: String,
+    pub old_topic: String,
+    pub new_topic: String,
+}
+
+/// Skill keys a problem topic must match to take part in selection
+pub fn known_topics() -> Vec<String> {
+    let mut topics: Vec<String> = KNOWN_SKILLS.iter().map(|skill| skill.to_string()).collect();
+    topics.sort();
+    topics
+}
+
+/// Distinct problem topics that don't correspond to any known skill once
+/// normalized (as selection does), sorted
+pub fn orphan_topics(problems: &[Problem]) -> Vec<String> {
+    let known: Vec<String> = known_topics().iter().map(|t| normalize_topic(t)).collect();
+    let mut orphans: Vec<String> = problems.iter()
+        .filter(|p| !known.contains(&normalize_topic(&p.topic)))
+        .map(|p| p.topic.clone())
+        .collect();
+    orphans.sort();
+    orphans.dedup();
+    orphans
+}
+
+fn warn_orphan_topics(problems: &[Problem]) {
+    let orphans = orphan_topics(problems);
+    if !orphans.is_empty() {
+        tracing::warn!(
+            orphan_topics = ?orphans,
+            "Problems have topics that match no known skill; they will not be selected or update skills (run repair_problem_topics to fix)"
+        );
+    }
+}
+
+impl Problem {
+    /// Locate the problems directory, returning the searched paths if none exists
+    fn find_problems_dir() -> Result<std::path::PathBuf, Vec<std::path::PathBuf>> {
+        // An explicit directory is used exclusively
+        if let Some(dir) = problems_dir_override() {
+            return if dir.is_dir() { Ok(dir) } else { Err(vec![dir]) };
+        }
+
+        // Build list of possible paths to check
+        // 1. FIRST: Try app data directory (where problems should be after initialization)
+        let mut possible_paths = vec![crate::paths::problems_dir()];
         
         // 2. Try relative to current working directory (development)
         possible_paths.push(std::path::PathBuf::from("problems"));
@@ -151,9 +191,37 @@ impl Problem {
             }
         }
 
-        let problems_dir = match problems_dir {
-            Some(dir) => dir,
-            None => {
+        problems_dir.ok_or(possible_paths)
+    }
+
+    /// Read a single problem file, clamping an out-of-range difficulty
+    pub fn load_from_file(path: &std::path::Path) -> Result<Problem, Box<dyn std::error::Error>> {
+        let content = std::fs::read_to_string(path)?;
+        let mut problem: Problem = serde_json::from_str(&content)?;
+        
+        let clamped = if problem.difficulty.is_nan() {
+            MIN_DIFFICULTY
+        } else {
+            problem.difficulty.clamp(MIN_DIFFICULTY, MAX_DIFFICULTY)
+        };
+        if clamped != problem.difficulty {
+            tracing::warn!(
+                problem_id = %problem.id,
+                path = ?path,
+                difficulty = problem.difficulty,
+                clamped = clamped,
+                "Problem difficulty out of range, clamping"
+            );
+            problem.difficulty = clamped;
+        }
+        
+        Ok(problem)
+    }
+
+    pub fn load_all() -> Result<Vec<Problem>, Box<dyn std::error::Error>> {
+        let problems_dir = match Self::find_problems_dir() {
+            Ok(dir) => dir,
+            Err(possible_paths) => {
                 // If no problems directory found, return empty (will trigger problem generation)
                 eprintln!("Warning: No problems directory found. Searched: {:?}", possible_paths);
                 return Ok(Vec::new());
@@ -169,9 +237,7 @@ impl Problem {
             let path = entry.path();
             
             if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                let content = std::fs::read_to_string(&path)?;
-                let problem: Problem = serde_json::from_str(&content)?;
-                problems.push(problem);
+                problems.push(Self::load_from_file(&path)?);
             }
         }
 
@@ -182,22 +248,80 @@ impl Problem {
                 for entry in entries.flatten() {
                     let path = entry.path();
                     if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                        if let Ok(content) = std::fs::read_to_string(&path) {
-                            if let Ok(problem) = serde_json::from_str::<Problem>(&content) {
-                                problems.push(problem);
-                            }
+                        if let Ok(problem) = Self::load_from_file(&path) {
+                            problems.push(problem);
                         }
                     }
                 }
             }
         }
 
+        warn_orphan_topics(&problems);
+
         Ok(problems)
     }
     
+    /// Reassign problems whose topic is not a known skill to the closest known
+    /// skill, rewriting the problem files in place. With `dry_run` nothing is written.
+    pub fn repair_orphan_topics(dry_run: bool) -> Result<Vec<TopicRepair>, ZosError> {
+        let problems_dir = match Self::find_problems_dir() {
+            Ok(dir) => dir,
+            Err(_) => return Ok(Vec::new()),
+        };
+        let known = known_topics();
+        let mut repairs = Vec::new();
+        
+        for dir in [problems_dir.clone(), problems_dir.join("autogen")] {
+            let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                    continue;
+                }
+                
+                let content = std::fs::read_to_string(&path)
+                    .map_err(|e| ZosError::new(format!("Failed to read problem file: {}", e), "io")
+                        .with_context(format!("path: {:?}", path)))?;
+                let mut value: serde_json::Value = match serde_json::from_str(&content) {
+                    Ok(value) => value,
+                    Err(e) => {
+                        tracing::warn!(path = ?path, error = %e, "Skipping unparseable problem file");
+                        continue;
+                    }
+                };
+                
+                let Some(topic) = value.get("topic").and_then(|t| t.as_str()).map(str::to_string) else { continue };
+                if known.contains(&topic) {
+                    continue;
+                }
+                let Some(new_topic) = closest_known_topic(&topic, &known) else { continue };
+                
+                let problem_id = value.get("id").and_then(|id| id.as_str()).unwrap_or_default().to_string();
+                tracing::info!(problem_id = %problem_id, old_topic = %topic, new_topic = %new_topic, dry_run = dry_run, "Repairing orphan topic");
+                
+                if !dry_run {
+                    value["topic"] = serde_json::Value::String(new_topic.clone());
+                    let json = serde_json::to_string_pretty(&value)?;
+                    std::fs::write(&path, json)
+                        .map_err(|e| ZosError::new(format!("Failed to write problem file: {}", e), "io")
+                            .with_context(format!("path: {:?}", path)))?;
+                }
+                
+                repairs.push(TopicRepair { problem_id, old_topic: topic, new_topic });
+            }
+        }
+        
+        Ok(repairs)
+    }
+    
     /// Initialize problems directory by copying from source if needed
     pub fn initialize_problems_dir() {
-        let app_data_problems = get_app_data_problems_dir();
+        if let Some(dir) = problems_dir_override() {
+            eprintln!("Using configured problems directory: {:?}", dir);
+            return;
+        }
+
+        let app_data_problems = crate::paths::problems_dir();
         
         // Check if app data directory has problems
         let has_problems = app_data_problems.exists() && 
@@ -268,41 +392,6 @@ impl Problem {
     }
 }
 
-fn get_app_data_problems_dir() -> std::path::PathBuf {
-    #[cfg(target_os = "macos")]
-    {
-        if let Some(home) = std::env::var_os("HOME") {
-            let mut dir = std::path::PathBuf::from(home);
-            dir.push("Library/Application Support/com.zacnwo.zos");
-            dir.push("problems");
-            return dir;
-        }
-    }
-    
-    #[cfg(target_os = "windows")]
-    {
-        if let Some(appdata) = std::env::var_os("APPDATA") {
-            let mut dir = std::path::PathBuf::from(appdata);
-            dir.push("com.zacnwo.zos");
-            dir.push("problems");
-            return dir;
-        }
-    }
-    
-    #[cfg(target_os = "linux")]
-    {
-        if let Some(home) = std::env::var_os("HOME") {
-            let mut dir = std::path::PathBuf::from(home);
-            dir.push(".local/share/com.zacnwo.zos");
-            dir.push("problems");
-            return dir;
-        }
-    }
-    
-    // Fallback
-    std::path::PathBuf::from("problems")
-}
-
 fn copy_dir_all(src: &std::path::Path, dst: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
     std::fs::create_dir_all(dst)?;
     
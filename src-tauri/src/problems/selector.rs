@@ -1,73 +1,153 @@
-use crate::problems::problem::Problem;
+use std::collections::{HashMap, HashSet};
+use rand::Rng;
+use crate::problems::problem::{Problem, MAX_DIFFICULTY};
+use crate::sessions::SessionRecord;
 use crate::skills::model::SkillVector;
 
-pub fn pick_problem(skills: &SkillVector, problems: &Vec<Problem>) -> Option<Problem> {
-    if problems.is_empty() {
-        return None;
-    }
+const SECS_PER_DAY: i64 = 86_400;
 
-    // Find the weakest skill
-    let weakest = match skills.get_weakest_skill() {
-        Some((skill_name, _)) => skill_name,
-        None => return problems.first().cloned(),
+/// Lowest difficulty `target_difficulty` aims for
+const MIN_TARGET_DIFFICULTY: f32 = 0.3;
+
+/// Difficulty to aim for on a skill: `1.0 - skill_value`, never below 0.3.
+/// Selection, generation, the daily plan and prefetching all use this.
+pub fn target_difficulty(skill_value: f32) -> f32 {
+    (1.0 - skill_value).clamp(MIN_TARGET_DIFFICULTY, MAX_DIFFICULTY)
+}
+
+/// Anneal difficulty based on success rate
+/// - If success > 0.7 → increase difficulty by +0.1
+/// - If success < 0.4 → decrease difficulty by -0.1
+/// - Else → leave unchanged
+/// Always clamp to [0.1, 1.0]
+pub fn anneal_difficulty(base: f32, success: f32) -> f32 {
+    let new_diff = if success > 0.7 {
+        base + 0.1
+    } else if success < 0.4 {
+        base - 0.1
+    } else {
+        base
     };
+    
+    new_diff.max(0.1).min(1.0)
+}
 
-    // Filter problems for the weakest skill and randomly pick from easiest ones
+/// The skill's stored target difficulty, or `default_base` if none is stored
+pub fn current_target_difficulty(targets: &HashMap<String, f32>, skill: &str, default_base: f32) -> f32 {
+    targets.get(skill).copied().unwrap_or(default_base)
+}
+
+/// Anneal from the skill's last stored target (or `default_base` the first
+/// time) and store the result, so progress carries into the next call.
+/// Returns the base used and the annealed difficulty.
+pub fn next_target_difficulty(
+    targets: &mut HashMap<String, f32>,
+    skill: &str,
+    default_base: f32,
+    success: f32,
+) -> (f32, f32) {
+    let base = current_target_difficulty(targets, skill, default_base);
+    let annealed = anneal_difficulty(base, success);
+    targets.insert(skill.to_string(), annealed);
+    (base, annealed)
+}
+
+/// The candidate whose difficulty is closest to `target`, breaking ties at random
+fn closest_to_target<'a, R: Rng + ?Sized>(candidates: &[&'a Problem], target: f32, rng: &mut R) -> Option<&'a Problem> {
     use rand::seq::SliceRandom;
-    use rand::thread_rng;
     
-    let matching_problems: Vec<&Problem> = problems
-        .iter()
-        .filter(|p| p.topic == weakest)
+    let best_distance = candidates.iter()
+        .map(|p| (p.difficulty - target).abs())
+        .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))?;
+    
+    let closest: Vec<&Problem> = candidates.iter()
+        .filter(|p| ((p.difficulty - target).abs() - best_distance).abs() < f32::EPSILON)
+        .copied()
         .collect();
     
-    if !matching_problems.is_empty() {
-        // Find minimum difficulty
-        let min_diff = matching_problems.iter()
-            .map(|p| p.difficulty)
-            .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
-            .unwrap_or(0.0);
-        
-        // Filter to easiest problems and randomly pick one
-        let easiest: Vec<&Problem> = matching_problems.iter()
-            .filter(|p| (p.difficulty - min_diff).abs() < f32::EPSILON)
-            .copied()
-            .collect();
-        
-        let mut rng = thread_rng();
-        easiest.choose(&mut rng).cloned().cloned()
-    } else {
-        // If no problems for weakest skill, randomly pick from easiest overall
-        let min_diff = problems.iter()
-            .map(|p| p.difficulty)
-            .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
-            .unwrap_or(0.0);
-        
-        let easiest: Vec<&Problem> = problems.iter()
-            .filter(|p| (p.difficulty - min_diff).abs() < f32::EPSILON)
-            .collect();
-        
-        let mut rng = thread_rng();
-        easiest.choose(&mut rng).cloned().cloned()
+    closest.choose(rng).copied()
+}
+
+/// Ids of problems attempted in the `window_days` days before `now` (a Unix
+/// timestamp). These are excluded from recommendations; older ones can come back.
+pub fn completed_problem_ids(sessions: &[SessionRecord], now: i64, window_days: u32) -> HashSet<String> {
+    let cutoff = now - i64::from(window_days) * SECS_PER_DAY;
+    sessions.iter()
+        .filter(|s| s.timestamp >= cutoff)
+        .map(|s| s.problem_id.clone())
+        .collect()
+}
+
+/// Pick a previously failed problem in `skill` to review, at random with each
+/// failed session adding weight to its problem: the most recent failure
+/// counts 1, the next 1/2, then 1/3 and so on, multiplied by one plus the
+/// number of "incorrect" Step 2 assessments. Only problems in `problems` are
+/// candidates; returns None when `skill` has no failed attempts at them.
+pub fn pick_review_problem<'a, R: Rng + ?Sized>(
+    sessions: &[SessionRecord],
+    skill: &str,
+    problems: &'a [Problem],
+    rng: &mut R,
+) -> Option<&'a Problem> {
+    use rand::distributions::{Distribution, WeightedIndex};
+    use crate::analytics::is_failed_session;
+
+    let mut failures: Vec<&SessionRecord> = sessions.iter()
+        .filter(|s| s.skill == skill && is_failed_session(s))
+        .collect();
+    // Most recent first
+    failures.sort_by_key(|s| std::cmp::Reverse(s.timestamp));
+
+    let mut candidates: Vec<&Problem> = Vec::new();
+    let mut weights: Vec<f32> = Vec::new();
+    let mut rank = 0;
+    for failure in failures {
+        let Some(index) = problems.iter().position(|p| p.id == failure.problem_id) else {
+            continue;
+        };
+        rank += 1;
+        let incorrect = failure.assessments.iter().filter(|a| *a == "incorrect").count();
+        let weight = (1 + incorrect) as f32 / rank as f32;
+        match candidates.iter().position(|p| p.id == failure.problem_id) {
+            Some(existing) => weights[existing] += weight,
+            None => {
+                candidates.push(&problems[index]);
+                weights.push(weight);
+            }
+        }
     }
+
+    let distribution = WeightedIndex::new(&weights).ok()?;
+    Some(candidates[distribution.sample(rng)])
+}
+
+/// Pick a problem for the weakest skill (see `pick_problem_from_list`)
+pub fn pick_problem<R: Rng + ?Sized>(skills: &SkillVector, problems: &[Problem], rng: &mut R) -> Option<Problem> {
+    let refs: Vec<&Problem> = problems.iter().collect();
+    pick_problem_from_list(skills, &refs, rng).cloned()
 }
 
-/// Pick a problem from a list of problems (helper for filtering completed problems)
-pub fn pick_problem_from_list<'a>(skills: &'a SkillVector, problems: &'a Vec<&'a Problem>) -> Option<&'a Problem> {
+/// Pick a problem from a list of problems (helper for filtering completed problems).
+/// Prefers the weakest skill's problems, falling back to the whole list when it has
+/// none, and picks the one closest to the skill's target difficulty. Ties
+/// are broken with `rng`.
+pub fn pick_problem_from_list<'a, R: Rng + ?Sized>(
+    skills: &SkillVector,
+    problems: &[&'a Problem],
+    rng: &mut R,
+) -> Option<&'a Problem> {
     if problems.is_empty() {
         return None;
     }
 
     // Find the weakest skill
-    let weakest = match skills.get_weakest_skill() {
-        Some((skill_name, _)) => skill_name,
+    let (weakest, skill_value) = match skills.get_weakest_skill_excluding_with(&[], rng) {
+        Some(weakest) => weakest,
         None => return problems.first().copied(),
     };
+    let target = target_difficulty(skill_value);
 
-    // Filter problems for the weakest skill and randomly pick from easiest ones
-    use rand::seq::SliceRandom;
-    use rand::thread_rng;
-    
+    // Pick the problem for the weakest skill closest to the target difficulty
     let matching_problems: Vec<&Problem> = problems
         .iter()
         .filter(|p| p.topic == weakest)
@@ -75,46 +155,202 @@ pub fn pick_problem_from_list<'a>(skills: &'a SkillVector, problems: &'a Vec<&'a
         .collect();
     
     if !matching_problems.is_empty() {
-        // Find minimum difficulty
-        let min_diff = matching_problems.iter()
-            .map(|p| p.difficulty)
-            .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
-            .unwrap_or(0.0);
-        
-        // Filter to easiest problems and randomly pick one
-        let easiest: Vec<&Problem> = matching_problems.iter()
-            .filter(|p| (p.difficulty - min_diff).abs() < f32::EPSILON)
-            .copied()
-            .collect();
-        
-        let mut rng = thread_rng();
-        easiest.choose(&mut rng).copied()
+        closest_to_target(&matching_problems, target, rng)
     } else {
-        // If no problems for weakest skill, randomly pick from easiest overall
-        let min_diff = problems.iter()
-            .map(|p| p.difficulty)
-            .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
-            .unwrap_or(0.0);
-        
-        let easiest: Vec<&Problem> = problems.iter()
-            .filter(|p| (p.difficulty - min_diff).abs() < f32::EPSILON)
-            .copied()
-            .collect();
-        
-        let mut rng = thread_rng();
-        easiest.choose(&mut rng).copied()
+        // If no problems for weakest skill, pick the closest to the target overall
+        closest_to_target(problems, target, rng)
+    }
+}
+
+/// Pick a problem while skipping recently shown ones (`recent` is most-recent-first).
+/// A repeat is only allowed when every candidate was recently shown, and then the
+/// least recently shown problem is chosen so the last one never comes up twice in a row.
+pub fn pick_problem_avoiding_recent<'a, R: Rng + ?Sized>(
+    skills: &SkillVector,
+    problems: &[&'a Problem],
+    recent: &[String],
+    rng: &mut R,
+) -> Option<&'a Problem> {
+    let fresh: Vec<&Problem> = problems.iter()
+        .filter(|p| !recent.contains(&p.id))
+        .copied()
+        .collect();
+    
+    if let Some(problem) = pick_problem_from_list(skills, &fresh, rng) {
+        return Some(problem);
+    }
+    
+    problems.iter()
+        .max_by_key(|p| recent.iter().position(|id| id == &p.id).unwrap_or(usize::MAX))
+        .copied()
+}
+
+/// Known alternate spellings for canonical skill keys (already normalized)
+const TOPIC_ALIASES: &[(&str, &str)] = &[
+    ("rl", "rl_theory"),
+    ("reinforcement_learning", "rl_theory"),
+    ("ml", "ml_theory"),
+    ("machine_learning", "ml_theory"),
+    ("research", "ai_research"),
+    ("coding", "coding_debugging"),
+    ("debugging", "coding_debugging"),
+    ("algorithm", "algorithms"),
+    ("algo", "algorithms"),
+    ("production", "production_engineering"),
+    ("analysis", "analysis_math"),
+    ("real_analysis", "analysis_math"),
+    ("putnam", "putnam_competition"),
+    ("proofs", "proof_strategy"),
+    ("logic", "logical_reasoning"),
+];
+
+/// Words in free text (e.g. Step 2 next tasks) that point at a skill.
+/// Multi-word keywords are joined with underscores.
+const SKILL_KEYWORDS: &[(&str, &str)] = &[
+    ("bellman", "rl_theory"),
+    ("mdp", "rl_theory"),
+    ("policy", "rl_theory"),
+    ("q_learning", "rl_theory"),
+    ("value_function", "rl_theory"),
+    ("gradient", "ml_theory"),
+    ("generalization", "ml_theory"),
+    ("overfitting", "ml_theory"),
+    ("loss", "ml_theory"),
+    ("paper", "ai_research"),
+    ("experiment", "ai_research"),
+    ("code", "coding_debugging"),
+    ("bug", "coding_debugging"),
+    ("debug", "coding_debugging"),
+    ("complexity", "algorithms"),
+    ("runtime", "algorithms"),
+    ("sorting", "algorithms"),
+    ("graph", "algorithms"),
+    ("dynamic_programming", "algorithms"),
+    ("deployment", "production_engineering"),
+    ("latency", "production_engineering"),
+    ("epsilon", "analysis_math"),
+    ("limit", "analysis_math"),
+    ("limits", "analysis_math"),
+    ("continuity", "analysis_math"),
+    ("convergence", "analysis_math"),
+    ("olympiad", "putnam_competition"),
+    ("competition", "putnam_competition"),
+    ("induction", "proof_strategy"),
+    ("contradiction", "proof_strategy"),
+    ("contrapositive", "proof_strategy"),
+    ("proof", "proof_strategy"),
+    ("justification", "proof_strategy"),
+    ("logical", "logical_reasoning"),
+    ("fallacy", "logical_reasoning"),
+    ("quantifier", "logical_reasoning"),
+    ("quantifiers", "logical_reasoning"),
+];
+
+/// Words that only say a task involves proofs, which nearly every task does.
+/// They decide the skill only when nothing more specific is mentioned.
+const GENERIC_WORDS: &[&str] = &["proof", "proofs"];
+
+/// Best guess at the skill a piece of free text is about: skill names,
+/// topic aliases and keywords are counted over its words and word pairs, and
+/// the most mentioned known skill wins (ties broken alphabetically). Generic
+/// words count only when nothing else matches. None when nothing matches.
+pub fn infer_skill(text: &str, known: &[String]) -> Option<String> {
+    let words: Vec<String> = text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect();
+    let pairs = words.windows(2).map(|pair| pair.join("_"));
+    
+    let mut counts: std::collections::BTreeMap<String, usize> = std::collections::BTreeMap::new();
+    let mut generic_counts: std::collections::BTreeMap<String, usize> = std::collections::BTreeMap::new();
+    for token in words.iter().cloned().chain(pairs) {
+        let keyword = SKILL_KEYWORDS.iter()
+            .find(|(word, _)| *word == token)
+            .map(|(_, skill)| skill.to_string());
+        let skill = keyword.unwrap_or_else(|| normalize_topic(&token));
+        if known.contains(&skill) {
+            let tally = if GENERIC_WORDS.contains(&token.as_str()) { &mut generic_counts } else { &mut counts };
+            *tally.entry(skill).or_default() += 1;
+        }
+    }
+    if counts.is_empty() {
+        counts = generic_counts;
     }
+    
+    // BTreeMap iterates alphabetically, so max_by_key keeps the last of equal
+    // counts; reverse to keep the first
+    counts.into_iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map(|(skill, _)| skill)
+}
+
+/// Normalize a topic for comparison: trim, lowercase, turn hyphens and
+/// whitespace into underscores, then map known aliases to the canonical skill key
+pub fn normalize_topic(topic: &str) -> String {
+    let normalized: String = topic.trim()
+        .to_lowercase()
+        .split(|c: char| c == '-' || c == '_' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+    
+    TOPIC_ALIASES.iter()
+        .find(|(alias, _)| *alias == normalized)
+        .map(|(_, canonical)| canonical.to_string())
+        .unwrap_or(normalized)
+}
+
+/// Edit distance between two strings (insertions, deletions, substitutions)
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b_chars.len()).collect();
+    let mut curr = vec![0; b_chars.len() + 1];
+    
+    for (i, ca) in a.chars().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b_chars.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j + 1] + 1).min(curr[j] + 1).min(prev[j] + cost);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b_chars.len()]
+}
+
+/// Closest known topic to `topic`: an exact match after normalization if there
+/// is one, otherwise the known topic with the smallest edit distance
+/// (ties broken alphabetically)
+pub fn closest_known_topic(topic: &str, known: &[String]) -> Option<String> {
+    let normalized = normalize_topic(topic);
+    if let Some(exact) = known.iter().find(|k| **k == normalized) {
+        return Some(exact.clone());
+    }
+    
+    known.iter()
+        .min_by(|a, b| {
+            levenshtein(&normalized, a).cmp(&levenshtein(&normalized, b)).then(a.cmp(b))
+        })
+        .cloned()
 }
 
 pub fn get_problems_by_topic(problems: &Vec<Problem>, topic: &str) -> Vec<Problem> {
-    // Filter by exact topic match (case-sensitive, no whitespace)
+    // Compare normalized topics so "rl-theory" / "RL_theory" match "rl_theory"
+    let expected_topic = normalize_topic(topic);
     let filtered: Vec<Problem> = problems
         .iter()
         .filter(|p| {
-            // Trim and compare topics exactly
-            let p_topic = p.topic.trim();
-            let expected_topic = topic.trim();
-            p_topic == expected_topic
+            let matches = normalize_topic(&p.topic) == expected_topic;
+            if matches && p.topic.trim() != topic.trim() {
+                tracing::debug!(
+                    problem_id = %p.id,
+                    problem_topic = %p.topic,
+                    topic = %topic,
+                    "Topic matched only after normalization"
+                );
+            }
+            matches
         })
         .cloned()
         .collect();
@@ -144,3 +380,43 @@ pub fn get_problems_by_topic(problems: &Vec<Problem>, topic: &str) -> Vec<Proble
     filtered
 }
 
+/// Canonical form of a tag: trimmed and lowercased
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+/// Problems carrying `tag` (case-insensitive)
+pub fn get_problems_by_tag(problems: &[Problem], tag: &str) -> Vec<Problem> {
+    let expected = normalize_tag(tag);
+    if expected.is_empty() {
+        return Vec::new();
+    }
+    problems
+        .iter()
+        .filter(|p| p.tags.iter().any(|t| normalize_tag(t) == expected))
+        .cloned()
+        .collect()
+}
+
+/// Problems with difficulty in `[min, max]`, optionally limited to one topic,
+/// sorted by ascending difficulty. Returns nothing when `min > max`.
+pub fn get_problems_by_difficulty(
+    problems: &[Problem],
+    topic: Option<&str>,
+    min: f32,
+    max: f32,
+) -> Vec<Problem> {
+    if min > max {
+        return Vec::new();
+    }
+    
+    let mut filtered: Vec<Problem> = problems
+        .iter()
+        .filter(|p| topic.is_none_or(|t| normalize_topic(&p.topic) == normalize_topic(t)))
+        .filter(|p| p.difficulty >= min && p.difficulty <= max)
+        .cloned()
+        .collect();
+    
+    filtered.sort_by(|a, b| a.difficulty.partial_cmp(&b.difficulty).unwrap_or(std::cmp::Ordering::Equal));
+    filtered
+}
@@ -0,0 +1,140 @@
+use std::collections::HashSet;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::error::ZosError;
+use crate::problems::generator::hash_statement;
+use crate::problems::problem::{Problem, MAX_DIFFICULTY, MIN_DIFFICULTY};
+
+/// Outcome of a bulk import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub imported: usize,
+    /// Duplicates of an existing (or earlier imported) statement
+    pub skipped: usize,
+    /// Failed validation; see `errors`
+    pub rejected: usize,
+    /// One message per rejected problem
+    pub errors: Vec<String>,
+}
+
+/// Check that a problem is usable: a topic, a statement and a difficulty in
+/// [MIN_DIFFICULTY, MAX_DIFFICULTY], the range loading clamps to
+pub fn validate_problem(problem: &Problem) -> Result<(), String> {
+    if problem.topic.trim().is_empty() {
+        return Err("missing topic".to_string());
+    }
+    if problem.statement.trim().is_empty() {
+        return Err("empty statement".to_string());
+    }
+    if !(MIN_DIFFICULTY..=MAX_DIFFICULTY).contains(&problem.difficulty) {
+        return Err(format!(
+            "difficulty {} is outside [{}, {}]",
+            problem.difficulty, MIN_DIFFICULTY, MAX_DIFFICULTY
+        ));
+    }
+    Ok(())
+}
+
+/// File name for a problem: its id with anything but `[A-Za-z0-9_-]` replaced
+fn problem_file_name(id: &str) -> String {
+    let safe: String = id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect();
+    format!("{}.json", safe)
+}
+
+/// Validate and write `problems` into `dir`, one file per problem, skipping any
+/// whose statement hash is already in `existing_hashes`
+pub fn import_problems_into(
+    problems: Vec<Problem>,
+    existing_hashes: &mut HashSet<String>,
+    dir: &Path,
+) -> Result<ImportSummary, ZosError> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| ZosError::new(format!("Failed to create problems directory: {}", e), "io")
+            .with_context(format!("path: {:?}", dir)))?;
+    
+    let batch_time = chrono::Utc::now().timestamp();
+    let mut summary = ImportSummary::default();
+    for (index, mut problem) in problems.into_iter().enumerate() {
+        if let Err(reason) = validate_problem(&problem) {
+            let label = if problem.id.is_empty() { format!("#{}", index) } else { problem.id.clone() };
+            summary.rejected += 1;
+            summary.errors.push(format!("{}: {}", label, reason));
+            continue;
+        }
+        
+        if !existing_hashes.insert(hash_statement(&problem.statement)) {
+            summary.skipped += 1;
+            continue;
+        }
+        
+        if problem.id.trim().is_empty() {
+            problem.id = format!("import_{}_{}", batch_time, index);
+        }
+        let path = dir.join(problem_file_name(&problem.id));
+        if path.exists() {
+            summary.rejected += 1;
+            summary.errors.push(format!("{}: a problem with this id already exists", problem.id));
+            continue;
+        }
+        
+        let json = serde_json::to_string_pretty(&problem)?;
+        std::fs::write(&path, json)
+            .map_err(|e| ZosError::new(format!("Failed to write problem file: {}", e), "io")
+                .with_context(format!("path: {:?}", path)))?;
+        summary.imported += 1;
+    }
+    
+    Ok(summary)
+}
+
+/// Import a JSON array of problems from `path` into the app-data problems directory
+pub fn import_problems(path: &Path) -> Result<ImportSummary, ZosError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| ZosError::new(format!("Failed to read import file: {}", e), "io")
+            .with_context(format!("path: {:?}", path)))?;
+    let problems: Vec<Problem> = serde_json::from_str(&content)
+        .map_err(|e| ZosError::new(format!("Import file is not a JSON array of problems: {}", e), "json_parse")
+            .with_context(format!("path: {:?}", path)))?;
+    
+    let existing = Problem::load_all()
+        .map_err(|e| ZosError::new(format!("Failed to load existing problems: {}", e), "io"))?;
+    let mut hashes: HashSet<String> = existing.iter()
+        .map(|p| hash_statement(&p.statement))
+        .collect();
+    
+    let summary = import_problems_into(problems, &mut hashes, &crate::paths::problems_dir())?;
+    tracing::info!(
+        path = ?path,
+        imported = summary.imported,
+        skipped = summary.skipped,
+        rejected = summary.rejected,
+        "Imported problems"
+    );
+    Ok(summary)
+}
+
+/// Write `problems` to `path` as a single JSON array, returning how many were written
+pub fn export_problems_to(problems: &[Problem], path: &Path) -> Result<usize, ZosError> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| ZosError::new(format!("Failed to create export directory: {}", e), "io")
+                .with_context(format!("path: {:?}", parent)))?;
+    }
+    let json = serde_json::to_string_pretty(problems)?;
+    std::fs::write(path, json)
+        .map_err(|e| ZosError::new(format!("Failed to write export file: {}", e), "io")
+            .with_context(format!("path: {:?}", path)))?;
+    Ok(problems.len())
+}
+
+/// Export every problem, including autogenerated ones, to `path`.
+/// The file can be read back with `import_problems`.
+pub fn export_problems(path: &Path) -> Result<usize, ZosError> {
+    let problems = Problem::load_all()
+        .map_err(|e| ZosError::new(format!("Failed to load problems: {}", e), "io"))?;
+    let count = export_problems_to(&problems, path)?;
+    tracing::info!(path = ?path, count = count, "Exported problems");
+    Ok(count)
+}
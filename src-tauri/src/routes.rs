@@ -1,4 +1,5 @@
 use crate::pipelines::proof::{
+    assemble_step1_prompt, assemble_step2_prompt,
     call_deepseek_step1, call_deepseek_step2, ProofIssue, Step1Response, Step2Response,
 };
 use crate::problems::{problem::Problem, selector, generator};
@@ -6,54 +7,177 @@ use crate::skills::{model::SkillVector, store as skills_store};
 use crate::memory::store;
 use crate::sessions::{SessionRecord, save_session, load_all_sessions, recent_success_rate};
 use crate::brain::TaskDirective;
-use crate::state::session::{get_state, set_state, reset_state, log_state, ProofState};
+use crate::state::session::{
+    begin_step, get_state, set_state, reset_state, log_state, session_id_for, state_after_step1, ProofState, ProofStateSummary, RecordPolicy,
+};
 use crate::state::app::AppState;
+use crate::analytics::{self, AnalyticsPayload, SessionSummary};
+use crate::error::{ZosError, STAGE_OFFLINE};
 use chrono::Utc;
 use tauri::State;
+use tracing::Instrument;
 
-/// Anneal difficulty based on success rate
-/// - If success > 0.7 → increase difficulty by +0.1
-/// - If success < 0.4 → decrease difficulty by -0.1
-/// - Else → leave unchanged
-/// Always clamp to [0.1, 1.0]
-fn anneal_difficulty(base: f32, success: f32) -> f32 {
-    let new_diff = if success > 0.7 {
-        base + 0.1
-    } else if success < 0.4 {
-        base - 0.1
-    } else {
-        base
+/// The difficulty to pitch a new problem for `skill` at: its stored target,
+/// or `default_base` before any attempt was recorded. Only reads the target,
+/// so asking for recommendations repeatedly doesn't move it.
+async fn current_target_difficulty(skill: &str, default_base: f32) -> f32 {
+    let targets = skills_store::load_target_difficulties().await;
+    selector::current_target_difficulty(&targets, skill, default_base)
+}
+
+/// Anneal from the skill's stored target difficulty and write the result back.
+/// Called once per recorded attempt. A failed save only costs the
+/// carried-over progress, so it is logged.
+async fn anneal_target_difficulty(skill: &str, default_base: f32, success: f32) -> (f32, f32) {
+    let mut targets = skills_store::load_target_difficulties().await;
+    let (base, annealed) = selector::next_target_difficulty(&mut targets, skill, default_base, success);
+    if let Err(e) = skills_store::save_target_difficulties(&targets).await {
+        tracing::warn!(skill = %skill, error = %e, "Failed to save target difficulty");
+    }
+    (base, annealed)
+}
+
+/// Every problem in the bank, for commands that report `ZosError`s
+fn load_problems() -> Result<Vec<Problem>, ZosError> {
+    Problem::load_all()
+        .map_err(|e| ZosError::new(format!("Failed to load problems: {}", e), "io"))
+}
+
+/// Result of a full problem attempt: the Step 1 analysis plus the updated skills
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ProblemAttemptResult {
+    pub step1: Step1Response,
+    pub skills: SkillVector,
+}
+
+/// What is known about an attempt before skills are re-read for `skill_after`
+struct SessionDraft<'a> {
+    problem_id: String,
+    topic: String,
+    user_attempt: String,
+    issues: &'a [ProofIssue],
+    eval_summary: String,
+    skill_before: f32,
+    difficulty: Option<f32>,
+    /// Graded outcome, if the attempt was graded
+    correct: Option<bool>,
+    /// Step 2 per-question assessments
+    assessments: Vec<String>,
+}
+
+/// Build a session record for an attempt and save it.
+/// `skill_after` is read from the current in-memory skills, so call this
+/// after any skill updates for the attempt have been applied.
+async fn record_session(app_state: &AppState, draft: SessionDraft<'_>) -> Result<(), ZosError> {
+    let SessionDraft {
+        problem_id, topic, user_attempt, issues, eval_summary, skill_before, difficulty, correct, assessments,
+    } = draft;
+    
+    let skills_after = store::get_skills(app_state).await?;
+    let skill_after = skills_after.skills.get(&topic)
+        .copied()
+        .unwrap_or(0.5);
+    
+    let issues_list: Vec<String> = issues.iter()
+        .map(|i| format!("{}: {}", i.step_id, i.explanation))
+        .collect();
+    let issue_types: Vec<String> = issues.iter()
+        .map(|i| i.issue_type.clone())
+        .collect();
+    
+    let record = SessionRecord {
+        session_id: crate::sessions::new_session_id(),
+        problem_id,
+        skill: topic,
+        user_attempt,
+        issues: issues_list,
+        eval_summary,
+        skill_before,
+        skill_after,
+        difficulty: difficulty.unwrap_or(0.5),
+        timestamp: Utc::now().timestamp(),
+        correct,
+        assessments,
+        issue_types,
+        revealed: false,
     };
     
-    new_diff.max(0.1).min(1.0)
+    if let Err(e) = save_session(&record).await {
+        tracing::warn!(error = %e, problem_id = %record.problem_id, "Failed to save session record");
+        return Ok(());
+    }
+    
+    // The attempt moves the skill's target difficulty on from recent results
+    let success_rate = recent_success_rate(&record.skill, 5).await.unwrap_or(0.5);
+    let (base_difficulty, annealed_difficulty) =
+        anneal_target_difficulty(&record.skill, selector::target_difficulty(skill_before), success_rate).await;
+    tracing::info!(
+        skill = %record.skill,
+        success_rate = success_rate,
+        base_difficulty = base_difficulty,
+        annealed_difficulty = annealed_difficulty,
+        "Annealed target difficulty after attempt"
+    );
+    
+    Ok(())
 }
 
-#[tauri::command]
-pub async fn step1_analyze_proof(
-    state: State<'_, std::sync::Arc<AppState>>,
+/// How much of the flow a Step 1 run drives
+#[derive(Debug, Clone, Copy)]
+struct Step1Options {
+    /// Which attempts go into session history
+    record: RecordPolicy,
+    /// Skip clarifying questions and leave the session awaiting a solution
+    quick: bool,
+    /// Update skills from the issues found
+    update_skills: bool,
+}
+
+impl Step1Options {
+    /// The full two-step flow: perfect proofs are always recorded, other
+    /// attempts only when `record_attempt` is set
+    fn full(record_attempt: bool) -> Self {
+        let record = if record_attempt { RecordPolicy::Always } else { RecordPolicy::PerfectOnly };
+        Step1Options { record, quick: false, update_skills: true }
+    }
+}
+
+/// Run Step 1 against the current proof state and apply skill updates.
+/// Attempts are recorded according to `options.record`.
+async fn run_step1(
+    app_state: &AppState,
     proof: String,
     problem_id: Option<String>,
     problem_topic: Option<String>,
     problem_difficulty: Option<f32>,
-) -> Result<Step1Response, String> {
-    let app_state = state.inner();
+    options: Step1Options,
+    request_id: &str,
+) -> Result<Step1Response, ZosError> {
+    if app_state.is_offline() {
+        return Err(ZosError::new(
+            "Proof analysis needs the local model. Turn off offline mode to analyze your proof.",
+            STAGE_OFFLINE
+        ));
+    }
+    
+    let session_id = session_id_for(problem_id.as_deref()).to_string();
+    let _step = begin_step(app_state, &session_id)?;
     
     // Check state - Step 1 should only run when AwaitingSolution or AwaitingRevision
-    let current_state = get_state(app_state);
-    log_state(app_state);
+    let current_state = get_state(app_state, &session_id);
+    log_state(app_state, &session_id);
     
     match &current_state {
         ProofState::AwaitingSolution | ProofState::AwaitingRevision { .. } => {
             // Valid state, proceed with Step 1
         }
         ProofState::AwaitingClarifyingAnswers { .. } => {
-            return Err("Please answer the clarifying questions first (Step 2)".to_string());
+            return Err(ZosError::new("Please answer the clarifying questions first (Step 2)", "proof_state"));
         }
     }
     
     // Get skill before update
-    let skills_before = store::get_skills(app_state).await
-        .map_err(|e| format!("Failed to get skills: {}", e))?;
+    let skills_before = store::get_skills(app_state).await?;
     let skill_before = problem_topic.as_ref()
         .and_then(|topic| skills_before.skills.get(topic))
         .copied()
@@ -69,66 +193,195 @@ pub async fn step1_analyze_proof(
         None
     };
     
-    match call_deepseek_step1(app_state, &proof, problem_statement.as_deref()).await {
-        Ok(response) => {
-            // Update state to AwaitingClarifyingAnswers
-            set_state(app_state, ProofState::AwaitingClarifyingAnswers {
-                step1_response: response.clone(),
-            });
-            log_state(app_state);
-            
-            // Update skills based on issues found
+    // Only the model call is cancellable; nothing below runs if it is aborted,
+    // so the proof state never ends up half-transitioned
+    let response = {
+        let state = app_state.clone();
+        let proof = proof.clone();
+        app_state.run_cancellable(request_id, async move {
+            call_deepseek_step1(&state, &proof, problem_statement.as_deref()).await
+        }).await?
+    };
+    
+    // Update state to AwaitingClarifyingAnswers, or back to AwaitingSolution for a quick check
+    set_state(app_state, &session_id, state_after_step1(&response, &proof, options.quick));
+    log_state(app_state, &session_id);
+    
+    // Update skills based on issues found
+    if options.update_skills {
+        store::update_skills(app_state, |skills| {
+            skills.update_from_issues(&response.issues);
+        })
+        .await?;
+    }
+    
+    // Check if proof is perfect (no issues and no questions in a complete analysis)
+    let is_perfect = !response.partial && response.issues.is_empty() && response.questions.is_empty();
+    if is_perfect && options.update_skills {
+        if let Some(topic) = &problem_topic {
             store::update_skills(app_state, |skills| {
-                skills.update_from_issues(&response.issues);
+                skills.update_for_perfect_proof(topic);
             })
-            .await
-            .map_err(|e| format!("Failed to update skills: {}", e))?;
-            
-            // Check if proof is perfect (no issues and no questions)
-            if response.issues.is_empty() && response.questions.is_empty() {
-                if let Some(topic) = &problem_topic {
-                    store::update_skills(app_state, |skills| {
-                        skills.update_for_perfect_proof(topic);
-                    })
-                    .await
-                    .map_err(|e| format!("Failed to update skills for perfect proof: {}", e))?;
-                }
-                
-                // Save session record for perfect proof
-                if let (Some(pid), Some(topic)) = (problem_id, problem_topic) {
-                    let skills_after = store::get_skills(app_state).await
-                        .map_err(|e| format!("Failed to get skills: {}", e))?;
-                    let skill_after = skills_after.skills.get(&topic)
-                        .copied()
-                        .unwrap_or(0.5);
-                    
-                    let issues_list: Vec<String> = response.issues.iter()
-                        .map(|i| format!("{}: {}", i.step_id, i.explanation))
-                        .collect();
-                    
-                    let record = SessionRecord {
-                        session_id: format!("sess_{}", Utc::now().timestamp_millis()),
-                        problem_id: pid,
-                        skill: topic,
-                        user_attempt: proof,
-                        issues: issues_list,
-                        eval_summary: "Perfect solution - no issues, no questions".to_string(),
-                        skill_before,
-                        skill_after,
-                        difficulty: problem_difficulty.unwrap_or(0.5),
-                        timestamp: Utc::now().timestamp(),
-                    };
-                    
-                    if let Err(e) = save_session(&record).await {
-                        tracing::warn!(error = %e, "Failed to save session record for perfect proof");
-                    }
-                }
-            }
-            
-            Ok(response)
+            .await?;
         }
-        Err(e) => Err(format!("Model error: {}", e)),
     }
+    
+    if options.record.records(is_perfect) {
+        if let (Some(pid), Some(topic)) = (problem_id, problem_topic) {
+            let eval_summary = if is_perfect {
+                "Perfect solution - no issues, no questions".to_string()
+            } else {
+                format!("{} issues, {} questions", response.issues.len(), response.questions.len())
+            };
+            // Issues mean the attempt was wrong; questions alone leave it ungraded
+            let correct = if is_perfect {
+                Some(true)
+            } else if !response.issues.is_empty() {
+                Some(false)
+            } else {
+                None
+            };
+            record_session(app_state, SessionDraft {
+                problem_id: pid,
+                topic,
+                user_attempt: proof,
+                issues: &response.issues,
+                eval_summary,
+                skill_before,
+                difficulty: problem_difficulty,
+                correct,
+                assessments: Vec::new(),
+            }).await?;
+        }
+    }
+    
+    Ok(response)
+}
+
+/// Step 1 proof analysis. Pass a `request_id` to be able to abort the
+/// model call with `cancel_request` while it runs.
+#[tauri::command]
+pub async fn step1_analyze_proof(
+    state: State<'_, std::sync::Arc<AppState>>,
+    proof: String,
+    problem_id: Option<String>,
+    problem_topic: Option<String>,
+    problem_difficulty: Option<f32>,
+    request_id: Option<String>,
+) -> Result<Step1Response, ZosError> {
+    let request_id = request_id.unwrap_or_else(crate::logging::new_request_id);
+    let span = tracing::info_span!("request", request_id = %request_id, command = "step1_analyze_proof");
+    run_step1(state.inner(), proof, problem_id, problem_topic, problem_difficulty, Step1Options::full(false), &request_id)
+        .instrument(span)
+        .await
+        .map_err(|e| e.with_request_id(&request_id))
+}
+
+/// Step 1 analysis only, for a quick self-check: returns the structure and
+/// issues, then leaves the session awaiting a solution instead of asking the
+/// clarifying questions. Skills are updated from the issues only when
+/// `update_skills` is set, and the attempt is not recorded in history.
+#[tauri::command]
+pub async fn quick_analyze(
+    state: State<'_, std::sync::Arc<AppState>>,
+    proof: String,
+    problem_id: Option<String>,
+    problem_topic: Option<String>,
+    problem_difficulty: Option<f32>,
+    update_skills: Option<bool>,
+    request_id: Option<String>,
+) -> Result<Step1Response, ZosError> {
+    let request_id = request_id.unwrap_or_else(crate::logging::new_request_id);
+    let span = tracing::info_span!("request", request_id = %request_id, command = "quick_analyze");
+    let options = Step1Options {
+        record: RecordPolicy::Never,
+        quick: true,
+        update_skills: update_skills.unwrap_or(false),
+    };
+    run_step1(state.inner(), proof, problem_id, problem_topic, problem_difficulty, options, &request_id)
+        .instrument(span)
+        .await
+        .map_err(|e| e.with_request_id(&request_id))
+}
+
+/// Analyze an attempt at a specific problem in one call: runs Step 1,
+/// optionally records the session, and returns the updated skill vector
+#[tauri::command]
+pub async fn analyze_problem_attempt(
+    state: State<'_, std::sync::Arc<AppState>>,
+    problem_id: String,
+    problem_topic: String,
+    problem_difficulty: f32,
+    user_attempt: String,
+    record_session: Option<bool>,
+) -> Result<ProblemAttemptResult, ZosError> {
+    let app_state = state.inner();
+    let step1 = run_step1(
+        app_state,
+        user_attempt,
+        Some(problem_id),
+        Some(problem_topic),
+        Some(problem_difficulty),
+        Step1Options::full(record_session.unwrap_or(true)),
+        &crate::logging::new_request_id(),
+    ).await?;
+    
+    let skills = store::get_skills(app_state).await?;
+    
+    Ok(ProblemAttemptResult { step1, skills })
+}
+
+/// Convert Step 2's structured inputs to the JSON strings embedded in the prompt
+fn step2_prompt_inputs(
+    issues: &[ProofIssue],
+    questions: &[String],
+    answers: &[String],
+) -> Result<(String, String, String), ZosError> {
+    let issues_json = serde_json::to_string(issues)
+        .map_err(|e| ZosError::new(format!("Failed to serialize issues: {}", e), "validation"))?;
+    let questions_json = serde_json::to_string(questions)
+        .map_err(|e| ZosError::new(format!("Failed to serialize questions: {}", e), "validation"))?;
+    let answers_json = serde_json::to_string(answers)
+        .map_err(|e| ZosError::new(format!("Failed to serialize answers: {}", e), "validation"))?;
+    Ok((issues_json, questions_json, answers_json))
+}
+
+/// Problem statement for Step 2 context, or a placeholder when unavailable
+fn step2_problem_statement(problem_id: Option<&str>) -> String {
+    problem_id
+        .and_then(|pid| {
+            Problem::load_all()
+                .ok()
+                .and_then(|problems| problems.into_iter().find(|p| p.id == pid))
+        })
+        .map(|p| p.statement)
+        .unwrap_or_else(|| "Problem statement not available".to_string())
+}
+
+/// The exact Step 1 prompt that would be sent for this proof, without calling the model
+#[tauri::command]
+pub fn preview_step1_prompt(
+    proof: String,
+    problem_id: Option<String>,
+) -> Result<String, ZosError> {
+    let problem_statement = problem_id
+        .and_then(|pid| get_problem_by_id(pid).ok())
+        .map(|p| p.statement);
+    assemble_step1_prompt(&proof, problem_statement.as_deref())
+}
+
+/// The exact Step 2 prompt that would be sent for these answers, without calling the model
+#[tauri::command]
+pub fn preview_step2_prompt(
+    proof: String,
+    issues: Vec<ProofIssue>,
+    questions: Vec<String>,
+    answers: Vec<String>,
+    problem_id: Option<String>,
+) -> Result<String, ZosError> {
+    let (issues_json, questions_json, answers_json) = step2_prompt_inputs(&issues, &questions, &answers)?;
+    let problem_statement = step2_problem_statement(problem_id.as_deref());
+    Ok(assemble_step2_prompt(&problem_statement, &proof, &issues_json, &questions_json, &answers_json))
 }
 
 #[tauri::command]
@@ -141,140 +394,146 @@ pub async fn step2_evaluate_answers(
     problem_id: Option<String>,
     problem_topic: Option<String>,
     problem_difficulty: Option<f32>,
-) -> Result<Step2Response, String> {
+) -> Result<Step2Response, ZosError> {
     let app_state = state.inner();
+    let session_id = session_id_for(problem_id.as_deref()).to_string();
+    let _step = begin_step(app_state, &session_id)?;
     
     // Check state - Step 2 should only run when AwaitingClarifyingAnswers
-    let current_state = get_state(app_state);
-    log_state(app_state);
+    let current_state = get_state(app_state, &session_id);
+    log_state(app_state, &session_id);
     
-    match &current_state {
-        ProofState::AwaitingClarifyingAnswers { .. } => {
+    let analyzed_proof = match &current_state {
+        ProofState::AwaitingClarifyingAnswers { proof: analyzed, .. } => {
             // Valid state, proceed with Step 2
+            analyzed.clone()
         }
         ProofState::AwaitingSolution => {
-            return Err("Please submit a solution first (Step 1)".to_string());
+            return Err(ZosError::new("Please submit a solution first (Step 1)", "proof_state"));
         }
         ProofState::AwaitingRevision { .. } => {
-            return Err("Please revise your solution and resubmit (Step 1)".to_string());
+            return Err(ZosError::new("Please revise your solution and resubmit (Step 1)", "proof_state"));
         }
-    }
-    
-    // Convert structured data to JSON strings for the prompt
-    let issues_json = serde_json::to_string(&issues)
-        .map_err(|e| format!("Failed to serialize issues: {}", e))?;
-    let questions_json = serde_json::to_string(&questions)
-        .map_err(|e| format!("Failed to serialize questions: {}", e))?;
-    let answers_json = serde_json::to_string(&answers)
-        .map_err(|e| format!("Failed to serialize answers: {}", e))?;
-
-    // Get problem statement for context (if problem_id is available)
-    let problem_statement = if let Some(pid) = &problem_id {
-        Problem::load_all()
-            .ok()
-            .and_then(|problems| problems.into_iter().find(|p| p.id == *pid))
-            .map(|p| p.statement)
-            .unwrap_or_else(|| "Problem statement not available".to_string())
-    } else {
-        "Problem statement not available".to_string()
     };
+    
+    let (issues_json, questions_json, answers_json) = step2_prompt_inputs(&issues, &questions, &answers)?;
+    let problem_statement = step2_problem_statement(problem_id.as_deref());
 
     // Get skill before update
-    let skills_before = store::get_skills(app_state).await
-        .map_err(|e| format!("Failed to get skills: {}", e))?;
+    let skills_before = store::get_skills(app_state).await?;
     let skill_before = problem_topic.as_ref()
         .and_then(|topic| skills_before.skills.get(topic))
         .copied()
         .unwrap_or(0.5);
 
-    match call_deepseek_step2(app_state, &problem_statement, &proof, &issues_json, &questions_json, &answers_json).await {
-        Ok(response) => {
-            // Update state to AwaitingRevision
-            set_state(app_state, ProofState::AwaitingRevision {
-                step2_response: response.clone(),
-            });
-            log_state(app_state);
-            
-            // Update skills based on evaluation
-            store::update_skills(app_state, |skills| {
-                skills.update_from_evaluation(&response.evaluation);
-            })
-            .await
-            .map_err(|e| format!("Failed to update skills: {}", e))?;
-            
-            // Get skill after update
-            let skills_after = store::get_skills(app_state).await
-                .map_err(|e| format!("Failed to get skills: {}", e))?;
-            let skill_after = problem_topic.as_ref()
-                .and_then(|topic| skills_after.skills.get(topic))
-                .copied()
-                .unwrap_or(0.5);
-
-            // Save session record if we have problem info
-            if let (Some(pid), Some(topic)) = (problem_id, problem_topic) {
-                let issues_list: Vec<String> = issues.iter()
-                    .map(|i| format!("{}: {}", i.step_id, i.explanation))
-                    .collect();
-                
-                let eval_summary = format!("{} evaluations", response.evaluation.len());
-                
-                let record = SessionRecord {
-                    session_id: format!("sess_{}", Utc::now().timestamp_millis()),
-                    problem_id: pid,
-                    skill: topic,
-                    user_attempt: proof.clone(),
-                    issues: issues_list,
-                    eval_summary,
-                    skill_before,
-                    skill_after,
-                    difficulty: problem_difficulty.unwrap_or(0.5),
-                    timestamp: Utc::now().timestamp(),
-                };
-
-                if let Err(e) = save_session(&record).await {
-                    tracing::warn!(error = %e, "Failed to save session record");
-                }
-            }
+    let response = call_deepseek_step2(app_state, &problem_statement, &proof, &issues_json, &questions_json, &answers_json).await?;
+    
+    // Update state to AwaitingRevision
+    // Keep the proof Step 1 saw so a revision can be diffed against it
+    let original_proof = if analyzed_proof.is_empty() { proof.clone() } else { analyzed_proof };
+    set_state(app_state, &session_id, ProofState::AwaitingRevision {
+        step2_response: response.clone(),
+        original_proof,
+    });
+    log_state(app_state, &session_id);
+    
+    // Update skills based on evaluation
+    store::update_skills(app_state, |skills| {
+        skills.update_from_evaluation(&response.evaluation);
+    })
+    .await?;
+    
+    // Save session record if we have problem info
+    if let (Some(pid), Some(topic)) = (problem_id, problem_topic) {
+        let eval_summary = format!("{} evaluations", response.evaluation.len());
+        // Correct only if no revision is needed and no answer was judged incorrect
+        let correct = !response.needs_revision
+            && !response.evaluation.iter().any(|e| e.assessment == "incorrect");
+        record_session(app_state, SessionDraft {
+            problem_id: pid,
+            topic,
+            user_attempt: proof.clone(),
+            issues: &issues,
+            eval_summary,
+            skill_before,
+            difficulty: problem_difficulty,
+            correct: Some(correct),
+            assessments: response.evaluation.iter().map(|e| e.assessment.clone()).collect(),
+        }).await?;
+    }
 
-            // Save skills to persistent store
-            let skills_final = store::get_skills(app_state).await
-                .map_err(|e| format!("Failed to get skills: {}", e))?;
-            if let Err(e) = skills_store::save_skill_vector(&skills_final).await {
-                tracing::warn!(error = %e, "Failed to save skills");
-            }
+    // Save skills to persistent store
+    let skills_final = store::get_skills(app_state).await?;
+    if let Err(e) = skills_store::save_skill_vector(&skills_final).await {
+        tracing::warn!(error = %e, "Failed to save skills");
+    }
 
-            Ok(response)
-        }
-        Err(e) => Err(format!("Model error: {}", e)),
+    Ok(response)
+}
+
+/// The skills a recommendation is drawn from and the one to recommend next
+struct RecommendationTarget {
+    /// Every skill, or only the focus skills in focus mode
+    skills: SkillVector,
+    /// The focus skills; empty outside focus mode
+    focus: Vec<String>,
+    /// Weakest non-mastered skill in `skills`, passing over those on cooldown
+    weakest: Option<(String, f32)>,
+}
+
+/// Pick the skill to recommend next. Selection, precomputed problems and
+/// precomputation all go through here so they honour the same focus,
+/// mastery and cooldown rules.
+async fn recommendation_target(app_state: &AppState) -> Result<RecommendationTarget, ZosError> {
+    let mut skills = store::get_skills(app_state).await?;
+    let focus = skills_store::load_focus_skills().await;
+    if !focus.is_empty() {
+        skills = skills.restricted_to(&focus);
     }
+    let mastered = {
+        let sessions = load_all_sessions().await.unwrap_or_default();
+        crate::skills::mastery::mastered_skills(&skills.skills, &sessions)
+    };
+    let cooldown = crate::config::models::get_model_config().skill_cooldown;
+    let weakest = app_state.next_weakest_skill(&skills, &mastered, cooldown);
+    Ok(RecommendationTarget { skills, focus, weakest })
 }
 
 /// Internal helper function to select a problem (extracted for reuse)
 async fn select_problem_internal(
     app_state: &AppState,
-) -> Result<Problem, String> {
-    let skills = store::get_skills(app_state).await
-        .map_err(|e| format!("Failed to get skills: {}", e))?;
-    let problems = Problem::load_all()
-        .map_err(|e| format!("Failed to load problems: {}", e))?;
+) -> Result<Problem, ZosError> {
+    let RecommendationTarget { skills, focus, weakest } = recommendation_target(app_state).await?;
+    let mut problems = load_problems()?;
+    
+    // Focus mode: only consider the focus skills' problems
+    if !focus.is_empty() {
+        problems.retain(|p| skills.skills.contains_key(&selector::normalize_topic(&p.topic)));
+        tracing::info!(focus = ?focus, "Focus mode: restricting recommendations");
+    }
     
-    // Find weakest skill (now with random selection for ties)
-    let weakest_skill = match skills.get_weakest_skill() {
+    let cooldown = crate::config::models::get_model_config().skill_cooldown;
+    let weakest_skill = match weakest {
         Some((skill_name, _)) => skill_name,
         None => {
             // If no skill found, try to generate for first available skill
             if let Some((skill, _)) = skills.skills.iter().next() {
                 skill.clone()
             } else {
-                return Err("No skills available".to_string());
+                return Err(ZosError::new("No skills available", "problem_selection"));
             }
         }
     };
     
-    // Get list of completed problem IDs to exclude
+    app_state.record_skill_recommended(&weakest_skill, cooldown);
+    // Static picks target the chosen skill rather than recomputing the weakest
+    let pick_skills = skills.restricted_to(std::slice::from_ref(&weakest_skill));
+    
+    // Get list of problems completed within the exclusion window to exclude
     let completed_problem_ids: std::collections::HashSet<String> = {
         let sessions = load_all_sessions().await.unwrap_or_default();
-        sessions.iter().map(|s| s.problem_id.clone()).collect()
+        let window_days = crate::config::models::get_model_config().completed_exclusion_days;
+        selector::completed_problem_ids(&sessions, Utc::now().timestamp(), window_days)
     };
     
     // Get recently used problem IDs (last 3 problems from sessions) to avoid immediate repeats
@@ -298,15 +557,25 @@ async fn select_problem_internal(
     let mut recently_used_problem_ids = session_recently_used;
     recently_used_problem_ids.extend(in_memory_recently_selected);
     
-    // FIRST: Try to get a cached problem (fast, no LLM call) - exclude completed and recently used ones
-    let mut cached = crate::problems::cache::ProblemCache::load_async().await;
-    if let Some(pos) = cached.queue.iter()
-        .position(|p| p.topic == weakest_skill 
-            && !completed_problem_ids.contains(&p.id)
-            && !recently_used_problem_ids.contains(&p.id)) {
-        let problem = cached.queue.remove(pos);
-        // Save updated cache
-        let _ = cached.save_async().await;
+    // FIRST: Try to get a cached problem (fast, no LLM call) - exclude completed and recently used ones.
+    // The cache keeps easier/same/harder bands, so take the one nearest the skill's target difficulty.
+    let cache_target = match skills_store::load_target_difficulties().await.get(&weakest_skill) {
+        Some(target) => *target,
+        None => selector::target_difficulty(skills.skills.get(&weakest_skill).copied().unwrap_or(0.5)),
+    };
+    let taken = {
+        let _cache_guard = app_state.problem_cache_lock.lock().await;
+        let mut cached = crate::problems::cache::ProblemCache::load_async().await;
+        let taken = cached.take_closest(&weakest_skill, cache_target, |p| {
+            !completed_problem_ids.contains(&p.id) && !recently_used_problem_ids.contains(&p.id)
+        });
+        if taken.is_some() {
+            // Save updated cache
+            let _ = cached.save_async().await;
+        }
+        taken
+    };
+    if let Some(problem) = taken {
         tracing::info!(skill = %weakest_skill, problem_id = %problem.id, "Using cached problem (not completed, not recently used)");
         app_state.record_problem_selected(problem.id.clone());
         return Ok(problem);
@@ -318,7 +587,8 @@ async fn select_problem_internal(
             && !recently_used_problem_ids.contains(&p.id))
         .collect();
     
-    if let Some(static_problem) = selector::pick_problem_from_list(&skills, &available_problems) {
+    let picked = app_state.with_selection_rng(|rng| selector::pick_problem_from_list(&pick_skills, &available_problems, rng));
+    if let Some(static_problem) = picked {
         tracing::info!(skill = %weakest_skill, problem_id = %static_problem.id, "Using static problem (not completed, not recently used)");
         app_state.record_problem_selected(static_problem.id.clone());
         return Ok(static_problem.clone());
@@ -334,12 +604,11 @@ async fn select_problem_internal(
     
     if !available_other_skill_problems.is_empty() {
         use rand::seq::SliceRandom;
-        use rand::thread_rng;
-        let mut rng = thread_rng();
-        if let Some(problem) = available_other_skill_problems.choose(&mut rng) {
+        let picked = app_state.with_selection_rng(|rng| available_other_skill_problems.choose(rng).copied());
+        if let Some(problem) = picked {
             tracing::info!(skill = %weakest_skill, selected_skill = %problem.topic, problem_id = %problem.id, "Using problem from different skill for variety");
             app_state.record_problem_selected(problem.id.clone());
-            return Ok((*problem).clone());
+            return Ok(problem.clone());
         }
     }
     
@@ -348,48 +617,54 @@ async fn select_problem_internal(
         .filter(|p| !recently_used_problem_ids.contains(&p.id))
         .collect();
     
-    if let Some(static_problem) = selector::pick_problem_from_list(&skills, &repeatable_problems) {
+    let picked = app_state.with_selection_rng(|rng| selector::pick_problem_from_list(&pick_skills, &repeatable_problems, rng));
+    if let Some(static_problem) = picked {
         tracing::info!(skill = %weakest_skill, problem_id = %static_problem.id, "Using static problem (all completed, avoiding recently used)");
         app_state.record_problem_selected(static_problem.id.clone());
         return Ok(static_problem.clone());
     }
     
-    // Final fallback: allow any problem (including recently used) if nothing else available
-    if let Some(static_problem) = selector::pick_problem(&skills, &problems) {
-        tracing::info!(skill = %weakest_skill, problem_id = %static_problem.id, "Using static problem (final fallback, may be recently used)");
+    // Final fallback: every problem was shown recently, so repeat the least recently shown one
+    let all_problems: Vec<&Problem> = problems.iter().collect();
+    let recently_selected = app_state.get_recently_selected_problems();
+    let picked = app_state.with_selection_rng(|rng| {
+        selector::pick_problem_avoiding_recent(&pick_skills, &all_problems, &recently_selected, rng)
+    });
+    if let Some(static_problem) = picked {
+        tracing::info!(skill = %weakest_skill, problem_id = %static_problem.id, "Using static problem (final fallback, least recently shown)");
         app_state.record_problem_selected(static_problem.id.clone());
-        return Ok(static_problem);
+        return Ok(static_problem.clone());
+    }
+    
+    // Everything below may generate with the model
+    if app_state.is_offline() {
+        tracing::info!(skill = %weakest_skill, "Offline mode: not generating a problem");
+        return Err(ZosError::new("No offline problems left. Turn off offline mode to generate new ones.", STAGE_OFFLINE));
     }
     
     // THIRD: Try to get a task from the daily plan (may generate, but only if needed)
-    if let Some(mut plan) = crate::brain::store::load().await
-        .map_err(|e| format!("Failed to load plan: {}", e))? {
-        if !plan.is_expired() && !plan.tasks.is_empty() {
-            // Pop first directive
-            let directive = plan.tasks.remove(0);
-            
+    if let Some(mut plan) = crate::brain::store::load().await? {
+        let next_task = if plan.is_expired() { None } else { plan.take_next_task() };
+        if let Some(directive) = next_task {
             // Save back reduced plan
             if let Err(e) = crate::brain::store::save(&plan).await {
                 eprintln!("Failed to save updated plan: {}", e);
             }
             
             match directive {
-                TaskDirective::Adaptive { skill, difficulty: base_difficulty } => {
-                    // Apply difficulty annealing based on recent performance
-                    let success_rate = recent_success_rate(&skill, 5).await
-                        .unwrap_or(0.5);
-                    let annealed_difficulty = anneal_difficulty(base_difficulty, success_rate);
+                TaskDirective::Adaptive { skill, difficulty: plan_difficulty } => {
+                    // Use the target annealed from recorded attempts,
+                    // starting from the plan's difficulty the first time
+                    let difficulty = current_target_difficulty(&skill, plan_difficulty).await;
                     
-                tracing::info!(
-                    skill = %skill,
-                    success_rate = success_rate,
-                    base_difficulty = base_difficulty,
-                    annealed_difficulty = annealed_difficulty,
-                    "Plan task with difficulty annealing"
-                );
+                    tracing::info!(
+                        skill = %skill,
+                        difficulty = difficulty,
+                        "Plan task at the skill's target difficulty"
+                    );
                     
-                    // Generate a new problem for this skill with annealed difficulty
-                    match generator::generate_problem(app_state, &skill, annealed_difficulty).await {
+                    // Generate a new problem for this skill at that difficulty
+                    match generator::generate_problem(app_state, &skill, difficulty).await {
                         Ok(problem) => {
                             app_state.record_problem_selected(problem.id.clone());
                             return Ok(problem);
@@ -401,21 +676,13 @@ async fn select_problem_internal(
                     }
                 }
                 TaskDirective::Review { skill } => {
-                    // Find a failed problem for this skill to review
-                    let fails = load_all_sessions().await
-                        .map_err(|e| format!("Failed to load sessions: {}", e))?;
-                    if let Some(fail) = fails.into_iter()
-                        .rev()
-                        .find(|s| s.skill == skill && 
-                             (s.eval_summary.contains("incorrect") || 
-                              s.eval_summary.contains("fail") ||
-                              s.skill_after < s.skill_before)) {
-                        if let Ok(all_problems) = Problem::load_all() {
-                            if let Some(problem) = all_problems.into_iter()
-                                .find(|p| p.id == fail.problem_id) {
-                                app_state.record_problem_selected(problem.id.clone());
-                                return Ok(problem);
-                            }
+                    // Pick a failed problem for this skill, favouring recent and severe failures
+                    let fails = load_all_sessions().await?;
+                    if let Ok(all_problems) = Problem::load_all() {
+                        let picked = app_state.with_selection_rng(|rng| selector::pick_review_problem(&fails, &skill, &all_problems, rng));
+                        if let Some(problem) = picked {
+                            app_state.record_problem_selected(problem.id.clone());
+                            return Ok(problem.clone());
                         }
                     }
                     // Fall through to final fallback
@@ -424,35 +691,30 @@ async fn select_problem_internal(
         }
     }
     
-    // FINAL FALLBACK: Generate a problem with difficulty annealing (slow, LLM call)
+    // FINAL FALLBACK: Generate a problem at the target difficulty (slow, LLM call)
     // Only if no uncompleted problems exist
-    let success_rate = recent_success_rate(&weakest_skill, 5).await
-        .unwrap_or(0.5);
     
-    // Get last difficulty used for this skill, or default based on skill level
+    // The target annealed from recorded attempts, or a default based on
+    // skill level
     let skill_value = skills.skills.get(&weakest_skill).copied().unwrap_or(0.5);
-    let base_difficulty = (0.3_f32).max(1.0 - skill_value);
-    
-    // Anneal difficulty
-    let annealed_difficulty = anneal_difficulty(base_difficulty, success_rate);
+    let default_base = selector::target_difficulty(skill_value);
+    let difficulty = current_target_difficulty(&weakest_skill, default_base).await;
     
     tracing::info!(
         skill = %weakest_skill,
-        success_rate = success_rate,
-        base_difficulty = base_difficulty,
-        annealed_difficulty = annealed_difficulty,
-        "Generating new problem with difficulty annealing (all static problems completed)"
+        difficulty = difficulty,
+        "Generating new problem at the target difficulty (all static problems completed)"
     );
     
-    // Try to generate a problem with annealed difficulty
-    match generator::generate_problem(app_state, &weakest_skill, annealed_difficulty).await {
+    // Try to generate a problem at that difficulty
+    match generator::generate_problem(app_state, &weakest_skill, difficulty).await {
         Ok(problem) => {
             app_state.record_problem_selected(problem.id.clone());
             Ok(problem)
         },
         Err(e) => {
             tracing::warn!(skill = %weakest_skill, error = %e, "Failed to generate problem");
-            Err(format!("No problems available and generation failed: {}", e))
+            Err(e.into())
         }
     }
 }
@@ -460,21 +722,37 @@ async fn select_problem_internal(
 #[tauri::command]
 pub async fn get_recommended_problem(
     state: State<'_, std::sync::Arc<AppState>>,
-) -> Result<Problem, String> {
-    let app_state = state.inner();
-    // Reset state when getting a new problem (user explicitly requested a new problem)
-    reset_state(app_state);
-    log_state(app_state);
+) -> Result<Problem, ZosError> {
+    let request_id = crate::logging::new_request_id();
+    let span = tracing::info_span!("request", request_id = %request_id, command = "get_recommended_problem");
+    let problem = recommend_problem(state.inner())
+        .instrument(span)
+        .await
+        .map_err(|e| e.with_request_id(&request_id))?;
+    // The user asked for a new problem: give it a fresh session without
+    // touching sessions for problems open elsewhere
+    reset_state(state.inner(), &problem.id);
+    log_state(state.inner(), &problem.id);
+    Ok(problem)
+}
+
+/// Body of `get_recommended_problem`, run inside its request span
+async fn recommend_problem(app_state: &std::sync::Arc<AppState>) -> Result<Problem, ZosError> {
     // First, check if we have a precomputed problem ready
     // Try to get one matching the expected difficulty (if we can determine it)
-    let skills = store::get_skills(app_state).await
-        .map_err(|e| format!("Failed to get skills: {}", e))?;
-    let expected_difficulty = skills.get_weakest_skill()
-        .and_then(|(skill, _)| skills.skills.get(&skill).copied())
-        .map(|skill_val| (0.3_f32).max(1.0 - skill_val));
-    
-    if let Some(precomputed) = app_state.take_precomputed_problem(expected_difficulty) {
+    // Only for the skill selection would pick, so focus, mastery and
+    // cooldown apply here too
+    let target = recommendation_target(app_state).await?;
+    let precomputed = target.weakest.and_then(|(skill, value)| {
+        let recently_selected = app_state.get_recently_selected_problems();
+        app_state.take_precomputed_problem(Some(selector::target_difficulty(value)), |p| {
+            selector::normalize_topic(&p.topic) == skill && !recently_selected.contains(&p.id)
+        })
+    });
+    if let Some(precomputed) = precomputed {
         tracing::info!(problem_id = %precomputed.id, difficulty = precomputed.difficulty, "Using precomputed problem");
+        let cooldown = crate::config::models::get_model_config().skill_cooldown;
+        app_state.record_skill_recommended(&selector::normalize_topic(&precomputed.topic), cooldown);
         app_state.record_problem_selected(precomputed.id.clone());
         
         // Trigger precomputation of next problems in background (don't await)
@@ -483,7 +761,7 @@ pub async fn get_recommended_problem(
             if let Err(e) = precompute_next_problems_internal(&app_state_clone, precomputed.difficulty).await {
                 tracing::warn!(error = %e, "Failed to precompute next problems");
             }
-        });
+        }.in_current_span());
         
         return Ok(precomputed);
     }
@@ -498,7 +776,7 @@ pub async fn get_recommended_problem(
         if let Err(e) = precompute_next_problems_internal(&app_state_clone, problem_difficulty).await {
             tracing::warn!(error = %e, "Failed to precompute next problems");
         }
-    });
+    }.in_current_span());
     
     Ok(problem)
 }
@@ -507,17 +785,19 @@ pub async fn get_recommended_problem(
 async fn precompute_next_problems_internal(
     app_state: &AppState,
     base_difficulty: f32,
-) -> Result<(), String> {
-    let skills = store::get_skills(app_state).await
-        .map_err(|e| format!("Failed to get skills: {}", e))?;
-    let weakest_skill = match skills.get_weakest_skill() {
+) -> Result<(), ZosError> {
+    if app_state.is_offline() {
+        return Ok(());
+    }
+    let RecommendationTarget { skills, weakest, .. } = recommendation_target(app_state).await?;
+    let weakest_skill = match weakest {
         Some((skill_name, _)) => skill_name,
         None => {
             // If no skill found, try to generate for first available skill
             if let Some((skill, _)) = skills.skills.iter().next() {
                 skill.clone()
             } else {
-                return Err("No skills available for precomputation".to_string());
+                return Err(ZosError::new("No skills available for precomputation", "problem_selection"));
             }
         }
     };
@@ -587,7 +867,7 @@ async fn precompute_next_problems_internal(
     if success_count > 0 {
         Ok(())
     } else {
-        Err("All precomputation attempts failed".to_string())
+        Err(ZosError::new("All precomputation attempts failed", "problem_selection"))
     }
 }
 
@@ -595,19 +875,48 @@ async fn precompute_next_problems_internal(
 #[tauri::command]
 pub async fn precompute_next_problem(
     state: State<'_, std::sync::Arc<AppState>>,
-) -> Result<(), String> {
+) -> Result<(), ZosError> {
     let app_state = state.inner();
     // Get current problem difficulty if available, otherwise use default
-    let skills = store::get_skills(app_state).await
-        .map_err(|e| format!("Failed to get skills: {}", e))?;
-    let base_difficulty = skills.get_weakest_skill()
-        .and_then(|(skill, _)| skills.skills.get(&skill).copied())
-        .map(|skill_val| (0.3_f32).max(1.0 - skill_val))
+    let target = recommendation_target(app_state).await?;
+    let base_difficulty = target.weakest
+        .map(|(_, value)| selector::target_difficulty(value))
         .unwrap_or(0.5);
 
     precompute_next_problems_internal(app_state, base_difficulty).await
 }
 
+/// Problems currently queued in the prefetch cache
+#[tauri::command]
+pub async fn get_problem_cache() -> Result<Vec<Problem>, String> {
+    Ok(crate::problems::cache::ProblemCache::load_async().await.queue)
+}
+
+/// Empty the prefetch cache, e.g. after a change of skill focus
+#[tauri::command]
+pub async fn clear_problem_cache(
+    state: State<'_, std::sync::Arc<AppState>>,
+) -> Result<(), String> {
+    let _cache_guard = state.problem_cache_lock.lock().await;
+    crate::problems::cache::ProblemCache::default().save_async().await
+        .map_err(|e| format!("Failed to clear problem cache: {}", e))
+}
+
+/// Drop one problem from the prefetch cache. Errors if it is not cached.
+#[tauri::command]
+pub async fn remove_cached_problem(
+    state: State<'_, std::sync::Arc<AppState>>,
+    problem_id: String,
+) -> Result<(), String> {
+    let _cache_guard = state.problem_cache_lock.lock().await;
+    let mut cache = crate::problems::cache::ProblemCache::load_async().await;
+    if !cache.remove(&problem_id) {
+        return Err(format!("Problem '{}' is not in the cache", problem_id));
+    }
+    cache.save_async().await
+        .map_err(|e| format!("Failed to save problem cache: {}", e))
+}
+
 #[tauri::command]
 pub fn get_problems_by_topic(topic: String) -> Result<Vec<Problem>, String> {
     let all_problems = Problem::load_all()
@@ -617,8 +926,9 @@ pub fn get_problems_by_topic(topic: String) -> Result<Vec<Problem>, String> {
     let filtered = selector::get_problems_by_topic(&all_problems, &topic);
     
     // Validate all returned problems have the correct topic
+    let expected_topic = selector::normalize_topic(&topic);
     for problem in &filtered {
-        if problem.topic != topic {
+        if selector::normalize_topic(&problem.topic) != expected_topic {
             tracing::error!(
                 problem_id = %problem.id,
                 actual_topic = %problem.topic,
@@ -631,44 +941,225 @@ pub fn get_problems_by_topic(topic: String) -> Result<Vec<Problem>, String> {
     Ok(filtered)
 }
 
+/// Problems in a difficulty band (inclusive), optionally for a single topic,
+/// easiest first
 #[tauri::command]
-pub fn get_problem_by_id(problem_id: String) -> Result<Problem, String> {
-    tracing::info!(problem_id = %problem_id, "Loading problem by ID (no LLM call)");
+pub fn get_problems_by_difficulty(
+    topic: Option<String>,
+    min: f32,
+    max: f32,
+) -> Result<Vec<Problem>, String> {
     let all_problems = Problem::load_all()
         .map_err(|e| format!("Failed to load problems: {}", e))?;
     
+    Ok(selector::get_problems_by_difficulty(&all_problems, topic.as_deref(), min, max))
+}
+
+/// Problems tagged with `tag` (case-insensitive)
+#[tauri::command]
+pub fn get_problems_by_tag(tag: String) -> Result<Vec<Problem>, String> {
+    let all_problems = Problem::load_all()
+        .map_err(|e| format!("Failed to load problems: {}", e))?;
+    
+    Ok(selector::get_problems_by_tag(&all_problems, &tag))
+}
+
+/// Import a JSON array of problems into the problems directory, skipping
+/// duplicate statements and rejecting invalid entries
+#[tauri::command]
+pub fn import_problems(path: String) -> Result<crate::problems::transfer::ImportSummary, ZosError> {
+    crate::problems::transfer::import_problems(std::path::Path::new(&path))
+}
+
+/// Export all problems (including autogenerated ones) to a single JSON file.
+/// Returns the number of problems written.
+#[tauri::command]
+pub fn export_problems(path: String) -> Result<usize, ZosError> {
+    crate::problems::transfer::export_problems(std::path::Path::new(&path))
+}
+
+/// Reassign problems with unknown topics to the closest known skill.
+/// Pass `dry_run: true` to preview the changes without rewriting any files.
+#[tauri::command]
+pub fn repair_problem_topics(
+    dry_run: Option<bool>,
+) -> Result<Vec<crate::problems::problem::TopicRepair>, ZosError> {
+    Problem::repair_orphan_topics(dry_run.unwrap_or(false))
+}
+
+#[tauri::command]
+pub fn get_problem_by_id(problem_id: String) -> Result<Problem, ZosError> {
+    tracing::info!(problem_id = %problem_id, "Loading problem by ID (no LLM call)");
+    let all_problems = load_problems()?;
+    
     let problem = all_problems
         .into_iter()
         .find(|p| p.id == problem_id)
         .ok_or_else(|| {
             tracing::warn!(problem_id = %problem_id, "Problem not found by ID");
-            format!("Problem with ID '{}' not found", problem_id)
+            ZosError::new(format!("Problem with ID '{}' not found", problem_id), "validation")
         })?;
     
     tracing::info!(problem_id = %problem.id, topic = %problem.topic, "Successfully loaded problem by ID");
     Ok(problem)
 }
 
+/// Rate a problem -1 (bad or too easy), 0 or +1. Down-rated statements are
+/// quoted as patterns to avoid when generating new problems for the skill.
+#[tauri::command]
+pub async fn rate_problem(problem_id: String, quality: i8) -> Result<(), ZosError> {
+    use crate::problems::feedback::{append_rating, ProblemRating};
+    let problem = get_problem_by_id(problem_id)?;
+    let rating = ProblemRating::new(problem.id, problem.topic, quality, &problem.statement, Utc::now().timestamp())?;
+    append_rating(&rating).await
+}
+
+/// Give up on a problem: return its solution sketch and record a "revealed"
+/// session marker. The marker counts as a failed attempt for the success rate
+/// and review schedule, and excludes the problem from recommendations like any
+/// other session.
+#[tauri::command]
+pub async fn reveal_solution(
+    state: State<'_, std::sync::Arc<AppState>>,
+    problem_id: String,
+) -> Result<String, ZosError> {
+    let problem = get_problem_by_id(problem_id)?;
+    let skill_value = store::get_skills(state.inner()).await?
+        .skills.get(&problem.topic)
+        .copied()
+        .unwrap_or(0.5);
+
+    let record = SessionRecord::revealed(problem.id.clone(), problem.topic.clone(), skill_value, problem.difficulty);
+    save_session(&record).await?;
+
+    tracing::info!(problem_id = %problem.id, skill = %problem.topic, session_id = %record.session_id, "Solution revealed");
+    Ok(problem.solution_sketch)
+}
+
 #[tauri::command]
 pub async fn get_skills(
     state: State<'_, std::sync::Arc<AppState>>,
-) -> Result<SkillVector, String> {
+) -> Result<SkillVector, ZosError> {
     store::get_skills(state.inner()).await
-        .map_err(|e| format!("Failed to get skills: {}", e))
+}
+
+/// Every skill with its label, last practice time, session count and
+/// weekly trend, for the skills table
+#[tauri::command]
+pub async fn get_skills_detailed(
+    state: State<'_, std::sync::Arc<AppState>>,
+) -> Result<Vec<analytics::SkillInfo>, ZosError> {
+    let skills = store::get_skills(state.inner()).await?;
+    analytics::compute_skill_details(&skills).await
+}
+
+/// One representative problem per skill for the onboarding calibration.
+/// Empty once calibration has been completed.
+#[tauri::command]
+pub async fn start_calibration(
+    state: State<'_, std::sync::Arc<AppState>>,
+) -> Result<Vec<Problem>, ZosError> {
+    use crate::skills::calibration;
+    
+    if calibration::load_calibration_in(&calibration::calibration_path()).await.is_some() {
+        return Ok(Vec::new());
+    }
+    let skills = store::get_skills(state.inner()).await?;
+    let problems = load_problems()?;
+    Ok(calibration::calibration_problems(&skills, &problems))
+}
+
+/// Seed skill values from calibration outcomes (skill, solved) and mark
+/// calibration as done. Fails if it was already completed.
+#[tauri::command]
+pub async fn submit_calibration(
+    state: State<'_, std::sync::Arc<AppState>>,
+    results: Vec<(String, bool)>,
+) -> Result<SkillVector, ZosError> {
+    use crate::skills::calibration;
+    
+    let app_state = state.inner();
+    let skills = store::get_skills(app_state).await?;
+    let seeded = calibration::complete_calibration_in(
+        &calibration::calibration_path(),
+        &skills,
+        &results,
+        |seeded| store::update_skills(app_state, move |skills| *skills = seeded),
+    ).await?;
+    tracing::info!(results = results.len(), "Skills seeded from calibration");
+    Ok(seeded)
+}
+
+/// Restrict recommendations and the daily plan to `skills` (focus mode),
+/// still picking the weakest among them. An empty list turns it off. The
+/// daily plan is regenerated so it follows the new focus right away.
+#[tauri::command]
+pub async fn set_focus_skills(
+    state: State<'_, std::sync::Arc<AppState>>,
+    skills: Vec<String>,
+) -> Result<Vec<String>, ZosError> {
+    let focus = store::set_focus_skills(state.inner(), &skills).await?;
+    let plan = crate::brain::generate_daily_plan().await;
+    crate::brain::store::save_generated(&plan).await?;
+    Ok(focus)
+}
+
+/// Average skill value per domain (see `skill_domains.toml`)
+#[tauri::command]
+pub async fn get_domain_scores(
+    state: State<'_, std::sync::Arc<AppState>>,
+) -> Result<std::collections::HashMap<String, f32>, ZosError> {
+    let skills = store::get_skills(state.inner()).await?;
+    Ok(analytics::domain_scores(&skills, &crate::config::skills::get_skill_domains()))
+}
+
+/// Manually set a skill value (clamped to [0, 1]); the change is audit-logged
+#[tauri::command]
+pub async fn set_skill_value(
+    state: State<'_, std::sync::Arc<AppState>>,
+    skill: String,
+    value: f32,
+) -> Result<SkillVector, ZosError> {
+    let app_state = state.inner();
+    store::set_skill_value(app_state, &skill, value).await?;
+    store::get_skills(app_state).await
+}
+
+/// Revert the most recent skill update (up to the last 10 can be undone)
+#[tauri::command]
+pub async fn undo_last_skill_update(
+    state: State<'_, std::sync::Arc<AppState>>,
+) -> Result<SkillVector, ZosError> {
+    store::undo_last_skill_update(state.inner()).await
+}
+
+/// Reset skills to defaults and replay every stored session with the current
+/// weights, e.g. after changing `skill_weights.toml` or a corrupt `skills.json`
+#[tauri::command]
+pub async fn recompute_skills_from_sessions(
+    state: State<'_, std::sync::Arc<AppState>>,
+) -> Result<SkillVector, ZosError> {
+    store::recompute_skills_from_sessions(state.inner()).await
+}
+
+/// One-time fix for sessions saved before difficulty was recorded: fill it in
+/// from the session's problem. Returns how many sessions were updated.
+#[tauri::command]
+pub async fn migrate_sessions() -> Result<usize, ZosError> {
+    let problems = load_problems()?;
+    crate::sessions::migrate_sessions(&problems).await
 }
 
 #[tauri::command]
 pub async fn update_skills_from_issues(
     state: State<'_, std::sync::Arc<AppState>>,
     issues: Vec<ProofIssue>,
-) -> Result<SkillVector, String> {
+) -> Result<SkillVector, ZosError> {
     let app_state = state.inner();
     store::update_skills(app_state, |skills| {
         skills.update_from_issues(&issues);
-    }).await
-        .map_err(|e| format!("Failed to update skills: {}", e))?;
-    let skills = store::get_skills(app_state).await
-        .map_err(|e| format!("Failed to get skills: {}", e))?;
+    }).await?;
+    let skills = store::get_skills(app_state).await?;
     if let Err(e) = skills_store::save_skill_vector(&skills).await {
         eprintln!("Failed to save skills: {}", e);
     }
@@ -676,21 +1167,373 @@ pub async fn update_skills_from_issues(
 }
 
 #[tauri::command]
-pub async fn save_session_record(record: SessionRecord) -> Result<(), String> {
-    save_session(&record).await.map_err(|e| e.to_string())
+pub async fn save_session_record(record: SessionRecord) -> Result<(), ZosError> {
+    save_session(&record).await
 }
 
 #[tauri::command]
-pub async fn refresh_daily_plan() -> Result<(), String> {
+pub async fn refresh_daily_plan() -> Result<(), ZosError> {
     let plan = crate::brain::generate_daily_plan().await;
-    crate::brain::store::save(&plan).await.map_err(|e| e.to_string())
+    crate::brain::store::save_generated(&plan).await
+}
+
+/// Regenerate and save the daily plan with a custom drill count, optional
+/// reviews and trend horizon (in days). Mastered skills are left out of the
+/// drills unless `exclude_mastered` is false. Returns the new plan.
+#[tauri::command]
+pub async fn refresh_daily_plan_with(
+    num_drills: usize,
+    include_reviews: bool,
+    horizon_days: i64,
+    exclude_mastered: Option<bool>,
+) -> Result<crate::brain::CurriculumPlan, ZosError> {
+    let options = crate::brain::PlanOptions {
+        num_drills,
+        include_reviews,
+        horizon_days,
+        exclude_mastered: exclude_mastered.unwrap_or(true),
+    };
+    let skill_count = skills_store::load_skill_vector().await.skills.len();
+    options.validate(skill_count)?;
+
+    let plan = crate::brain::generate_daily_plan_with(&options).await;
+    crate::brain::store::save_generated(&plan).await?;
+    tracing::info!(num_drills, include_reviews, horizon_days, tasks = plan.tasks.len(), "Daily plan regenerated");
+    Ok(plan)
+}
+
+#[tauri::command]
+pub async fn get_daily_plan() -> Result<crate::brain::CurriculumPlan, ZosError> {
+    crate::brain::store::load().await?
+        .ok_or_else(|| ZosError::new("No plan", "state"))
+}
+
+/// Skills that are mastered: value above 0.9 and more than 80% of the last
+/// 5 sessions correct. Mastered skills are not drilled or recommended.
+#[tauri::command]
+pub async fn get_mastered_skills(
+    state: State<'_, std::sync::Arc<AppState>>,
+) -> Result<Vec<String>, ZosError> {
+    let skills = store::get_skills(state.inner()).await?;
+    let sessions = load_all_sessions().await?;
+    Ok(crate::skills::mastery::mastered_skills(&skills.skills, &sessions))
+}
+
+/// The most recent `limit` generated plans, newest first
+#[tauri::command]
+pub async fn get_plan_history(limit: usize) -> Result<Vec<crate::brain::CurriculumPlan>, ZosError> {
+    crate::brain::store::load_plan_history(limit).await
+}
+
+/// Completed and total task counts for the active plan
+#[tauri::command]
+pub async fn get_plan_progress() -> Result<crate::brain::PlanProgress, ZosError> {
+    crate::brain::store::load().await?
+        .map(|plan| plan.progress())
+        .ok_or_else(|| ZosError::new("No plan", "state"))
+}
+
+#[tauri::command]
+pub async fn get_analytics_data() -> Result<AnalyticsPayload, ZosError> {
+    analytics::compute_analytics().await
+}
+
+#[tauri::command]
+pub async fn get_session_history() -> Result<Vec<SessionRecord>, ZosError> {
+    load_all_sessions().await
+}
+
+/// A single session with its full attempt, issues and evaluation
+#[tauri::command]
+pub async fn get_session_by_id(session_id: String) -> Result<SessionRecord, ZosError> {
+    crate::sessions::load_session(&session_id).await
+}
+
+/// Delete a session (e.g. an accidental junk attempt). Returns whether a file was removed.
+/// Analytics are computed from the session files, so they drop it immediately;
+/// the review schedule replays the skill's remaining sessions without it.
+#[tauri::command]
+pub async fn delete_session(session_id: String) -> Result<bool, ZosError> {
+    let record = crate::sessions::load_session(&session_id).await.ok();
+    let deleted = crate::sessions::delete_session(&session_id).await?;
+    if deleted {
+        tracing::info!(session_id = %session_id, "Deleted session");
+    }
+    if let (true, Some(record)) = (deleted, record) {
+        let remaining = load_all_sessions().await?;
+        let mut schedule = crate::brain::store::load_schedule().await?;
+        if schedule.forget_session(&record, &remaining) {
+            crate::brain::store::save_schedule(&schedule).await?;
+        }
+    }
+    Ok(deleted)
+}
+
+#[tauri::command]
+pub async fn get_recent_failures(limit: Option<usize>) -> Result<Vec<SessionRecord>, ZosError> {
+    analytics::recent_failures(limit.unwrap_or(10)).await
+}
+
+#[tauri::command]
+pub async fn get_skill_drift() -> Result<std::collections::HashMap<String, f32>, ZosError> {
+    analytics::compute_skill_drift().await
+}
+
+#[tauri::command]
+pub async fn get_session_summary() -> Result<SessionSummary, ZosError> {
+    analytics::compute_session_summary().await
+}
+
+/// Markdown summary of the last 7 days. With `path`, the report is also
+/// written to that file, which must be inside the app data dir (relative
+/// paths are taken from it).
+#[tauri::command]
+pub async fn generate_weekly_report(path: Option<String>) -> Result<String, ZosError> {
+    let target = match &path {
+        Some(requested) => Some(crate::paths::path_within(&crate::paths::app_data_dir(), requested)
+            .ok_or_else(|| ZosError::new(
+                format!("Reports can only be written inside the app data folder, not {}", requested),
+                "validation"
+            ))?),
+        None => None,
+    };
+    let report = analytics::generate_weekly_report().await?;
+    if let Some(target) = target {
+        if let Some(parent) = target.parent() {
+            tokio::fs::create_dir_all(parent).await
+                .map_err(|e| ZosError::new(format!("Failed to create {:?}: {}", parent, e), "io"))?;
+        }
+        tokio::fs::write(&target, &report).await
+            .map_err(|e| ZosError::new(format!("Failed to write weekly report to {:?}: {}", target, e), "io"))?;
+    }
+    Ok(report)
+}
+
+/// Free-form question for the general model (e.g. "explain this concept").
+/// Returns the model's raw text rather than JSON: answers are prose shown
+/// directly in the UI, so forcing a schema would only add parse failures.
+#[tauri::command]
+pub async fn general_query(
+    state: State<'_, std::sync::Arc<AppState>>,
+    prompt: String,
+) -> Result<String, ZosError> {
+    if prompt.trim().is_empty() {
+        return Err(ZosError::new("Prompt is empty", "validation"));
+    }
+    crate::pipelines::router::zos_query_text(state.inner(), crate::pipelines::router::TaskType::General, prompt)
+        .await
+}
+
+/// A graduated hint for a stuck attempt (level 1 = gentle, 3 = nearly the answer).
+/// Uses the problem's solution sketch when `problem_id` is given, without revealing it.
+/// Hints never change skills or the proof state.
+#[tauri::command]
+pub async fn get_hint(
+    state: State<'_, std::sync::Arc<AppState>>,
+    proof: String,
+    issues: Vec<ProofIssue>,
+    level: u8,
+    problem_id: Option<String>,
+) -> Result<String, ZosError> {
+    use crate::pipelines::proof::{build_hint_prompt, guard_input_size, HINT_LEVELS};
+    
+    if !HINT_LEVELS.contains(&level) {
+        return Err(ZosError::new(
+            format!("Hint level must be between {} and {}, got {}", HINT_LEVELS.start(), HINT_LEVELS.end(), level),
+            "validation"
+        ));
+    }
+    let limit = crate::config::models::get_model_config().max_input_chars;
+    let proof = guard_input_size(&proof, limit)?;
+    let problem = problem_id.and_then(|pid| get_problem_by_id(pid).ok());
+    let prompt = build_hint_prompt(
+        problem.as_ref().map(|p| p.statement.as_str()),
+        problem.as_ref().map(|p| p.solution_sketch.as_str()),
+        &proof,
+        &issues,
+        level,
+    );
+    crate::pipelines::router::zos_query_text(state.inner(), crate::pipelines::router::TaskType::General, prompt)
+        .await
+}
+
+/// Which step the backend thinks a problem's proof flow is at, so the UI can resync
+#[tauri::command]
+pub async fn get_proof_state(
+    state: State<'_, std::sync::Arc<AppState>>,
+    problem_id: Option<String>,
+) -> Result<ProofStateSummary, String> {
+    let session_id = session_id_for(problem_id.as_deref());
+    Ok(ProofStateSummary::from(&get_state(state.inner(), session_id)))
+}
+
+/// Line diff of a revised proof against the one Step 2 asked to revise.
+/// Only available while the problem is awaiting revision.
+#[tauri::command]
+pub async fn get_proof_diff(
+    state: State<'_, std::sync::Arc<AppState>>,
+    revised: String,
+    problem_id: Option<String>,
+) -> Result<Vec<crate::state::session::ProofDiffLine>, String> {
+    let session_id = session_id_for(problem_id.as_deref());
+    let current = get_state(state.inner(), session_id);
+    let original = current.original_proof()
+        .ok_or_else(|| "No proof is awaiting revision".to_string())?;
+    Ok(crate::state::session::proof_diff(original, &revised))
+}
+
+/// Put a problem's proof flow back to AwaitingSolution, e.g. when the UI and
+/// backend disagree and the user is stuck
+#[tauri::command]
+pub async fn reset_proof_state(
+    state: State<'_, std::sync::Arc<AppState>>,
+    problem_id: Option<String>,
+) -> Result<ProofStateSummary, String> {
+    let app_state = state.inner();
+    let session_id = session_id_for(problem_id.as_deref());
+    reset_state(app_state, session_id);
+    tracing::info!(session_id = %session_id, "Proof state reset by user");
+    Ok(ProofStateSummary::from(&get_state(app_state, session_id)))
+}
+
+/// Abort an in-flight request started with this `request_id`.
+/// Returns whether a running request was found.
+#[tauri::command]
+pub async fn cancel_request(
+    state: State<'_, std::sync::Arc<AppState>>,
+    request_id: String,
+) -> Result<bool, String> {
+    let cancelled = state.inner().cancel_request(&request_id);
+    tracing::info!(request_id = %request_id, cancelled = cancelled, "Cancel requested");
+    Ok(cancelled)
+}
+
+/// Turn offline mode on or off. While on, no command calls the model.
+#[tauri::command]
+pub async fn set_offline_mode(
+    state: State<'_, std::sync::Arc<AppState>>,
+    offline: bool,
+) -> Result<(), String> {
+    state.inner().set_offline_mode(offline);
+    tracing::info!(offline = offline, "Offline mode changed");
+    Ok(())
+}
+
+/// Change log verbosity at runtime (error, warn, info, debug or trace)
+#[tauri::command]
+pub fn set_log_level(level: String) -> Result<(), ZosError> {
+    crate::logging::set_log_level(&level)
+}
+
+/// Folder holding the rotating log files, for "open log folder" in the UI
+#[tauri::command]
+pub fn get_log_path() -> Result<String, String> {
+    Ok(crate::logging::log_dir().to_string_lossy().into_owned())
+}
+
+/// Counters in Prometheus text exposition format
+#[tauri::command]
+pub async fn get_metrics_text(
+    state: State<'_, std::sync::Arc<AppState>>,
+) -> Result<String, String> {
+    Ok(state.inner().metrics.metrics_text())
+}
+
+/// Check every registered model against Ollama (registry name -> installed)
+#[tauri::command]
+pub async fn get_model_health() -> Result<std::collections::HashMap<String, bool>, String> {
+    let models: Vec<_> = crate::models::registry::MODEL_REGISTRY.read()
+        .iter()
+        .map(|(name, model)| (name.clone(), model.clone()))
+        .collect();
+    
+    let mut checks = tokio::task::JoinSet::new();
+    for (name, model) in models {
+        checks.spawn(async move { (name, model.healthcheck().await) });
+    }
+    
+    let mut health = std::collections::HashMap::new();
+    while let Some(result) = checks.join_next().await {
+        let (name, healthy) = result
+            .map_err(|e| format!("Model healthcheck task failed: {}", e))?;
+        health.insert(name, healthy);
+    }
+    Ok(health)
+}
+
+/// Installed Ollama models; `error` is set (and `models` empty) when Ollama is unreachable
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct OllamaModelList {
+    pub models: Vec<crate::models::availability::OllamaModelInfo>,
+    pub error: Option<String>,
+}
+
+/// List the models actually installed in Ollama (via /api/tags)
+#[tauri::command]
+pub async fn list_ollama_models() -> Result<OllamaModelList, String> {
+    match crate::models::availability::list_installed_models().await {
+        Ok(models) => Ok(OllamaModelList { models, error: None }),
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to list Ollama models");
+            Ok(OllamaModelList {
+                models: Vec::new(),
+                error: Some(format!("Ollama is not reachable: {:#}", e)),
+            })
+        }
+    }
 }
 
+/// p50/p90/p99 model call latency per model since the app started
 #[tauri::command]
-pub async fn get_daily_plan() -> Result<crate::brain::CurriculumPlan, String> {
-    crate::brain::store::load().await
-        .map_err(|e| format!("Failed to load plan: {}", e))?
-        .ok_or("No plan".into())
+pub async fn get_model_latency_percentiles(
+    state: State<'_, std::sync::Arc<AppState>>,
+) -> Result<Vec<crate::metrics::LatencyPercentiles>, String> {
+    Ok(state.inner().model_latency_percentiles())
+}
+
+/// Response size and tokens/second of the last successful model call
+#[tauri::command]
+pub fn get_last_call_throughput(
+    state: State<'_, std::sync::Arc<AppState>>,
+) -> Result<Option<crate::pipelines::ollama::ModelThroughput>, String> {
+    Ok(state.inner().last_call_throughput())
+}
+
+/// Time a few sequential pings to Ollama to see whether slowness comes from
+/// connection setup or from inference
+#[tauri::command]
+pub async fn http_diagnostics() -> Result<crate::pipelines::ollama::HttpDiagnostics, String> {
+    use crate::pipelines::ollama::{self, DIAGNOSTIC_PINGS};
+    Ok(ollama::http_diagnostics(DIAGNOSTIC_PINGS).await)
+}
+
+/// Readiness checklist for a study session: Ollama, each configured model,
+/// the problem bank, skills and the response cache
+#[tauri::command]
+pub async fn get_app_status(
+    state: State<'_, std::sync::Arc<AppState>>,
+) -> Result<crate::status::AppStatus, String> {
+    Ok(crate::status::app_status(state.inner()).await)
+}
+
+#[tauri::command]
+pub async fn get_model_config() -> Result<crate::config::models::ModelConfig, String> {
+    Ok(crate::config::models::get_model_config())
+}
+
+/// Save a new model config and apply it immediately.
+/// Only new queries pick up the change: a query that has already routed
+/// finishes (including its retries and fallback) on the models it started with.
+#[tauri::command]
+pub async fn set_model_config(
+    state: State<'_, std::sync::Arc<AppState>>,
+    config: crate::config::models::ModelConfig,
+) -> Result<(), ZosError> {
+    crate::config::models::set_model_config(config).await?;
+    crate::models::registry::refresh_registry();
+    // A new seed starts selection over from the top
+    state.reseed_selection_rng();
+    Ok(())
 }
 
 /// Submit/abandon a problem attempt (for tracking when user moves on without completing)
@@ -702,41 +1545,38 @@ pub async fn submit_problem_attempt(
     problem_difficulty: Option<f32>,
     user_attempt: String,
     status: String, // "abandoned", "incomplete", "perfect", etc.
-) -> Result<(), String> {
+) -> Result<(), ZosError> {
     let app_state = state.inner();
     
     // Only save if we have problem info
     if let (Some(pid), Some(topic)) = (problem_id, problem_topic) {
-        let skills = store::get_skills(app_state).await
-            .map_err(|e| format!("Failed to get skills: {}", e))?;
+        let skills = store::get_skills(app_state).await?;
         let skill_before = skills.skills.get(&topic).copied().unwrap_or(0.5);
-        let skill_after = skill_before; // No change if abandoned/incomplete
         
-        let record = SessionRecord {
-            session_id: format!("sess_{}", Utc::now().timestamp_millis()),
+        // No skill change if abandoned/incomplete, so skill_after == skill_before
+        record_session(app_state, SessionDraft {
             problem_id: pid,
-            skill: topic,
+            topic,
             user_attempt,
-            issues: vec![],
+            issues: &[],
             eval_summary: format!("Attempt {} - user moved on", status),
             skill_before,
-            skill_after,
-            difficulty: problem_difficulty.unwrap_or(0.5),
-            timestamp: Utc::now().timestamp(),
-        };
-        
-        if let Err(e) = save_session(&record).await {
-            tracing::warn!(error = %e, "Failed to save problem attempt record");
-        }
+            difficulty: problem_difficulty,
+            correct: None,
+            assessments: Vec::new(),
+        }).await?;
     }
     
     Ok(())
 }
 
+/// Start over: skills go back to their configured starting values, and
+/// sessions, plans, the review schedule, calibration, focus skills, the
+/// skill override log and the problem cache are deleted
 #[tauri::command]
 pub async fn reset_all_progress(
     state: State<'_, std::sync::Arc<AppState>>,
-) -> Result<(), String> {
+) -> Result<(), ZosError> {
     use std::fs;
     use crate::skills::store as skills_store;
     use crate::sessions;
@@ -744,16 +1584,16 @@ pub async fn reset_all_progress(
     
     let app_state = state.inner();
     
-    // Reset skills to defaults
-    let default_skills = crate::skills::model::SkillVector::new();
-    skills_store::save_skill_vector(&default_skills).await
-        .map_err(|e| format!("Failed to reset skills: {}", e))?;
+    // Reset skills to the configured starting values
+    let default_skills = crate::skills::model::SkillVector::with_defaults(
+        &crate::config::skills::reload_skill_defaults()
+    );
+    skills_store::save_skill_vector(&default_skills).await?;
     
     // Clear in-memory skills store
     crate::memory::store::update_skills(app_state, |skills| {
         *skills = default_skills.clone();
-    }).await
-        .map_err(|e| format!("Failed to update skills: {}", e))?;
+    }).await?;
     
     // Delete all session files
     let sessions_dir = sessions::sessions_dir();
@@ -771,7 +1611,44 @@ pub async fn reset_all_progress(
         let _ = fs::remove_file(&plan_path);
     }
     
+    // Delete stored target difficulties
+    let targets_path = skills_store::target_difficulty_path();
+    if targets_path.exists() {
+        let _ = fs::remove_file(&targets_path);
+    }
+    
+    // Offer calibration again
+    let calibration_path = crate::skills::calibration::calibration_path();
+    if calibration_path.exists() {
+        let _ = fs::remove_file(&calibration_path);
+    }
+    
+    // Turn off focus mode
+    let focus_path = skills_store::focus_skills_path();
+    if focus_path.exists() {
+        let _ = fs::remove_file(&focus_path);
+    }
+    
+    // Delete the manual skill override log
+    let audit_path = skills_store::skill_audit_path();
+    if audit_path.exists() {
+        let _ = fs::remove_file(&audit_path);
+    }
+    
+    // Delete plan history
+    let history_path = crate::brain::store::get_plan_history_path();
+    if history_path.exists() {
+        let _ = fs::remove_file(&history_path);
+    }
+    
+    // Delete review schedule
+    let schedule_path = crate::brain::store::get_schedule_path();
+    if schedule_path.exists() {
+        let _ = fs::remove_file(&schedule_path);
+    }
+    
     // Clear problem cache
+    let _cache_guard = app_state.problem_cache_lock.lock().await;
     let cache = ProblemCache::default();
     let _ = cache.save_async().await;
     
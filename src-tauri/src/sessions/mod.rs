@@ -1,6 +1,9 @@
 use serde::{Serialize, Deserialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::collections::HashMap;
 use crate::error::ZosError;
+use crate::problems::problem::Problem;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SessionRecord {
@@ -15,62 +18,98 @@ pub struct SessionRecord {
     #[serde(default = "default_difficulty")]
     pub difficulty: f32,
     pub timestamp: i64,
+    /// Graded outcome set when the session is saved. `None` for ungraded
+    /// attempts and for records saved before this field existed.
+    #[serde(default)]
+    pub correct: Option<bool>,
+    /// Per-question Step 2 assessments ("correct", "partially_correct",
+    /// "incorrect", "unclear"), in question order. Empty if Step 2 didn't run.
+    #[serde(default)]
+    pub assessments: Vec<String>,
+    /// Step 1 issue types, in issue order, so skill penalties can be replayed.
+    /// Empty for records saved before this field existed.
+    #[serde(default)]
+    pub issue_types: Vec<String>,
+    /// Set when the user gave up and revealed the solution instead of submitting
+    #[serde(default)]
+    pub revealed: bool,
+}
+
+/// Eval summary stored on "gave up" markers
+pub const REVEALED_SUMMARY: &str = "revealed";
+
+impl SessionRecord {
+    /// A "gave up" marker for a problem whose solution was revealed. It is
+    /// graded as incorrect so it counts against the success rate and resets
+    /// the skill's review interval; the skill value itself is left unchanged.
+    pub fn revealed(problem_id: String, skill: String, skill_value: f32, difficulty: f32) -> Self {
+        SessionRecord {
+            session_id: new_session_id(),
+            problem_id,
+            skill,
+            user_attempt: String::new(),
+            issues: Vec::new(),
+            eval_summary: REVEALED_SUMMARY.to_string(),
+            skill_before: skill_value,
+            skill_after: skill_value,
+            difficulty,
+            timestamp: chrono::Utc::now().timestamp(),
+            correct: Some(false),
+            assessments: Vec::new(),
+            issue_types: Vec::new(),
+            revealed: true,
+        }
+    }
 }
 
 fn default_difficulty() -> f32 {
     0.5
 }
 
+/// Per-process sequence number so ids minted in the same millisecond differ
+static SESSION_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// A new unique session id: `sess_<millis>_<seq>_<random>`. The sequence number
+/// keeps ids unique within this process and the random suffix across restarts.
+pub fn new_session_id() -> String {
+    let seq = SESSION_SEQ.fetch_add(1, Ordering::Relaxed);
+    let suffix: u16 = rand::random();
+    format!("sess_{}_{}_{:04x}", chrono::Utc::now().timestamp_millis(), seq, suffix)
+}
+
 pub fn sessions_dir() -> PathBuf {
-    // Use platform-specific app data directory
-    #[cfg(target_os = "macos")]
-    {
-        if let Some(home) = std::env::var_os("HOME") {
-            let mut dir = PathBuf::from(home);
-            dir.push("Library/Application Support/com.zacnwo.zos");
-            dir.push("data");
-            dir.push("sessions");
-            return dir;
-        }
-    }
-    
-    #[cfg(target_os = "windows")]
-    {
-        if let Some(appdata) = std::env::var_os("APPDATA") {
-            let mut dir = PathBuf::from(appdata);
-            dir.push("com.zacnwo.zos");
-            dir.push("data");
-            dir.push("sessions");
-            return dir;
-        }
-    }
-    
-    #[cfg(target_os = "linux")]
-    {
-        if let Some(home) = std::env::var_os("HOME") {
-            let mut dir = PathBuf::from(home);
-            dir.push(".local/share/com.zacnwo.zos");
-            dir.push("data");
-            dir.push("sessions");
-            return dir;
-        }
+    crate::paths::data_dir().join("sessions")
+}
+
+/// Path of the file holding `session_id` in `dir`. Ids are used as file names,
+/// so anything that could escape the directory is rejected.
+fn session_path(dir: &Path, session_id: &str) -> Result<PathBuf, ZosError> {
+    let valid = !session_id.is_empty()
+        && session_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(ZosError::new(
+            format!("Invalid session id '{}'", session_id),
+            "session"
+        ));
     }
-    
-    // Fallback
-    PathBuf::from("data/sessions")
+    Ok(dir.join(format!("{}.json", session_id)))
 }
 
 /// Save a session record asynchronously
 pub async fn save_session(record: &SessionRecord) -> Result<(), ZosError> {
-    let dir = sessions_dir();
-    tokio::fs::create_dir_all(&dir)
+    save_session_in(&sessions_dir(), record).await
+}
+
+/// Save a session record into `dir`
+pub async fn save_session_in(dir: &Path, record: &SessionRecord) -> Result<(), ZosError> {
+    tokio::fs::create_dir_all(dir)
         .await
         .map_err(|e| ZosError::new(
             format!("Failed to create sessions directory: {}", e),
             "io"
         ).with_context(format!("path: {:?}", dir)))?;
     
-    let fname = dir.join(format!("{}.json", record.session_id));
+    let fname = session_path(dir, &record.session_id)?;
     let json = serde_json::to_string_pretty(record)
         .map_err(|e| ZosError::new(
             format!("Failed to serialize session record: {}", e),
@@ -87,12 +126,65 @@ pub async fn save_session(record: &SessionRecord) -> Result<(), ZosError> {
     Ok(())
 }
 
+/// Load a single session by id, reading only its own file
+pub async fn load_session(session_id: &str) -> Result<SessionRecord, ZosError> {
+    load_session_in(&sessions_dir(), session_id).await
+}
+
+/// Load a single session by id from `dir`
+pub async fn load_session_in(dir: &Path, session_id: &str) -> Result<SessionRecord, ZosError> {
+    let path = session_path(dir, session_id)?;
+    let text = match tokio::fs::read_to_string(&path).await {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(ZosError::new(
+                format!("Session '{}' not found", session_id),
+                "session"
+            ).with_context(format!("path: {:?}", path)));
+        }
+        Err(e) => {
+            return Err(ZosError::new(
+                format!("Failed to read session file: {}", e),
+                "io"
+            ).with_context(format!("path: {:?}", path)));
+        }
+    };
+    
+    serde_json::from_str::<SessionRecord>(&text)
+        .map_err(|e| ZosError::new(
+            format!("Failed to parse session file: {}", e),
+            "json_parse"
+        ).with_context(format!("path: {:?}", path)))
+}
+
+/// Delete a session's file. Returns whether a file was removed.
+pub async fn delete_session(session_id: &str) -> Result<bool, ZosError> {
+    delete_session_in(&sessions_dir(), session_id).await
+}
+
+/// Delete a session's file from `dir`. Returns whether a file was removed.
+pub async fn delete_session_in(dir: &Path, session_id: &str) -> Result<bool, ZosError> {
+    let path = session_path(dir, session_id)?;
+    match tokio::fs::remove_file(&path).await {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(ZosError::new(
+            format!("Failed to delete session file: {}", e),
+            "io"
+        ).with_context(format!("path: {:?}", path))),
+    }
+}
+
 /// Load all session records asynchronously
 pub async fn load_all_sessions() -> Result<Vec<SessionRecord>, ZosError> {
+    load_all_sessions_in(&sessions_dir()).await
+}
+
+/// Load all session records in `dir`, oldest first
+pub async fn load_all_sessions_in(dir: &Path) -> Result<Vec<SessionRecord>, ZosError> {
     let mut records = Vec::new();
-    let dir = sessions_dir();
 
-    let mut entries = match tokio::fs::read_dir(&dir).await {
+    let mut entries = match tokio::fs::read_dir(dir).await {
         Ok(entries) => entries,
         Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
             // Directory doesn't exist yet, return empty vec
@@ -139,6 +231,37 @@ pub async fn load_all_sessions() -> Result<Vec<SessionRecord>, ZosError> {
     Ok(records)
 }
 
+/// Backfill the difficulty of records saved before it was stored, which load
+/// with the 0.5 default, from their problem. Returns how many were updated.
+pub async fn migrate_sessions(problems: &[Problem]) -> Result<usize, ZosError> {
+    migrate_sessions_in(&sessions_dir(), problems).await
+}
+
+/// Backfill default difficulties for the records in `dir`. Records whose
+/// problem is unknown keep the default.
+pub async fn migrate_sessions_in(dir: &Path, problems: &[Problem]) -> Result<usize, ZosError> {
+    let difficulties: HashMap<&str, f32> = problems.iter()
+        .map(|p| (p.id.as_str(), p.difficulty))
+        .collect();
+    let mut migrated = 0;
+    
+    for mut record in load_all_sessions_in(dir).await? {
+        if record.difficulty != default_difficulty() {
+            continue;
+        }
+        let Some(&difficulty) = difficulties.get(record.problem_id.as_str()) else { continue };
+        if difficulty == record.difficulty {
+            continue;
+        }
+        record.difficulty = difficulty;
+        save_session_in(dir, &record).await?;
+        migrated += 1;
+    }
+    
+    tracing::info!(migrated = migrated, "Backfilled session difficulties");
+    Ok(migrated)
+}
+
 /// Synchronous version for backward compatibility (deprecated)
 #[deprecated(note = "Use load_all_sessions().await instead")]
 pub fn load_all_sessions_sync() -> Vec<SessionRecord> {
@@ -159,15 +282,39 @@ pub fn load_all_sessions_sync() -> Vec<SessionRecord> {
     records
 }
 
+/// Whether a session counts as a correct attempt. This is the one definition
+/// of success; `analytics::is_failed_session` is its negation.
+/// Uses the stored `correct` flag when present, then the Step 2 assessments
+/// (correct unless any is "incorrect"); otherwise (older or ungraded
+/// records) falls back to the legacy heuristic:
+/// - eval_summary doesn't contain "incorrect" or "fail"
+/// - skill_after >= skill_before (or close to it)
+pub fn is_session_correct(record: &SessionRecord) -> bool {
+    if let Some(correct) = record.correct {
+        return correct;
+    }
+    if !record.assessments.is_empty() {
+        return !record.assessments.iter().any(|a| a == "incorrect");
+    }
+    let eval_lower = record.eval_summary.to_lowercase();
+    !eval_lower.contains("incorrect") && 
+    !eval_lower.contains("fail") &&
+    record.skill_after >= record.skill_before - 0.01 // Allow tiny rounding errors
+}
+
 /// Compute recent success rate for a skill
 /// Returns the fraction of correct sessions in the last n attempts
 /// If fewer than 3 attempts exist, returns 0.5 (neutral)
 pub async fn recent_success_rate(skill: &str, n: usize) -> Result<f32, ZosError> {
     let all_sessions = load_all_sessions().await?;
-    
+    Ok(success_rate_in(&all_sessions, skill, n))
+}
+
+/// `recent_success_rate` over an already loaded list of sessions
+pub fn success_rate_in(sessions: &[SessionRecord], skill: &str, n: usize) -> f32 {
     // Filter by skill and get last n sessions
-    let mut relevant: Vec<_> = all_sessions
-        .into_iter()
+    let mut relevant: Vec<&SessionRecord> = sessions
+        .iter()
         .filter(|s| s.skill == skill)
         .collect();
     
@@ -179,23 +326,14 @@ pub async fn recent_success_rate(skill: &str, n: usize) -> Result<f32, ZosError>
     
     // Need at least 3 attempts for meaningful data
     if recent.len() < 3 {
-        return Ok(0.5);
+        return 0.5;
     }
     
-    // Count correct sessions
-    // A session is considered correct if:
-    // - eval_summary doesn't contain "incorrect" or "fail"
-    // - skill_after >= skill_before (or close to it)
     let correct_count = recent.iter()
-        .filter(|s| {
-            let eval_lower = s.eval_summary.to_lowercase();
-            !eval_lower.contains("incorrect") && 
-            !eval_lower.contains("fail") &&
-            s.skill_after >= s.skill_before - 0.01 // Allow tiny rounding errors
-        })
+        .filter(|s| is_session_correct(s))
         .count();
     
-    Ok(correct_count as f32 / recent.len() as f32)
+    correct_count as f32 / recent.len() as f32
 }
 
 /// Synchronous version for backward compatibility (deprecated)
@@ -222,12 +360,7 @@ pub fn recent_success_rate_sync(skill: &str, n: usize) -> f32 {
     
     // Count correct sessions
     let correct_count = recent.iter()
-        .filter(|s| {
-            let eval_lower = s.eval_summary.to_lowercase();
-            !eval_lower.contains("incorrect") && 
-            !eval_lower.contains("fail") &&
-            s.skill_after >= s.skill_before - 0.01
-        })
+        .filter(|s| is_session_correct(s))
         .count();
     
     correct_count as f32 / recent.len() as f32
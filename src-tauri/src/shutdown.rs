@@ -0,0 +1,76 @@
+use std::path::Path;
+use tokio::time::{timeout, Duration};
+use crate::cache::save_response_cache_in;
+use crate::config::models::get_model_config;
+use crate::error::ZosError;
+use crate::problems::cache::{cache_path, ProblemCache};
+use crate::state::app::AppState;
+
+/// Longest quitting waits on the flush before giving up on it
+const SHUTDOWN_FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Write the response cache to `path` if it changed since it was last saved
+async fn flush_response_cache(state: &AppState, path: &Path) -> Result<(), ZosError> {
+    if !state.take_response_cache_dirty() {
+        return Ok(());
+    }
+    let result = save_response_cache_in(state, path).await;
+    if result.is_err() {
+        state.mark_response_cache_dirty();
+    }
+    result
+}
+
+/// Move the precomputed problems into `cache`'s queue, skipping any already
+/// queued. Returns whether `cache` changed.
+fn queue_precomputed_problems(state: &AppState, cache: &mut ProblemCache) -> bool {
+    let precomputed = std::mem::take(&mut *state.precomputed_problems.write());
+    let before = cache.queue.len();
+    for problem in precomputed {
+        if !cache.queue.iter().any(|queued| queued.id == problem.id) {
+            cache.queue.push(problem);
+        }
+    }
+    cache.queue.len() != before
+}
+
+/// Flush state that would otherwise be lost on quit: the response cache to
+/// `response_cache` (None when it isn't persisted) and the precomputed
+/// problems into the problem cache at `problem_cache`. Only what changed
+/// since the last flush is written, so calling it again is harmless.
+pub async fn flush_state_in(
+    state: &AppState,
+    response_cache: Option<&Path>,
+    problem_cache: &Path,
+) -> Result<(), ZosError> {
+    if let Some(path) = response_cache {
+        flush_response_cache(state, path).await?;
+    }
+    let _cache_guard = state.problem_cache_lock.lock().await;
+    let mut cache = ProblemCache::load_in(problem_cache).await;
+    if queue_precomputed_problems(state, &mut cache) {
+        cache.save_in(problem_cache).await?;
+    }
+    Ok(())
+}
+
+/// `flush_state_in` for the app's own files
+async fn flush_state(state: &AppState) -> Result<(), ZosError> {
+    let response_cache = get_model_config()
+        .persist_response_cache
+        .then(crate::cache::response_cache_path);
+    flush_state_in(state, response_cache.as_deref(), &cache_path()).await
+}
+
+/// Run on exit: flush state, but never hold up quitting for more than
+/// `SHUTDOWN_FLUSH_TIMEOUT`
+pub async fn on_shutdown(state: &AppState) {
+    match timeout(SHUTDOWN_FLUSH_TIMEOUT, flush_state(state)).await {
+        Ok(Ok(())) => tracing::info!("State flushed on shutdown"),
+        Ok(Err(e)) => tracing::warn!(error = %e, "Failed to flush state on shutdown"),
+        Err(_) => tracing::warn!(
+            timeout_secs = SHUTDOWN_FLUSH_TIMEOUT.as_secs(),
+            "Flushing state on shutdown timed out"
+        ),
+    }
+}
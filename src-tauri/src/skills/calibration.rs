@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use crate::error::ZosError;
+use crate::problems::problem::Problem;
+use crate::problems::selector::normalize_topic;
+use crate::skills::model::SkillVector;
+
+/// Starting value for a skill whose calibration problem was solved
+pub const CALIBRATED_CORRECT: f32 = 0.7;
+/// Starting value for a skill whose calibration problem was missed
+pub const CALIBRATED_INCORRECT: f32 = 0.3;
+/// Calibration problems are the ones closest to this difficulty
+const CALIBRATION_DIFFICULTY: f32 = 0.5;
+
+/// Written once calibration has been submitted, so it isn't offered again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalibrationRecord {
+    pub completed_at: i64,
+    /// Skills that were seeded from a result
+    pub skills: Vec<String>,
+}
+
+/// Calibration marker, kept next to skills.json
+pub fn calibration_path() -> PathBuf {
+    crate::paths::app_data_dir().join("calibration.json")
+}
+
+/// One problem per skill in `skills`, sorted by skill: the one closest to
+/// medium difficulty, ties going to the smallest id. Skills without a
+/// problem are left out.
+pub fn calibration_problems(skills: &SkillVector, problems: &[Problem]) -> Vec<Problem> {
+    let mut names: Vec<&String> = skills.skills.keys().collect();
+    names.sort();
+    names.into_iter()
+        .filter_map(|skill| {
+            problems.iter()
+                .filter(|p| normalize_topic(&p.topic) == normalize_topic(skill))
+                .min_by(|a, b| {
+                    let da = (a.difficulty - CALIBRATION_DIFFICULTY).abs();
+                    let db = (b.difficulty - CALIBRATION_DIFFICULTY).abs();
+                    da.total_cmp(&db).then_with(|| a.id.cmp(&b.id))
+                })
+                .cloned()
+        })
+        .collect()
+}
+
+/// `skills` with each calibrated skill set from its outcomes: all correct
+/// gives `CALIBRATED_CORRECT`, all incorrect `CALIBRATED_INCORRECT`, a mix
+/// lands in between. Skills without a result keep their value.
+pub fn seed_from_calibration(skills: &SkillVector, results: &[(String, bool)]) -> Result<SkillVector, ZosError> {
+    let mut outcomes: HashMap<&str, (usize, usize)> = HashMap::new();
+    for (skill, correct) in results {
+        if !skills.skills.contains_key(skill) {
+            return Err(ZosError::new(
+                format!("Unknown skill '{}' in calibration results", skill),
+                "validation"
+            ));
+        }
+        let entry = outcomes.entry(skill.as_str()).or_insert((0, 0));
+        entry.1 += 1;
+        if *correct {
+            entry.0 += 1;
+        }
+    }
+
+    let mut seeded = skills.clone();
+    for (skill, (correct, total)) in outcomes {
+        let rate = correct as f32 / total as f32;
+        let value = CALIBRATED_INCORRECT + (CALIBRATED_CORRECT - CALIBRATED_INCORRECT) * rate;
+        seeded.skills.insert(skill.to_string(), value);
+    }
+    Ok(seeded)
+}
+
+/// The calibration record at `path`, if calibration was completed
+pub async fn load_calibration_in(path: &Path) -> Option<CalibrationRecord> {
+    let text = tokio::fs::read_to_string(path).await.ok()?;
+    match serde_json::from_str(&text) {
+        Ok(record) => Some(record),
+        Err(e) => {
+            tracing::warn!(path = ?path, error = %e, "Failed to parse calibration record");
+            None
+        }
+    }
+}
+
+/// Mark calibration as completed by writing `record` to `path`
+pub async fn save_calibration_in(path: &Path, record: &CalibrationRecord) -> Result<(), ZosError> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| ZosError::new(
+                format!("Failed to create directory: {}", e),
+                "io"
+            ).with_context(format!("path: {:?}", parent)))?;
+    }
+
+    let json = serde_json::to_string_pretty(record)
+        .map_err(|e| ZosError::new(
+            format!("Failed to serialize calibration record: {}", e),
+            "json_serialize"
+        ))?;
+
+    tokio::fs::write(path, json)
+        .await
+        .map_err(|e| ZosError::new(
+            format!("Failed to write calibration record: {}", e),
+            "io"
+        ).with_context(format!("path: {:?}", path)))?;
+
+    Ok(())
+}
+
+/// Seed `skills` from `results` unless calibration at `path` is already
+/// done, hand the seeded vector to `persist`, and only once that succeeds
+/// record calibration as done. Returns the seeded vector.
+pub async fn complete_calibration_in<F, Fut>(
+    path: &Path,
+    skills: &SkillVector,
+    results: &[(String, bool)],
+    persist: F,
+) -> Result<SkillVector, ZosError>
+where
+    F: FnOnce(SkillVector) -> Fut,
+    Fut: std::future::Future<Output = Result<(), ZosError>>,
+{
+    if load_calibration_in(path).await.is_some() {
+        return Err(ZosError::new("Calibration has already been completed", "validation"));
+    }
+    if results.is_empty() {
+        return Err(ZosError::new("No calibration results given", "validation"));
+    }
+
+    let seeded = seed_from_calibration(skills, results)?;
+    let mut calibrated: Vec<String> = results.iter().map(|(skill, _)| skill.clone()).collect();
+    calibrated.sort();
+    calibrated.dedup();
+    persist(seeded.clone()).await?;
+    save_calibration_in(path, &CalibrationRecord {
+        completed_at: chrono::Utc::now().timestamp(),
+        skills: calibrated,
+    }).await?;
+    Ok(seeded)
+}
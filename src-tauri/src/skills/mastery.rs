@@ -0,0 +1,28 @@
+use std::collections::HashMap;
+use crate::sessions::{success_rate_in, SessionRecord};
+
+/// A skill must be above this value to count as mastered
+pub const MASTERY_SKILL_THRESHOLD: f32 = 0.9;
+/// Recent success rate a skill must exceed to count as mastered
+pub const MASTERY_SUCCESS_THRESHOLD: f32 = 0.8;
+/// Number of recent sessions the success rate is taken over
+pub const MASTERY_SESSION_WINDOW: usize = 5;
+
+/// Whether a skill value and its recent success rate mean the topic is
+/// mastered ("graduated"). Both thresholds are strict.
+pub fn is_mastered(skill_value: f32, success_rate: f32) -> bool {
+    skill_value > MASTERY_SKILL_THRESHOLD && success_rate > MASTERY_SUCCESS_THRESHOLD
+}
+
+/// Mastered skills, sorted by name. With fewer than 3 sessions for a skill
+/// the success rate is neutral (0.5), so new skills never graduate.
+pub fn mastered_skills(skills: &HashMap<String, f32>, sessions: &[SessionRecord]) -> Vec<String> {
+    let mut mastered: Vec<String> = skills.iter()
+        .filter(|(skill, value)| {
+            is_mastered(**value, success_rate_in(sessions, skill, MASTERY_SESSION_WINDOW))
+        })
+        .map(|(skill, _)| skill.clone())
+        .collect();
+    mastered.sort();
+    mastered
+}
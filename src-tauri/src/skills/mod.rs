@@ -1,2 +1,4 @@
+pub mod calibration;
+pub mod mastery;
 pub mod model;
 pub mod store;
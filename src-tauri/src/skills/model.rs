@@ -1,5 +1,22 @@
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
+use rand::Rng;
+use crate::config::skills::{get_skill_defaults, get_skill_weights, SkillDefaults, SkillWeights};
+use crate::sessions::SessionRecord;
+
+/// Skills every profile tracks
+pub const KNOWN_SKILLS: [&str; 10] = [
+    "rl_theory",
+    "ml_theory",
+    "ai_research",
+    "coding_debugging",
+    "algorithms",
+    "production_engineering",
+    "analysis_math",
+    "putnam_competition",
+    "proof_strategy",
+    "logical_reasoning",
+];
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SkillVector {
@@ -7,150 +24,218 @@ pub struct SkillVector {
 }
 
 impl SkillVector {
+    /// Every known skill at its configured starting value (see `SkillDefaults`)
     pub fn new() -> Self {
-        let mut skills = HashMap::new();
-        skills.insert("rl_theory".into(), 0.5);
-        skills.insert("ml_theory".into(), 0.5);
-        skills.insert("ai_research".into(), 0.5);
-        skills.insert("coding_debugging".into(), 0.5);
-        skills.insert("algorithms".into(), 0.5);
-        skills.insert("production_engineering".into(), 0.5);
-        skills.insert("analysis_math".into(), 0.5);
-        skills.insert("putnam_competition".into(), 0.5);
-        skills.insert("proof_strategy".into(), 0.5);
-        skills.insert("logical_reasoning".into(), 0.5);
+        Self::with_defaults(&get_skill_defaults())
+    }
+
+    /// Every known skill at the starting value from `defaults`
+    pub fn with_defaults(defaults: &SkillDefaults) -> Self {
+        let skills = KNOWN_SKILLS.iter()
+            .map(|name| (name.to_string(), defaults.initial_value(name)))
+            .collect();
         Self { skills }
     }
 
-    pub fn update_from_issues(&mut self, issues: &Vec<crate::pipelines::proof::ProofIssue>) {
+    /// Only the skills in `focus`, for focus mode. An empty focus, or one
+    /// naming no tracked skill, keeps every skill.
+    pub fn restricted_to(&self, focus: &[String]) -> SkillVector {
+        let restricted: HashMap<String, f32> = self.skills.iter()
+            .filter(|(skill, _)| focus.contains(skill))
+            .map(|(skill, value)| (skill.clone(), *value))
+            .collect();
+        if restricted.is_empty() {
+            return self.clone();
+        }
+        Self { skills: restricted }
+    }
+
+    pub fn update_from_issues(&mut self, issues: &[crate::pipelines::proof::ProofIssue]) {
+        self.update_from_issues_with(issues, &get_skill_weights());
+    }
+
+    /// Apply issue penalties using the given weights
+    pub fn update_from_issues_with(&mut self, issues: &[crate::pipelines::proof::ProofIssue], weights: &SkillWeights) {
         for issue in issues {
-            match issue.issue_type.as_str() {
-                "missing_justification" => {
-                    if let Some(skill) = self.skills.get_mut("proof_strategy") {
-                        *skill = (*skill - 0.02).max(0.0);
-                    }
-                }
-                "incorrect_logic" => {
-                    if let Some(skill) = self.skills.get_mut("logical_reasoning") {
-                        *skill = (*skill - 0.03).max(0.0);
-                    }
-                }
-                "wrong_definition" => {
-                    if let Some(skill) = self.skills.get_mut("analysis_math") {
-                        *skill = (*skill - 0.02).max(0.0);
-                    }
-                }
-                "math_gaps" => {
-                    if let Some(skill) = self.skills.get_mut("analysis_math") {
-                        *skill = (*skill - 0.03).max(0.0);
-                    }
-                    if let Some(skill) = self.skills.get_mut("putnam_competition") {
-                        *skill = (*skill - 0.02).max(0.0);
-                    }
-                }
-                "rl_math_error" => {
-                    if let Some(skill) = self.skills.get_mut("rl_theory") {
-                        *skill = (*skill - 0.03).max(0.0);
-                    }
-                }
-                "ml_derivation_error" => {
-                    if let Some(skill) = self.skills.get_mut("ml_theory") {
-                        *skill = (*skill - 0.03).max(0.0);
-                    }
-                }
-                "code_bug" => {
-                    if let Some(skill) = self.skills.get_mut("coding_debugging") {
-                        *skill = (*skill - 0.03).max(0.0);
-                    }
-                }
-                "faulty_logic" => {
-                    if let Some(skill) = self.skills.get_mut("logical_reasoning") {
-                        *skill = (*skill - 0.02).max(0.0);
-                    }
-                }
-                "misuse_of_theorem" => {
-                    if let Some(skill) = self.skills.get_mut("proof_strategy") {
-                        *skill = (*skill - 0.02).max(0.0);
-                    }
-                }
-                "undefined_term" => {
-                    if let Some(skill) = self.skills.get_mut("analysis_math") {
-                        *skill = (*skill - 0.02).max(0.0);
-                    }
-                }
-                _ => {}
+            self.penalize_issue_type(&issue.issue_type, weights);
+        }
+    }
+
+    fn penalize_issue_type(&mut self, issue_type: &str, weights: &SkillWeights) {
+        let minor = -weights.minor_issue_penalty;
+        let major = -weights.major_issue_penalty;
+        match issue_type {
+            "missing_justification" => self.adjust("proof_strategy", minor),
+            "incorrect_logic" => self.adjust("logical_reasoning", major),
+            "wrong_definition" => self.adjust("analysis_math", minor),
+            "math_gaps" => {
+                self.adjust("analysis_math", major);
+                self.adjust("putnam_competition", minor);
             }
+            "rl_math_error" => self.adjust("rl_theory", major),
+            "ml_derivation_error" => self.adjust("ml_theory", major),
+            "code_bug" => self.adjust("coding_debugging", major),
+            "faulty_logic" => self.adjust("logical_reasoning", minor),
+            "misuse_of_theorem" => self.adjust("proof_strategy", minor),
+            "undefined_term" => self.adjust("analysis_math", minor),
+            _ => {}
         }
     }
 
-    pub fn update_from_evaluation(&mut self, evaluation: &Vec<crate::pipelines::proof::QuestionEvaluation>) {
+    pub fn update_from_evaluation(&mut self, evaluation: &[crate::pipelines::proof::QuestionEvaluation]) {
+        self.update_from_evaluation_with(evaluation, &get_skill_weights());
+    }
+
+    /// Apply Step 2 answer rewards using the given weights
+    pub fn update_from_evaluation_with(&mut self, evaluation: &[crate::pipelines::proof::QuestionEvaluation], weights: &SkillWeights) {
         for eval in evaluation {
-            match eval.assessment.as_str() {
-                "correct" => {
-                    // Small positive XP for correct answers
-                    if let Some(skill) = self.skills.get_mut("logical_reasoning") {
-                        *skill = (*skill + 0.01).min(1.0);
-                    }
-                }
-                "partially_correct" => {
-                    if let Some(skill) = self.skills.get_mut("proof_strategy") {
-                        *skill = (*skill + 0.005).min(1.0);
-                    }
-                }
-                _ => {}
-            }
+            self.reward_assessment(&eval.assessment, weights);
+        }
+    }
+
+    fn reward_assessment(&mut self, assessment: &str, weights: &SkillWeights) {
+        match assessment {
+            // Small positive XP for correct answers
+            "correct" => self.adjust("logical_reasoning", weights.correct_answer_reward),
+            "partially_correct" => self.adjust("proof_strategy", weights.partial_answer_reward),
+            _ => {}
         }
     }
 
     /// Reward skills for a perfect proof (no issues, no questions needed)
     pub fn update_for_perfect_proof(&mut self, skill_topic: &str) {
+        self.update_for_perfect_proof_with(skill_topic, &get_skill_weights());
+    }
+
+    /// Perfect proof reward using the given weights
+    pub fn update_for_perfect_proof_with(&mut self, skill_topic: &str, weights: &SkillWeights) {
         // Reward the specific skill domain for a perfect proof
-        if let Some(skill) = self.skills.get_mut(skill_topic) {
-            *skill = (*skill + 0.02).min(1.0);
-        }
+        self.adjust(skill_topic, weights.perfect_proof_reward);
         // Also reward proof strategy and logical reasoning as secondary skills
-        if let Some(skill) = self.skills.get_mut("proof_strategy") {
-            *skill = (*skill + 0.01).min(1.0);
+        self.adjust("proof_strategy", weights.perfect_proof_secondary_reward);
+        self.adjust("logical_reasoning", weights.perfect_proof_secondary_reward);
+    }
+
+    /// Rebuild a skill vector from defaults by replaying `sessions` in
+    /// timestamp order with the given weights
+    pub fn from_sessions(sessions: &[SessionRecord], weights: &SkillWeights) -> Self {
+        let mut ordered: Vec<&SessionRecord> = sessions.iter().collect();
+        ordered.sort_by_key(|s| s.timestamp);
+        let mut skills = Self::new();
+        for record in ordered {
+            skills.replay_session(record, weights);
         }
-        if let Some(skill) = self.skills.get_mut("logical_reasoning") {
-            *skill = (*skill + 0.01).min(1.0);
+        skills
+    }
+
+    /// Re-apply the updates a recorded session made: Step 1 issue penalties,
+    /// Step 2 answer rewards, and the perfect-proof reward. "Revealed" markers
+    /// never changed skills and are skipped. Records saved before issue types
+    /// were stored only replay their rewards.
+    pub fn replay_session(&mut self, record: &SessionRecord, weights: &SkillWeights) {
+        if record.revealed {
+            return;
+        }
+        for issue_type in &record.issue_types {
+            self.penalize_issue_type(issue_type, weights);
+        }
+        for assessment in &record.assessments {
+            self.reward_assessment(assessment, weights);
+        }
+        let is_perfect = record.correct == Some(true)
+            && record.issues.is_empty()
+            && record.assessments.is_empty();
+        if is_perfect {
+            self.update_for_perfect_proof_with(&record.skill, weights);
+        }
+    }
+
+    /// Shift a known skill by `delta`, clamped to [0, 1]
+    fn adjust(&mut self, skill: &str, delta: f32) {
+        if let Some(value) = self.skills.get_mut(skill) {
+            *value = (*value + delta).clamp(0.0, 1.0);
         }
     }
 
     pub fn get_weakest_skill(&self) -> Option<(String, f32)> {
+        self.get_weakest_skill_excluding(&[])
+    }
+
+    /// Weakest skill not in `excluded` (e.g. mastered skills). If every skill
+    /// is excluded, falls back to the weakest of all skills. Ties are broken
+    /// unseeded; selection for the user goes through the `_with` variant
+    /// and `AppState::with_selection_rng`.
+    pub fn get_weakest_skill_excluding(&self, excluded: &[String]) -> Option<(String, f32)> {
+        self.get_weakest_skill_excluding_with(excluded, &mut rand::thread_rng())
+    }
+
+    /// `get_weakest_skill_excluding`, breaking ties with `rng`
+    pub fn get_weakest_skill_excluding_with<R: Rng + ?Sized>(&self, excluded: &[String], rng: &mut R) -> Option<(String, f32)> {
         use rand::seq::SliceRandom;
-        use rand::thread_rng;
         
         if self.skills.is_empty() {
             return None;
         }
         
+        let mut candidates: Vec<(&String, f32)> = self.skills.iter()
+            .filter(|(k, _)| !excluded.contains(k))
+            .map(|(k, v)| (k, *v))
+            .collect();
+        if candidates.is_empty() {
+            candidates = self.skills.iter().map(|(k, v)| (k, *v)).collect();
+        }
+        
         // Find the minimum skill value
-        let min_value = self.skills.values()
-            .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
-            .copied()?;
+        let min_value = candidates.iter()
+            .map(|(_, v)| *v)
+            .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))?;
         
-        // Collect all skills with the minimum value
-        let tied_skills: Vec<(String, f32)> = self.skills.iter()
-            .filter(|(_, &v)| (v - min_value).abs() < f32::EPSILON)
-            .map(|(k, v)| (k.clone(), *v))
+        // Collect all skills with the minimum value, in name order so a
+        // seeded rng picks the same one regardless of map order
+        let mut tied_skills: Vec<(String, f32)> = candidates.into_iter()
+            .filter(|(_, v)| (v - min_value).abs() < f32::EPSILON)
+            .map(|(k, v)| (k.clone(), v))
             .collect();
+        tied_skills.sort_by(|a, b| a.0.cmp(&b.0));
         
         // Randomly pick one from the tied skills
-        let mut rng = thread_rng();
-        tied_skills.choose(&mut rng).cloned()
+        tied_skills.choose(rng).cloned()
+    }
+
+    /// Weakest skill not in `excluded`, passing over skills in `cooldown`
+    /// (recently recommended) unless they are the only ones left. Ties are
+    /// broken with `rng`.
+    pub fn get_weakest_skill_with_cooldown<R: Rng + ?Sized>(
+        &self,
+        excluded: &[String],
+        cooldown: &[String],
+        rng: &mut R,
+    ) -> Option<(String, f32)> {
+        let rested = self.skills.keys().any(|k| !excluded.contains(k) && !cooldown.contains(k));
+        if !rested {
+            return self.get_weakest_skill_excluding_with(excluded, rng);
+        }
+        let mut skipped = excluded.to_vec();
+        skipped.extend(cooldown.iter().cloned());
+        self.get_weakest_skill_excluding_with(&skipped, rng)
     }
 
     pub fn weakest_n(&self, n: usize) -> Vec<(String, f32)> {
+        self.weakest_n_with(n, &mut rand::thread_rng())
+    }
+
+    /// `weakest_n`, shuffling ties with `rng`
+    pub fn weakest_n_with<R: Rng + ?Sized>(&self, n: usize, rng: &mut R) -> Vec<(String, f32)> {
         use rand::seq::SliceRandom;
-        use rand::thread_rng;
         
         let mut skills_vec: Vec<(String, f32)> = self.skills.iter()
             .map(|(k, v)| (k.clone(), *v))
             .collect();
         
-        // Sort by skill value
-        skills_vec.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        // Sort by skill value, then name so ties start in a fixed order
+        skills_vec.sort_by(|a, b| a.1.partial_cmp(&b.1)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.0.cmp(&b.0)));
         
         if skills_vec.is_empty() {
             return vec![];
@@ -159,7 +244,6 @@ impl SkillVector {
         // Group by skill value and randomly pick from ties
         let mut result = Vec::new();
         let mut i = 0;
-        let mut rng = thread_rng();
         
         while result.len() < n && i < skills_vec.len() {
             let current_value = skills_vec[i].1;
@@ -171,7 +255,7 @@ impl SkillVector {
             }
             
             // Randomly shuffle tied group and add to result
-            tied_group.shuffle(&mut rng);
+            tied_group.shuffle(rng);
             for skill in tied_group {
                 if result.len() >= n {
                     break;
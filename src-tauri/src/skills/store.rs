@@ -1,41 +1,135 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
 use crate::skills::model::SkillVector;
 use crate::error::ZosError;
 
+/// A manual skill override, one JSON object per line in `skill_audit.jsonl`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillAuditEntry {
+    pub skill: String,
+    pub old_value: f32,
+    pub new_value: f32,
+    pub timestamp: i64,
+}
+
 fn skills_path() -> PathBuf {
-    // Use platform-specific app data directory
-    #[cfg(target_os = "macos")]
-    {
-        if let Some(home) = std::env::var_os("HOME") {
-            let mut dir = PathBuf::from(home);
-            dir.push("Library/Application Support/com.zacnwo.zos");
-            dir.push("skills.json");
-            return dir;
-        }
+    crate::paths::app_data_dir().join("skills.json")
+}
+
+/// Manual skill overrides, next to skills.json
+pub fn skill_audit_path() -> PathBuf {
+    skills_path().with_file_name("skill_audit.jsonl")
+}
+
+/// Last annealed difficulty per skill, kept next to skills.json
+pub fn target_difficulty_path() -> PathBuf {
+    skills_path().with_file_name("target_difficulty.json")
+}
+
+/// Load the stored per-skill target difficulties (empty if none yet)
+pub async fn load_target_difficulties() -> HashMap<String, f32> {
+    load_target_difficulties_in(&target_difficulty_path()).await
+}
+
+pub async fn load_target_difficulties_in(path: &Path) -> HashMap<String, f32> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(data) => serde_json::from_str(&data).unwrap_or_else(|e| {
+            tracing::warn!(path = ?path, error = %e, "Failed to parse target difficulties, starting fresh");
+            HashMap::new()
+        }),
+        Err(_) => HashMap::new(),
     }
-    
-    #[cfg(target_os = "windows")]
-    {
-        if let Some(appdata) = std::env::var_os("APPDATA") {
-            let mut dir = PathBuf::from(appdata);
-            dir.push("com.zacnwo.zos");
-            dir.push("skills.json");
-            return dir;
-        }
+}
+
+/// Persist the per-skill target difficulties
+pub async fn save_target_difficulties(targets: &HashMap<String, f32>) -> Result<(), ZosError> {
+    save_target_difficulties_in(&target_difficulty_path(), targets).await
+}
+
+pub async fn save_target_difficulties_in(path: &Path, targets: &HashMap<String, f32>) -> Result<(), ZosError> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| ZosError::new(
+                format!("Failed to create directory: {}", e),
+                "io"
+            ).with_context(format!("path: {:?}", parent)))?;
     }
     
-    #[cfg(target_os = "linux")]
-    {
-        if let Some(home) = std::env::var_os("HOME") {
-            let mut dir = PathBuf::from(home);
-            dir.push(".local/share/com.zacnwo.zos");
-            dir.push("skills.json");
-            return dir;
-        }
+    let json = serde_json::to_string_pretty(targets)
+        .map_err(|e| ZosError::new(
+            format!("Failed to serialize target difficulties: {}", e),
+            "json_serialize"
+        ))?;
+    
+    tokio::fs::write(path, json)
+        .await
+        .map_err(|e| ZosError::new(
+            format!("Failed to write target difficulties: {}", e),
+            "io"
+        ).with_context(format!("path: {:?}", path)))?;
+    
+    Ok(())
+}
+
+/// Skills recommendations are restricted to ("focus mode"), next to skills.json
+pub fn focus_skills_path() -> PathBuf {
+    skills_path().with_file_name("focus_skills.json")
+}
+
+/// Load the focus skills (empty when focus mode is off)
+pub async fn load_focus_skills() -> Vec<String> {
+    let path = focus_skills_path();
+    match tokio::fs::read_to_string(&path).await {
+        Ok(data) => serde_json::from_str(&data).unwrap_or_else(|e| {
+            tracing::warn!(path = ?path, error = %e, "Failed to parse focus skills, focus mode off");
+            Vec::new()
+        }),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Persist the focus skills; an empty list turns focus mode off
+pub async fn save_focus_skills(focus: &[String]) -> Result<(), ZosError> {
+    let path = focus_skills_path();
+    if focus.is_empty() {
+        return match tokio::fs::remove_file(&path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(ZosError::new(
+                format!("Failed to clear focus skills: {}", e),
+                "io"
+            ).with_context(format!("path: {:?}", path))),
+            _ => Ok(()),
+        };
+    }
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| ZosError::new(
+                format!("Failed to create directory: {}", e),
+                "io"
+            ).with_context(format!("path: {:?}", parent)))?;
     }
     
-    // Fallback
-    PathBuf::from("skills.json")
+    let json = serde_json::to_string_pretty(focus)
+        .map_err(|e| ZosError::new(
+            format!("Failed to serialize focus skills: {}", e),
+            "json_serialize"
+        ))?;
+    
+    tokio::fs::write(&path, json)
+        .await
+        .map_err(|e| ZosError::new(
+            format!("Failed to write focus skills: {}", e),
+            "io"
+        ).with_context(format!("path: {:?}", path)))?;
+    
+    Ok(())
+}
+
+/// Append an entry to the skill override audit log
+pub async fn append_skill_audit(entry: &SkillAuditEntry) -> Result<(), ZosError> {
+    crate::jsonl::append_line(&skill_audit_path(), entry, "skill audit log").await
 }
 
 /// Load skill vector from disk asynchronously
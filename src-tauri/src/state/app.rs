@@ -1,13 +1,31 @@
 use std::sync::Arc;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use crate::skills::model::SkillVector;
 use crate::state::session::ProofState;
+use crate::pipelines::ollama::ModelThroughput;
 use crate::pipelines::router::RoutingMetrics;
 use crate::cache::CachedResponse;
 use crate::problems::problem::Problem;
+use crate::metrics::{LatencyHistogram, LatencyPercentiles, Metrics};
+use std::collections::{HashMap, HashSet};
 use lru::LruCache;
 use std::num::NonZeroUsize;
 use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::task::AbortHandle;
+use tracing::Instrument;
+use crate::error::{ZosError, STAGE_CANCELLED};
+use crate::config::models::new_selection_rng;
+use rand::rngs::StdRng;
+
+/// How many skill vector snapshots are kept for `undo_skill_update`
+const SKILL_HISTORY_LIMIT: usize = 10;
+/// How many in-progress proof sessions are kept; the least recently used is dropped first
+const PROOF_SESSION_LIMIT: usize = 16;
+/// How many extracted JSON strings are kept (see `extraction_cache`)
+const EXTRACTION_CACHE_LIMIT: usize = 32;
 
 /// Application-wide state container.
 /// All mutable state is centralized here and passed explicitly to functions.
@@ -16,16 +34,58 @@ use std::collections::VecDeque;
 pub struct AppState {
     /// In-memory skill vector cache
     pub skills: Arc<RwLock<Option<SkillVector>>>,
-    /// Current proof-solving session state
-    pub session_state: Arc<RwLock<ProofState>>,
+    /// Skill vectors as they were before each recent update, oldest first
+    pub skill_history: Arc<RwLock<VecDeque<SkillVector>>>,
+    /// Proof-solving state per session, keyed by problem id
+    pub session_state: Arc<RwLock<LruCache<String, ProofState>>>,
+    /// Sessions with a Step 1 or Step 2 run in progress (see `session::begin_step`)
+    pub proof_steps_in_progress: Arc<Mutex<HashSet<String>>>,
     /// Routing performance metrics
     pub routing_metrics: Arc<RwLock<RoutingMetrics>>,
     /// Response cache (LRU with bounded size)
     pub response_cache: Arc<RwLock<LruCache<u64, CachedResponse>>>,
+    /// Set when the response cache changed since it was last persisted
+    pub response_cache_dirty: Arc<AtomicBool>,
     /// Recently selected problem IDs (to avoid immediate repeats)
     pub recently_selected_problems: Arc<RwLock<VecDeque<String>>>,
     /// Precomputed next problems (for instant loading) - stores easier, same, harder
     pub precomputed_problems: Arc<RwLock<Vec<Problem>>>,
+    /// Prometheus-style counters (see `Metrics::metrics_text`)
+    pub metrics: Metrics,
+    /// Per-model call latency histograms
+    pub model_latency: Arc<RwLock<HashMap<String, LatencyHistogram>>>,
+    /// Size and speed of the most recent successful model call
+    pub last_throughput: Arc<RwLock<Option<ModelThroughput>>>,
+    /// When set, nothing calls the model; only static and cached problems are served
+    pub offline_mode: Arc<AtomicBool>,
+    /// Abort handles of cancellable in-flight requests, keyed by request id
+    pub in_flight: Arc<RwLock<HashMap<String, AbortHandle>>>,
+    /// When the user last asked for a problem or started an analysis
+    pub last_activity: Arc<Mutex<Instant>>,
+    /// When each model was last confirmed installed in Ollama
+    pub model_availability: Arc<RwLock<HashMap<String, Instant>>>,
+    /// Recently recommended skills, most recent first (see `skill_cooldown`)
+    pub recent_skills: Arc<RwLock<VecDeque<String>>>,
+    /// JSON extracted from raw model output, keyed by a hash of the raw
+    /// text and stored with that text so a hash collision isn't a hit
+    pub extraction_cache: Arc<Mutex<LruCache<u64, (String, String)>>>,
+    /// Breaks ties in skill and problem selection (see `new_selection_rng`)
+    pub selection_rng: Arc<Mutex<StdRng>>,
+    /// Held across every load-change-save of the problem cache file, so the
+    /// prefetch loop and the routes never overwrite each other's changes
+    pub problem_cache_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+/// Removes a request from `in_flight` however the waiting future ends
+struct InFlightGuard<'a> {
+    state: &'a AppState,
+    request_id: &'a str,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.state.in_flight.write().remove(self.request_id);
+    }
 }
 
 impl AppState {
@@ -33,13 +93,31 @@ impl AppState {
     pub fn new() -> Self {
         AppState {
             skills: Arc::new(RwLock::new(None)),
-            session_state: Arc::new(RwLock::new(ProofState::AwaitingSolution)),
+            skill_history: Arc::new(RwLock::new(VecDeque::with_capacity(SKILL_HISTORY_LIMIT))),
+            session_state: Arc::new(RwLock::new(
+                LruCache::new(NonZeroUsize::new(PROOF_SESSION_LIMIT).expect("16 > 0"))
+            )),
+            proof_steps_in_progress: Arc::new(Mutex::new(HashSet::new())),
             routing_metrics: Arc::new(RwLock::new(RoutingMetrics::default())),
             response_cache: Arc::new(RwLock::new(
                 LruCache::new(NonZeroUsize::new(200).expect("200 > 0"))
             )),
+            response_cache_dirty: Arc::new(AtomicBool::new(false)),
             recently_selected_problems: Arc::new(RwLock::new(VecDeque::with_capacity(5))),
             precomputed_problems: Arc::new(RwLock::new(Vec::new())),
+            metrics: Metrics::new(),
+            model_latency: Arc::new(RwLock::new(HashMap::new())),
+            last_throughput: Arc::new(RwLock::new(None)),
+            offline_mode: Arc::new(AtomicBool::new(false)),
+            in_flight: Arc::new(RwLock::new(HashMap::new())),
+            last_activity: Arc::new(Mutex::new(Instant::now())),
+            model_availability: Arc::new(RwLock::new(HashMap::new())),
+            recent_skills: Arc::new(RwLock::new(VecDeque::new())),
+            extraction_cache: Arc::new(Mutex::new(
+                LruCache::new(NonZeroUsize::new(EXTRACTION_CACHE_LIMIT).expect("32 > 0"))
+            )),
+            selection_rng: Arc::new(Mutex::new(new_selection_rng())),
+            problem_cache_lock: Arc::new(tokio::sync::Mutex::new(())),
         }
     }
 
@@ -56,7 +134,9 @@ impl AppState {
     }
 
     /// Update skills with a closure (requires skills to already be loaded)
-    /// For loading from disk first, use memory::store::update_skills() instead
+    /// For loading from disk first, use memory::store::update_skills() instead.
+    /// The vector is snapshotted before `f` runs so the update can be undone;
+    /// updates that change nothing are not recorded.
     pub fn update_skills<F>(&self, f: F) -> Result<(), crate::error::ZosError>
     where
         F: FnOnce(&mut SkillVector),
@@ -67,28 +147,54 @@ impl AppState {
                 "Skills not loaded - use memory::store::update_skills() to load from disk first",
                 "state"
             ))?;
+        let before = skills.clone();
         f(skills);
+        if skills.skills != before.skills {
+            let mut history = self.skill_history.write();
+            if history.len() == SKILL_HISTORY_LIMIT {
+                history.pop_front();
+            }
+            history.push_back(before);
+        }
         Ok(())
     }
+
+    /// Restore the skill vector from before the most recent update.
+    /// Returns the restored vector, or None when there is nothing to undo.
+    pub fn undo_skill_update(&self) -> Option<SkillVector> {
+        let previous = self.skill_history.write().pop_back()?;
+        *self.skills.write() = Some(previous.clone());
+        Some(previous)
+    }
     
     /// Set skills directly (for initialization from async load)
     pub fn set_skills(&self, skills: SkillVector) {
         *self.skills.write() = Some(skills);
     }
 
-    /// Get current session state
-    pub fn get_session_state(&self) -> ProofState {
-        self.session_state.read().clone()
+    /// Get a session's state; sessions not seen yet are AwaitingSolution
+    pub fn get_session_state(&self, session_id: &str) -> ProofState {
+        self.session_state.write()
+            .get(session_id)
+            .cloned()
+            .unwrap_or(ProofState::AwaitingSolution)
     }
 
-    /// Set session state
-    pub fn set_session_state(&self, state: ProofState) {
-        *self.session_state.write() = state;
+    /// Set a session's state. AwaitingSolution is what unknown sessions
+    /// report anyway, so it is stored by dropping the entry: only sessions
+    /// with work in progress take up one of the `PROOF_SESSION_LIMIT` slots.
+    pub fn set_session_state(&self, session_id: &str, state: ProofState) {
+        let mut sessions = self.session_state.write();
+        if matches!(state, ProofState::AwaitingSolution) {
+            sessions.pop(session_id);
+        } else {
+            sessions.put(session_id.to_string(), state);
+        }
     }
 
-    /// Reset session state
-    pub fn reset_session_state(&self) {
-        *self.session_state.write() = ProofState::AwaitingSolution;
+    /// Start a session over (or create it) at AwaitingSolution
+    pub fn reset_session_state(&self, session_id: &str) {
+        self.set_session_state(session_id, ProofState::AwaitingSolution);
     }
 
     /// Get routing metrics
@@ -101,6 +207,35 @@ impl AppState {
         let mut metrics = self.routing_metrics.write();
         metrics.success_count += 1;
         metrics.total_latency_ms += latency_ms;
+        self.metrics.record_model_latency(latency_ms);
+    }
+
+    /// Add one model call's latency to that model's histogram
+    pub fn record_model_latency(&self, model: &str, latency_ms: u64) {
+        self.model_latency.write()
+            .entry(model.to_string())
+            .or_default()
+            .record(latency_ms);
+    }
+
+    /// Remember the size and speed of a successful call to `model`
+    pub fn record_call_throughput(&self, model: &str, response: &str, latency_ms: u64) {
+        *self.last_throughput.write() = Some(ModelThroughput::new(model, response, latency_ms));
+    }
+
+    /// Throughput of the last successful model call, if any
+    pub fn last_call_throughput(&self) -> Option<ModelThroughput> {
+        self.last_throughput.read().clone()
+    }
+
+    /// p50/p90/p99 latency for every model called so far, sorted by model name
+    pub fn model_latency_percentiles(&self) -> Vec<LatencyPercentiles> {
+        let histograms = self.model_latency.read();
+        let mut percentiles: Vec<LatencyPercentiles> = histograms.iter()
+            .filter_map(|(model, histogram)| LatencyPercentiles::from_histogram(model, histogram))
+            .collect();
+        percentiles.sort_by(|a, b| a.model.cmp(&b.model));
+        percentiles
     }
 
     /// Record a routing failure
@@ -109,8 +244,143 @@ impl AppState {
         metrics.failure_count += 1;
     }
 
+    /// Run `fut` as its own task, registered under `request_id` so that
+    /// `cancel_request` can abort it. Aborting drops the future, which also
+    /// cancels any HTTP request it is waiting on.
+    /// Callers should only put work in `fut` that is safe to abandon midway
+    /// (model calls), and apply state transitions after it returns.
+    /// A `request_id` that is already in flight is rejected without running `fut`.
+    pub async fn run_cancellable<T, F>(&self, request_id: &str, fut: F) -> Result<T, ZosError>
+    where
+        T: Send + 'static,
+        F: Future<Output = Result<T, ZosError>> + Send + 'static,
+    {
+        let handle = {
+            let mut in_flight = self.in_flight.write();
+            if in_flight.contains_key(request_id) {
+                return Err(ZosError::new(
+                    format!("Request '{}' is already in flight", request_id),
+                    "state"
+                ));
+            }
+            let handle = tokio::spawn(fut.in_current_span());
+            in_flight.insert(request_id.to_string(), handle.abort_handle());
+            handle
+        };
+        let _guard = InFlightGuard { state: self, request_id };
+        
+        match handle.await {
+            Ok(result) => result,
+            Err(e) if e.is_cancelled() => {
+                tracing::info!(request_id = %request_id, "Request cancelled");
+                Err(ZosError::new("Request was cancelled", STAGE_CANCELLED))
+            }
+            Err(e) => Err(ZosError::new(format!("Request task failed: {}", e), "state")),
+        }
+    }
+
+    /// Abort the in-flight request with this id. Returns false if no such
+    /// request is running (it may already have finished).
+    pub fn cancel_request(&self, request_id: &str) -> bool {
+        match self.in_flight.write().remove(request_id) {
+            Some(handle) => {
+                handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Mark the response cache as changed since the last persist
+    pub fn mark_response_cache_dirty(&self) {
+        self.response_cache_dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Clear the dirty flag, returning whether it was set
+    pub fn take_response_cache_dirty(&self) -> bool {
+        self.response_cache_dirty.swap(false, Ordering::Relaxed)
+    }
+
+    /// Whether offline mode is on
+    pub fn is_offline(&self) -> bool {
+        self.offline_mode.load(Ordering::Relaxed)
+    }
+
+    /// Turn offline mode on or off
+    pub fn set_offline_mode(&self, offline: bool) {
+        self.offline_mode.store(offline, Ordering::Relaxed);
+    }
+
+    /// Note user activity, which holds off idle-time background work
+    pub fn record_activity(&self) {
+        *self.last_activity.lock() = Instant::now();
+    }
+
+    /// Run `f` with the shared selection rng. Keep `f` short: the rng is
+    /// locked while it runs.
+    pub fn with_selection_rng<T>(&self, f: impl FnOnce(&mut StdRng) -> T) -> T {
+        f(&mut self.selection_rng.lock())
+    }
+
+    /// Start the selection rng over from the configured seed
+    pub fn reseed_selection_rng(&self) {
+        *self.selection_rng.lock() = new_selection_rng();
+    }
+
+    /// How long since the last user activity; zero while an analysis is running
+    pub fn idle_for(&self) -> Duration {
+        if !self.proof_steps_in_progress.lock().is_empty() {
+            return Duration::ZERO;
+        }
+        self.last_activity.lock().elapsed()
+    }
+
+    /// Whether `model` was confirmed installed within the last `ttl`
+    pub fn model_available_within(&self, model: &str, ttl: Duration) -> bool {
+        self.model_availability.read()
+            .get(model)
+            .is_some_and(|confirmed| confirmed.elapsed() < ttl)
+    }
+
+    /// Remember that `model` was just confirmed installed
+    pub fn record_model_available(&self, model: &str) {
+        self.model_availability.write().insert(model.to_string(), Instant::now());
+    }
+
+    /// Forget a model's availability, e.g. after a call to it failed
+    pub fn invalidate_model_availability(&self, model: &str) {
+        self.model_availability.write().remove(model);
+    }
+
+    /// Record that `skill` was just recommended, remembering the last `cooldown`
+    pub fn record_skill_recommended(&self, skill: &str, cooldown: usize) {
+        let mut recent = self.recent_skills.write();
+        recent.push_front(skill.to_string());
+        recent.truncate(cooldown);
+    }
+
+    /// Skills recommended within the last `cooldown` selections
+    pub fn skills_on_cooldown(&self, cooldown: usize) -> Vec<String> {
+        self.recent_skills.read().iter().take(cooldown).cloned().collect()
+    }
+
+    /// The skill to recommend next from `skills`: the weakest not in
+    /// `mastered`, passing over skills recommended in the last `cooldown`
+    /// selections, with ties broken by the shared selection rng. Doesn't
+    /// record the recommendation; see `record_skill_recommended`.
+    pub fn next_weakest_skill(
+        &self,
+        skills: &SkillVector,
+        mastered: &[String],
+        cooldown: usize,
+    ) -> Option<(String, f32)> {
+        let on_cooldown = self.skills_on_cooldown(cooldown);
+        self.with_selection_rng(|rng| skills.get_weakest_skill_with_cooldown(mastered, &on_cooldown, rng))
+    }
+
     /// Record that a problem was just selected (to avoid immediate repeats)
     pub fn record_problem_selected(&self, problem_id: String) {
+        self.record_activity();
         let mut recent = self.recently_selected_problems.write();
         // Remove if already present (to avoid duplicates)
         recent.retain(|id| id != &problem_id);
@@ -127,24 +397,30 @@ impl AppState {
         self.recently_selected_problems.read().iter().cloned().collect()
     }
 
-    /// Get and remove a precomputed problem (prefers same difficulty, then easier, then harder)
-    pub fn take_precomputed_problem(&self, target_difficulty: Option<f32>) -> Option<Problem> {
+    /// Get and remove an eligible precomputed problem (closest to the target
+    /// difficulty, preferring the easier one on ties; most recently added if
+    /// no target). Ineligible problems stay queued.
+    pub fn take_precomputed_problem<F>(&self, target_difficulty: Option<f32>, eligible: F) -> Option<Problem>
+    where
+        F: Fn(&Problem) -> bool,
+    {
         let mut problems = self.precomputed_problems.write();
-        if problems.is_empty() {
-            return None;
-        }
+        let candidates = problems.iter().enumerate().filter(|(_, p)| eligible(p));
         
-        // If we have a target difficulty, try to find the closest match
-        if let Some(target) = target_difficulty {
-            // Sort by distance from target difficulty
-            problems.sort_by(|a, b| {
-                let diff_a = (a.difficulty - target).abs();
-                let diff_b = (b.difficulty - target).abs();
-                diff_a.partial_cmp(&diff_b).unwrap_or(std::cmp::Ordering::Equal)
-            });
-        }
+        let closest = match target_difficulty {
+            None => candidates.last().map(|(i, _)| i)?,
+            Some(target) => candidates
+                .min_by(|(_, a), (_, b)| {
+                    let diff_a = (a.difficulty - target).abs();
+                    let diff_b = (b.difficulty - target).abs();
+                    diff_a.partial_cmp(&diff_b)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                        .then(a.difficulty.partial_cmp(&b.difficulty).unwrap_or(std::cmp::Ordering::Equal))
+                })
+                .map(|(i, _)| i)?,
+        };
         
-        problems.pop()
+        Some(problems.remove(closest))
     }
 
     /// Add a precomputed problem (keeps max 3: easier, same, harder)
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use crate::pipelines::proof::{Step1Response, Step2Response};
 use crate::state::app::AppState;
+use crate::error::ZosError;
 
 /// Represents the current state of a proof-solving session
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,41 +11,174 @@ pub enum ProofState {
     /// User submitted a solution, Step 1 analysis is done, waiting for answers to clarifying questions
     AwaitingClarifyingAnswers {
         step1_response: Step1Response,
+        /// The proof Step 1 analyzed
+        #[serde(default)]
+        proof: String,
     },
     /// User answered questions, Step 2 evaluation is done, waiting for revision
     AwaitingRevision {
         step2_response: Step2Response,
+        /// The proof before revision, to diff the resubmission against
+        #[serde(default)]
+        original_proof: String,
     },
 }
 
-/// Get the current session state from AppState
-pub fn get_state(state: &AppState) -> ProofState {
-    state.get_session_state()
-}
+impl ProofState {
+    /// The submitted proof a revision should be compared with, once Step 2 asked for one
+    pub fn original_proof(&self) -> Option<&str> {
+        match self {
+            ProofState::AwaitingRevision { original_proof, .. } => Some(original_proof),
+            _ => None,
+        }
+    }
 
-/// Set the session state in AppState
-pub fn set_state(state: &AppState, new_state: ProofState) {
-    state.set_session_state(new_state);
+    /// Variant name, as shown to the frontend
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProofState::AwaitingSolution => "AwaitingSolution",
+            ProofState::AwaitingClarifyingAnswers { .. } => "AwaitingClarifyingAnswers",
+            ProofState::AwaitingRevision { .. } => "AwaitingRevision",
+        }
+    }
 }
 
-/// Reset state to initial (when starting a new problem)
-pub fn reset_state(state: &AppState) {
-    state.reset_session_state();
+/// What the frontend needs to resync with the backend's proof state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofStateSummary {
+    pub state: String,
+    pub has_step1: bool,
+    pub has_step2: bool,
 }
 
-/// Log the current state (for debugging)
-pub fn log_state(state: &AppState) {
-    let current_state = get_state(state);
-    match &current_state {
-        ProofState::AwaitingSolution => {
-            tracing::debug!("[Coach] State = AwaitingSolution");
+impl From<&ProofState> for ProofStateSummary {
+    fn from(state: &ProofState) -> Self {
+        ProofStateSummary {
+            state: state.name().to_string(),
+            has_step1: matches!(state, ProofState::AwaitingClarifyingAnswers { .. }),
+            has_step2: matches!(state, ProofState::AwaitingRevision { .. }),
         }
-        ProofState::AwaitingClarifyingAnswers { .. } => {
-            tracing::debug!("[Coach] State = AwaitingClarifyingAnswers");
+    }
+}
+
+/// How a line changed between the original proof and its revision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffTag {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// One line of a proof diff
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProofDiffLine {
+    pub tag: DiffTag,
+    pub text: String,
+}
+
+/// Line-level diff from `original` to `revised`, in reading order
+pub fn proof_diff(original: &str, revised: &str) -> Vec<ProofDiffLine> {
+    use similar::{ChangeTag, TextDiff};
+    TextDiff::from_lines(original, revised)
+        .iter_all_changes()
+        .map(|change| ProofDiffLine {
+            tag: match change.tag() {
+                ChangeTag::Equal => DiffTag::Equal,
+                ChangeTag::Insert => DiffTag::Insert,
+                ChangeTag::Delete => DiffTag::Delete,
+            },
+            text: change.value().trim_end_matches(['\r', '\n']).to_string(),
+        })
+        .collect()
+}
+
+/// Session used when a step is run without a problem id
+pub const DEFAULT_SESSION_ID: &str = "default";
+
+/// Sessions are keyed by problem id, so each open problem has its own flow
+pub fn session_id_for(problem_id: Option<&str>) -> &str {
+    problem_id.unwrap_or(DEFAULT_SESSION_ID)
+}
+
+/// Get a session's current state from AppState
+pub fn get_state(state: &AppState, session_id: &str) -> ProofState {
+    state.get_session_state(session_id)
+}
+
+/// Set a session's state in AppState
+pub fn set_state(state: &AppState, session_id: &str, new_state: ProofState) {
+    state.set_session_state(session_id, new_state);
+}
+
+/// Reset a session to its initial state (when starting a new problem)
+pub fn reset_state(state: &AppState, session_id: &str) {
+    state.reset_session_state(session_id);
+}
+
+/// State a session moves to once Step 1 has analyzed `proof`. A quick check
+/// asks no clarifying questions, so the session goes back to awaiting a solution.
+pub fn state_after_step1(response: &Step1Response, proof: &str, quick: bool) -> ProofState {
+    if quick {
+        ProofState::AwaitingSolution
+    } else {
+        ProofState::AwaitingClarifyingAnswers {
+            step1_response: response.clone(),
+            proof: proof.to_string(),
         }
-        ProofState::AwaitingRevision { .. } => {
-            tracing::debug!("[Coach] State = AwaitingRevision");
+    }
+}
+
+/// Which Step 1 attempts go into session history
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordPolicy {
+    /// Nothing is recorded, e.g. for a quick self-check
+    Never,
+    /// Only perfect proofs are recorded
+    PerfectOnly,
+    /// Every attempt is recorded
+    Always,
+}
+
+impl RecordPolicy {
+    /// Whether an attempt, perfect or not, is recorded
+    pub fn records(self, is_perfect: bool) -> bool {
+        match self {
+            RecordPolicy::Never => false,
+            RecordPolicy::PerfectOnly => is_perfect,
+            RecordPolicy::Always => true,
         }
     }
 }
 
+/// Held by the Step 1 or Step 2 run that owns a session's state; released on drop
+pub struct StepGuard<'a> {
+    state: &'a AppState,
+    session_id: String,
+}
+
+impl Drop for StepGuard<'_> {
+    fn drop(&mut self) {
+        self.state.proof_steps_in_progress.lock().remove(&self.session_id);
+    }
+}
+
+/// Claim a session's state for one Step 1 or Step 2 run. The state is read
+/// before and written after a long model call, so a second submission in
+/// between would act on stale state; it is rejected instead.
+/// Other sessions are unaffected.
+pub fn begin_step<'a>(state: &'a AppState, session_id: &str) -> Result<StepGuard<'a>, ZosError> {
+    if !state.proof_steps_in_progress.lock().insert(session_id.to_string()) {
+        return Err(ZosError::new(
+            "An analysis is already in progress. Wait for it to finish or cancel it.",
+            "proof_state"
+        ));
+    }
+    state.record_activity();
+    Ok(StepGuard { state, session_id: session_id.to_string() })
+}
+
+/// Log a session's current state (for debugging)
+pub fn log_state(state: &AppState, session_id: &str) {
+    tracing::debug!(session_id = %session_id, "[Coach] State = {}", get_state(state, session_id).name());
+}
@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use tokio::time::{timeout, Duration};
+use crate::config::models::{get_model_config, ollama_base_url};
+use crate::models::availability::{ensure_ollama_reachable, model_exists_in_ollama};
+use crate::problems::problem::Problem;
+use crate::state::app::AppState;
+
+/// Longest any one Ollama check in `app_status` may take
+const STATUS_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// One configured model and whether Ollama has it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelStatus {
+    /// Which config entry it is: "proof", "problem" or "general"
+    pub role: String,
+    pub name: String,
+    pub installed: bool,
+}
+
+/// One line of the readiness checklist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusCheck {
+    pub label: String,
+    pub ok: bool,
+    pub detail: String,
+}
+
+/// Everything a study session needs, checked in one call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppStatus {
+    pub ollama_reachable: bool,
+    pub models: Vec<ModelStatus>,
+    pub problem_count: usize,
+    pub skills_loaded: bool,
+    pub cache_entries: usize,
+    /// The above as a checklist, in display order
+    pub checks: Vec<StatusCheck>,
+    /// Every check passed
+    pub ready: bool,
+}
+
+impl AppStatus {
+    /// Build the checklist from the gathered results. `ollama` carries the
+    /// reason when Ollama could not be reached; models are only checked
+    /// when it could.
+    pub fn new(
+        ollama: Result<(), String>,
+        models: Vec<ModelStatus>,
+        problem_count: usize,
+        skills_loaded: bool,
+        cache_entries: usize,
+    ) -> Self {
+        let mut checks = vec![StatusCheck {
+            label: "Ollama reachable".to_string(),
+            ok: ollama.is_ok(),
+            detail: match &ollama {
+                Ok(()) => ollama_base_url(),
+                Err(reason) => reason.clone(),
+            },
+        }];
+        checks.extend(models.iter().map(|model| StatusCheck {
+            label: format!("{} model installed", model.role),
+            ok: model.installed,
+            detail: if model.installed {
+                model.name.clone()
+            } else {
+                format!("{} not found (ollama pull {})", model.name, model.name)
+            },
+        }));
+        checks.push(StatusCheck {
+            label: "Problems available".to_string(),
+            ok: problem_count > 0,
+            detail: format!("{} problems", problem_count),
+        });
+        checks.push(StatusCheck {
+            label: "Skills loaded".to_string(),
+            ok: skills_loaded,
+            detail: if skills_loaded { "ok".to_string() } else { "no skills found".to_string() },
+        });
+        // An empty cache is normal, so this is informational only
+        checks.push(StatusCheck {
+            label: "Response cache".to_string(),
+            ok: true,
+            detail: format!("{} entries", cache_entries),
+        });
+
+        let ready = checks.iter().all(|check| check.ok);
+        AppStatus {
+            ollama_reachable: ollama.is_ok(),
+            models,
+            problem_count,
+            skills_loaded,
+            cache_entries,
+            checks,
+            ready,
+        }
+    }
+}
+
+/// Whether `model` is installed, giving up after `STATUS_CHECK_TIMEOUT`
+async fn model_status(role: &str, name: String) -> ModelStatus {
+    let installed = timeout(STATUS_CHECK_TIMEOUT, model_exists_in_ollama(&name))
+        .await
+        .unwrap_or(false);
+    ModelStatus { role: role.to_string(), name, installed }
+}
+
+/// Check Ollama, the configured models, the problem bank, skills and the
+/// response cache. The Ollama checks run concurrently and each is time-boxed.
+pub async fn app_status(state: &AppState) -> AppStatus {
+    let config = get_model_config();
+    let base_url = ollama_base_url();
+    let (reachable, proof, problem, general) = tokio::join!(
+        timeout(STATUS_CHECK_TIMEOUT, ensure_ollama_reachable(&base_url)),
+        model_status("proof", config.proof_model.clone()),
+        model_status("problem", config.problem_model.clone()),
+        model_status("general", config.general_model.clone()),
+    );
+    let ollama = match reachable {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(e)) => Err(e.message),
+        Err(_) => Err(format!("no response within {}s", STATUS_CHECK_TIMEOUT.as_secs())),
+    };
+    let models = if ollama.is_ok() { vec![proof, problem, general] } else { Vec::new() };
+
+    let problem_count = Problem::load_all().map(|problems| problems.len()).unwrap_or(0);
+    let skills_loaded = crate::memory::store::get_skills(state)
+        .await
+        .map(|skills| !skills.skills.is_empty())
+        .unwrap_or(false);
+    let cache_entries = state.response_cache.read().len();
+
+    AppStatus::new(ollama, models, problem_count, skills_loaded, cache_entries)
+}
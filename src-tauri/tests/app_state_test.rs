@@ -0,0 +1,299 @@
+#[cfg(test)]
+mod tests {
+    use crate::problems::problem::Problem;
+    use crate::state::app::AppState;
+    use crate::error::{STAGE_CANCELLED, STAGE_OFFLINE};
+    use crate::pipelines::router::{zos_query, zos_query_text, TaskType};
+    use crate::memory::store::set_skill_value;
+    use crate::skills::model::SkillVector;
+    use crate::status::{AppStatus, ModelStatus};
+
+    fn problem(id: &str, difficulty: f32) -> Problem {
+        Problem {
+            id: id.to_string(),
+            topic: "algorithms".to_string(),
+            difficulty,
+            statement: String::new(),
+            solution_sketch: String::new(),
+            tags: vec![],
+        }
+    }
+
+    #[test]
+    fn test_take_precomputed_closest_to_target() {
+        let state = AppState::new();
+        state.add_precomputed_problem(problem("easier", 0.3));
+        state.add_precomputed_problem(problem("same", 0.5));
+        state.add_precomputed_problem(problem("harder", 0.7));
+
+        let taken = state.take_precomputed_problem(Some(0.52), |_| true).unwrap();
+        assert_eq!(taken.id, "same");
+        // Tie between 0.3 and 0.7 prefers the easier problem
+        let taken = state.take_precomputed_problem(Some(0.5), |_| true).unwrap();
+        assert_eq!(taken.id, "easier");
+        let taken = state.take_precomputed_problem(Some(0.5), |_| true).unwrap();
+        assert_eq!(taken.id, "harder");
+        assert!(state.take_precomputed_problem(Some(0.5), |_| true).is_none());
+    }
+
+    #[test]
+    fn test_take_precomputed_without_target() {
+        let state = AppState::new();
+        state.add_precomputed_problem(problem("first", 0.3));
+        state.add_precomputed_problem(problem("second", 0.5));
+        assert_eq!(state.take_precomputed_problem(None, |_| true).unwrap().id, "second");
+    }
+
+    #[test]
+    fn test_ineligible_precomputed_problem_stays_queued() {
+        let state = AppState::new();
+        state.add_precomputed_problem(problem("recent", 0.5));
+
+        assert!(state.take_precomputed_problem(Some(0.5), |p| p.id != "recent").is_none());
+        assert_eq!(state.take_precomputed_problem(Some(0.5), |_| true).unwrap().id, "recent");
+    }
+
+    #[tokio::test]
+    async fn test_offline_mode_never_reaches_the_model() {
+        let state = AppState::new();
+        state.set_offline_mode(true);
+
+        let err = zos_query::<serde_json::Value>(&state, TaskType::ProofAnalysis, "", "prove it".to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(err.stage, STAGE_OFFLINE);
+        assert!(!err.is_retryable());
+
+        let err = zos_query_text(&state, TaskType::General, "why?".to_string()).await.unwrap_err();
+        assert_eq!(err.stage, STAGE_OFFLINE);
+
+        // No model call was attempted, so routing recorded neither success nor failure
+        let routing = state.get_routing_metrics();
+        assert_eq!(routing.success_count, 0);
+        assert_eq!(routing.failure_count, 0);
+
+        state.set_offline_mode(false);
+        assert!(!state.is_offline());
+    }
+
+    #[tokio::test]
+    async fn test_set_skill_value_rejects_unknown_skill() {
+        let state = AppState::new();
+        state.set_skills(SkillVector::new());
+
+        let err = set_skill_value(&state, "alchemy", 0.9).await.unwrap_err();
+        assert_eq!(err.stage, "validation");
+        assert!(err.message.contains("alchemy"));
+        assert!(set_skill_value(&state, "algorithms", f32::NAN).await.is_err());
+
+        // Nothing changed in memory
+        assert_eq!(state.get_skills().unwrap().skills.get("algorithms"), Some(&0.5));
+    }
+
+    #[test]
+    fn test_undo_skill_update_restores_pre_update_values() {
+        let state = AppState::new();
+        state.set_skills(SkillVector::new());
+        let before = state.get_skills().unwrap();
+
+        state.update_skills(|s| {
+            s.skills.insert("logical_reasoning".to_string(), 0.1);
+        }).unwrap();
+        assert_eq!(state.get_skills().unwrap().skills.get("logical_reasoning"), Some(&0.1));
+
+        // A no-op update is not recorded, so undo goes back past it
+        state.update_skills(|_| {}).unwrap();
+
+        let restored = state.undo_skill_update().unwrap();
+        assert_eq!(restored.skills, before.skills);
+        assert_eq!(state.get_skills().unwrap().skills, before.skills);
+        assert!(state.undo_skill_update().is_none());
+    }
+
+    #[test]
+    fn test_skill_history_is_bounded() {
+        let state = AppState::new();
+        state.set_skills(SkillVector::new());
+        for i in 0..15 {
+            state.update_skills(|s| {
+                s.skills.insert("algorithms".to_string(), i as f32 / 100.0);
+            }).unwrap();
+        }
+
+        let mut undone = 0;
+        while state.undo_skill_update().is_some() {
+            undone += 1;
+        }
+        assert_eq!(undone, 10);
+        // The oldest kept snapshot is from before update #5
+        assert_eq!(state.get_skills().unwrap().skills.get("algorithms"), Some(&0.04));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_request_aborts_in_flight_task() {
+        let state = AppState::new();
+        let canceller = state.clone();
+        tokio::spawn(async move {
+            while !canceller.cancel_request("req_test") {
+                tokio::task::yield_now().await;
+            }
+        });
+
+        let result: Result<(), _> = state.run_cancellable("req_test", async {
+            std::future::pending::<()>().await;
+            Ok(())
+        }).await;
+        assert_eq!(result.unwrap_err().stage, STAGE_CANCELLED);
+        assert!(state.in_flight.read().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_completed_request_is_unregistered() {
+        let state = AppState::new();
+        let value = state.run_cancellable("req_done", async { Ok(7) }).await.unwrap();
+        assert_eq!(value, 7);
+        assert!(state.in_flight.read().is_empty());
+        assert!(!state.cancel_request("req_done"));
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_request_id_is_rejected() {
+        let state = std::sync::Arc::new(AppState::new());
+        let first = state.clone();
+        let running = tokio::spawn(async move {
+            first.run_cancellable("req_dup", async {
+                std::future::pending::<()>().await;
+                Ok(())
+            }).await
+        });
+        while !state.in_flight.read().contains_key("req_dup") {
+            tokio::task::yield_now().await;
+        }
+
+        let err = state.run_cancellable("req_dup", async { Ok(1) }).await.unwrap_err();
+        assert!(err.message.contains("already in flight"));
+        // The original request is still registered and can be cancelled
+        assert!(state.cancel_request("req_dup"));
+        assert_eq!(running.await.unwrap().unwrap_err().stage, STAGE_CANCELLED);
+    }
+
+    #[tokio::test]
+    async fn test_availability_check_is_cached_within_ttl() {
+        use crate::models::availability::cached_availability;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Duration;
+
+        let state = AppState::new();
+        let checks = AtomicUsize::new(0);
+        let check = || async {
+            checks.fetch_add(1, Ordering::SeqCst);
+            Ok(true)
+        };
+        let ttl = Duration::from_secs(30);
+
+        cached_availability(&state, "m", ttl, check).await.unwrap();
+        cached_availability(&state, "m", ttl, check).await.unwrap();
+        assert_eq!(checks.load(Ordering::SeqCst), 1);
+
+        state.invalidate_model_availability("m");
+        cached_availability(&state, "m", ttl, check).await.unwrap();
+        assert_eq!(checks.load(Ordering::SeqCst), 2);
+
+        // A model that is still being pulled is not cached as available
+        let pulls = AtomicUsize::new(0);
+        let pulling = || async {
+            pulls.fetch_add(1, Ordering::SeqCst);
+            Ok(false)
+        };
+        cached_availability(&state, "other", ttl, pulling).await.unwrap();
+        cached_availability(&state, "other", ttl, pulling).await.unwrap();
+        assert_eq!(pulls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_skill_cooldown_alternates_near_tied_skills() {
+        let state = AppState::new();
+        let mut skills = SkillVector::new();
+        for value in skills.skills.values_mut() {
+            *value = 0.9;
+        }
+        skills.skills.insert("algorithms".to_string(), 0.10);
+        skills.skills.insert("rl_theory".to_string(), 0.11);
+
+        let mut picks = Vec::new();
+        for _ in 0..4 {
+            let cooldown = state.skills_on_cooldown(1);
+            let (skill, _) = skills.get_weakest_skill_with_cooldown(&[], &cooldown, &mut rand::thread_rng()).unwrap();
+            state.record_skill_recommended(&skill, 1);
+            picks.push(skill);
+        }
+        assert_eq!(picks, vec!["algorithms", "rl_theory", "algorithms", "rl_theory"]);
+
+        // The only option is still recommended while on cooldown
+        let only = skills.restricted_to(&["algorithms".to_string()]);
+        let cooldown = vec!["algorithms".to_string()];
+        assert_eq!(only.get_weakest_skill_with_cooldown(&[], &cooldown, &mut rand::thread_rng()).unwrap().0, "algorithms");
+        // A cooldown of 0 remembers nothing
+        state.record_skill_recommended("algorithms", 0);
+        assert!(state.skills_on_cooldown(0).is_empty());
+    }
+
+    #[test]
+    fn test_recommending_twice_honours_focus_mastery_and_cooldown() {
+        let state = AppState::new();
+        let mut skills = SkillVector::new();
+        for value in skills.skills.values_mut() {
+            *value = 0.9;
+        }
+        skills.skills.insert("rl_theory".to_string(), 0.05);
+        skills.skills.insert("algorithms".to_string(), 0.10);
+        skills.skills.insert("ml_theory".to_string(), 0.20);
+        skills.skills.insert("analysis_math".to_string(), 0.30);
+        // rl_theory is weakest overall but outside the focus
+        let focused = skills.restricted_to(&[
+            "algorithms".to_string(),
+            "ml_theory".to_string(),
+            "analysis_math".to_string(),
+        ]);
+        let mastered = vec!["algorithms".to_string()];
+
+        // Looking ahead (as precomputation does) doesn't start a cooldown
+        let peek = state.next_weakest_skill(&focused, &mastered, 1).unwrap().0;
+        assert_eq!(peek, "ml_theory");
+        assert_eq!(state.next_weakest_skill(&focused, &mastered, 1).unwrap().0, "ml_theory");
+
+        let mut picks = Vec::new();
+        for _ in 0..2 {
+            let (skill, _) = state.next_weakest_skill(&focused, &mastered, 1).unwrap();
+            state.record_skill_recommended(&skill, 1);
+            picks.push(skill);
+        }
+        assert_eq!(picks, vec!["ml_theory", "analysis_math"]);
+    }
+
+    #[test]
+    fn test_app_status_checklist() {
+        let model = |role: &str, installed: bool| ModelStatus {
+            role: role.to_string(),
+            name: format!("{}-model", role),
+            installed,
+        };
+        let status = AppStatus::new(Ok(()), vec![model("proof", true), model("general", true)], 12, true, 0);
+        assert!(status.ready);
+        let labels: Vec<&str> = status.checks.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, [
+            "Ollama reachable", "proof model installed", "general model installed",
+            "Problems available", "Skills loaded", "Response cache",
+        ]);
+
+        let status = AppStatus::new(Ok(()), vec![model("proof", false)], 12, true, 3);
+        assert!(!status.ready);
+        assert!(status.checks[1].detail.contains("ollama pull proof-model"));
+
+        let status = AppStatus::new(Err("connection refused".to_string()), Vec::new(), 0, true, 0);
+        assert!(!status.ready && !status.ollama_reachable);
+        assert_eq!(status.checks[0].detail, "connection refused");
+        let failing: Vec<&str> = status.checks.iter().filter(|c| !c.ok).map(|c| c.label.as_str()).collect();
+        assert_eq!(failing, ["Ollama reachable", "Problems available"]);
+    }
+}
@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod tests {
+    use crate::cache::{cache_response, get_cached, load_response_cache_in, normalize_prompt_for_key};
+    use crate::pipelines::proof::SYSTEM_PROMPT;
+    use crate::problems::cache::{
+        band_difficulties, prefetch_step, prefetch_targets, ProblemCache, MAX_PREFETCH_PER_ITERATION, MIN_CACHE,
+        TARGET_CACHE,
+    };
+    use crate::problems::problem::Problem;
+    use crate::shutdown::flush_state_in;
+    use crate::state::app::AppState;
+
+    #[test]
+    fn test_normalization_ignores_whitespace_and_system_prompt() {
+        let user = "Proof:\n  Let n = 1.\n\tThen  n^2 = 1.  ";
+        assert_eq!(normalize_prompt_for_key(user, ""), "Proof: Let n = 1. Then n^2 = 1.");
+
+        let full = format!("{}\n\n{}", SYSTEM_PROMPT, user);
+        assert_eq!(normalize_prompt_for_key(&full, SYSTEM_PROMPT), normalize_prompt_for_key(user, ""));
+        assert_ne!(normalize_prompt_for_key("n = 1", ""), normalize_prompt_for_key("n = 2", ""));
+        // Only the caller's system prompt is dropped
+        let other = format!("Generate a problem.\n\n{}", user);
+        assert_ne!(normalize_prompt_for_key(&other, SYSTEM_PROMPT), normalize_prompt_for_key(user, ""));
+        assert_eq!(normalize_prompt_for_key(&other, "Generate a problem."), normalize_prompt_for_key(user, ""));
+    }
+
+    #[test]
+    fn test_reindented_proof_hits_cache() {
+        let state = AppState::new();
+        let original = format!("{}\n\nProof:\n    Let n = 1.\n    Then n^2 = 1.", SYSTEM_PROMPT);
+        let reindented = format!("{}\n\nProof:\nLet n = 1.\n\n  Then n^2 = 1.\n", SYSTEM_PROMPT);
+        cache_response(&state, "model", SYSTEM_PROMPT, &original, &"analysis".to_string()).unwrap();
+
+        assert_eq!(get_cached::<String>(&state, "model", SYSTEM_PROMPT, &reindented).as_deref(), Some("analysis"));
+        // The model name is still part of the key
+        assert!(get_cached::<String>(&state, "other-model", SYSTEM_PROMPT, &reindented).is_none());
+    }
+
+    #[test]
+    fn test_prefetch_sleeps_adaptively_with_backpressure() {
+        // Empty: generate a capped batch and come back soon
+        let (empty_batch, empty_pause) = prefetch_step(0, false);
+        assert_eq!(empty_batch, MAX_PREFETCH_PER_ITERATION);
+        // One short of the minimum: only what is missing
+        assert_eq!(prefetch_step(MIN_CACHE - 1, false).0, 1);
+
+        // At the minimum: nothing while active, top up toward the target when idle
+        let (active_batch, partial_pause) = prefetch_step(MIN_CACHE, false);
+        assert_eq!(active_batch, 0);
+        assert_eq!(prefetch_step(MIN_CACHE, true).0, MAX_PREFETCH_PER_ITERATION);
+
+        // Full: nothing to do, and the longest sleep
+        let (full_batch, full_pause) = prefetch_step(TARGET_CACHE, true);
+        assert_eq!(full_batch, 0);
+        assert!(empty_pause < partial_pause && partial_pause < full_pause);
+    }
+
+    #[test]
+    fn test_activity_resets_idle_time() {
+        use std::time::{Duration, Instant};
+        let state = AppState::new();
+        *state.last_activity.lock() = Instant::now().checked_sub(Duration::from_secs(600)).unwrap();
+        assert!(state.idle_for() >= Duration::from_secs(600));
+        state.record_problem_selected("p1".to_string());
+        assert!(state.idle_for() < Duration::from_secs(5));
+    }
+
+    fn cached_problem(id: &str, topic: &str, difficulty: f32) -> Problem {
+        Problem {
+            id: id.to_string(),
+            topic: topic.to_string(),
+            difficulty,
+            statement: format!("Statement for {}", id),
+            solution_sketch: "Sketch".to_string(),
+            tags: vec![],
+        }
+    }
+
+    #[test]
+    fn test_prefetch_fills_three_difficulty_bands() {
+        // algorithms is weakest (target 0.6); one problem already sits in its middle band
+        let weakest = [("algorithms".to_string(), 0.4), ("rl_theory".to_string(), 0.7)];
+        let mut cache = ProblemCache { queue: vec![cached_problem("a0", "algorithms", 0.62)] };
+
+        let mut iterations = 0;
+        loop {
+            let targets = prefetch_targets(&cache, &weakest, MAX_PREFETCH_PER_ITERATION);
+            if targets.is_empty() {
+                break;
+            }
+            assert!(targets.len() <= MAX_PREFETCH_PER_ITERATION);
+            for (skill, difficulty) in targets {
+                let id = format!("gen{}", cache.queue.len());
+                cache.queue.push(cached_problem(&id, &skill, difficulty));
+            }
+            iterations += 1;
+        }
+        // Two bands for algorithms plus three for rl_theory, three at a time
+        assert_eq!(iterations, 2);
+
+        let mut spread: Vec<f32> = cache.queue.iter()
+            .filter(|p| p.topic == "algorithms")
+            .map(|p| p.difficulty)
+            .collect();
+        spread.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(spread.len(), 3);
+        let [easier, same, harder] = band_difficulties(0.6);
+        assert!((spread[0] - easier).abs() < 1e-6);
+        assert!((spread[1] - 0.62).abs() < 1e-6 && (same - 0.6).abs() < 1e-6);
+        assert!((spread[2] - harder).abs() < 1e-6);
+        assert!(cache.missing_bands("rl_theory", 0.3).is_empty());
+
+        // A requested difficulty is served from the nearest band
+        let stretch = cache.take_closest("algorithms", 0.9, |_| true).unwrap();
+        assert!((stretch.difficulty - harder).abs() < 1e-6);
+        assert_eq!(cache.missing_bands("algorithms", 0.6), vec![harder]);
+    }
+
+    #[tokio::test]
+    async fn test_problem_cache_remove_round_trip() {
+        let path = std::env::temp_dir()
+            .join(format!("zos_problem_cache_{}", std::process::id()))
+            .join("problems_cache.json");
+        assert!(ProblemCache::load_in(&path).await.queue.is_empty());
+
+        let cache = ProblemCache {
+            queue: vec![cached_problem("p1", "algorithms", 0.4), cached_problem("stale", "rl_theory", 0.6)],
+        };
+        cache.save_in(&path).await.unwrap();
+
+        let mut loaded = ProblemCache::load_in(&path).await;
+        assert!(loaded.remove("stale"));
+        assert!(!loaded.remove("stale"));
+        loaded.save_in(&path).await.unwrap();
+
+        let ids: Vec<String> = ProblemCache::load_in(&path).await.queue.into_iter().map(|p| p.id).collect();
+        assert_eq!(ids, ["p1"]);
+
+        // Clearing saves an empty queue
+        ProblemCache::default().save_in(&path).await.unwrap();
+        assert!(ProblemCache::load_in(&path).await.queue.is_empty());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_flush_writes_caches_once() {
+        let dir = std::env::temp_dir().join(format!("zos_shutdown_flush_{}", std::process::id()));
+        let response_path = dir.join("response_cache.json");
+        let problem_path = dir.join("problems_cache.json");
+
+        let state = AppState::new();
+        cache_response(&state, "model", "", "prompt", &"answer".to_string()).unwrap();
+        state.add_precomputed_problem(cached_problem("pre1", "algorithms", 0.5));
+        flush_state_in(&state, Some(&response_path), &problem_path).await.unwrap();
+
+        let restored = AppState::new();
+        assert_eq!(load_response_cache_in(&restored, &response_path).await.unwrap(), 1);
+        assert_eq!(get_cached::<String>(&restored, "model", "", "prompt").as_deref(), Some("answer"));
+        let ids: Vec<String> = ProblemCache::load_in(&problem_path).await.queue.into_iter().map(|p| p.id).collect();
+        assert_eq!(ids, ["pre1"]);
+
+        // A second flush has nothing new, so it writes nothing and duplicates nothing
+        std::fs::remove_file(&response_path).unwrap();
+        flush_state_in(&state, Some(&response_path), &problem_path).await.unwrap();
+        assert!(!response_path.exists());
+        assert_eq!(ProblemCache::load_in(&problem_path).await.queue.len(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
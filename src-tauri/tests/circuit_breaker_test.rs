@@ -1,7 +1,6 @@
 #[cfg(test)]
 mod tests {
     use crate::circuit_breaker::{CircuitBreaker, ExponentialBackoff};
-    use std::time::Duration;
 
     #[test]
     fn test_circuit_breaker_initial_state() {
@@ -48,4 +47,18 @@ mod tests {
         // Should cap at max
         assert!(backoff.delay_for_attempt(10) <= 5000);
     }
+
+    #[test]
+    fn test_exponential_backoff_jitter_within_bounds() {
+        let backoff = ExponentialBackoff::with_jitter(100, 5000, true);
+
+        for attempt in 0..12 {
+            let upper = backoff.max_delay_for_attempt(attempt);
+            for _ in 0..50 {
+                let delay = backoff.delay_for_attempt(attempt);
+                assert!(delay <= upper, "attempt {}: {} > {}", attempt, delay, upper);
+            }
+        }
+        assert_eq!(backoff.max_delay_for_attempt(10), 5000);
+    }
 }
@@ -1,6 +1,11 @@
 #[cfg(test)]
 mod tests {
-    use crate::error::ZosError;
+    use crate::error::{
+        ZosError, STAGE_JSON_EXTRACT, STAGE_JSON_PARSE, STAGE_MODEL_CALL, STAGE_MODEL_UNAVAILABLE,
+        STAGE_OLLAMA_UNREACHABLE, STAGE_RESPONSE_TOO_LARGE, STAGE_RETRY_EXHAUSTED, STAGE_TIMEOUT,
+        STAGE_TIMEOUT_TRUNCATION, STAGE_TRUNCATED,
+    };
+    use crate::models::availability::ensure_ollama_reachable;
 
     #[test]
     fn test_error_creation() {
@@ -34,4 +39,95 @@ mod tests {
         assert!(display.contains("test_stage"));
         assert!(display.contains("Test error"));
     }
+
+    #[test]
+    fn test_error_is_retryable() {
+        assert!(ZosError::new("bad json", STAGE_JSON_PARSE).is_retryable());
+        assert!(ZosError::new("timed out", STAGE_TIMEOUT).is_retryable());
+        assert!(ZosError::new("both failed", STAGE_RETRY_EXHAUSTED).is_retryable());
+        assert!(!ZosError::new("ollama down", STAGE_MODEL_UNAVAILABLE).is_retryable());
+        assert!(!ZosError::new("disk full", "io").is_retryable());
+        // Stopped for growing past max_response_bytes: asking again would do the same
+        assert!(!ZosError::new("too long", STAGE_RESPONSE_TOO_LARGE).is_retryable());
+    }
+
+    #[test]
+    fn test_both_models_failing_is_retry_exhaustion() {
+        use crate::pipelines::router::fallback_exhausted;
+
+        let last = ZosError::new("bad json", STAGE_JSON_PARSE).with_model("fallback:7b");
+        let err = fallback_exhausted("primary:7b", "fallback:7b", last);
+        assert_eq!(err.stage, STAGE_RETRY_EXHAUSTED);
+        assert!(err.is_retryable());
+        assert!(err.message.contains("primary:7b") && err.message.contains("fallback:7b"));
+        assert_eq!(err.model.as_deref(), Some("fallback:7b"));
+        assert!(err.context.unwrap().contains(STAGE_JSON_PARSE));
+    }
+
+    #[tokio::test]
+    async fn test_only_call_failures_are_retried_against_the_same_model() {
+        use crate::pipelines::router::{call_failure_stage, should_retry_attempt};
+
+        let elapsed = tokio::time::timeout(std::time::Duration::ZERO, std::future::pending::<()>())
+            .await
+            .unwrap_err();
+        let timeout = anyhow::Error::new(elapsed).context("Model 'm' call timed out after 120s");
+        let refused = anyhow::anyhow!("Failed to connect to Ollama API for model 'm'");
+        let empty = anyhow::anyhow!("Model 'm' returned empty response");
+        assert_eq!(call_failure_stage(&timeout), STAGE_TIMEOUT);
+        assert_eq!(call_failure_stage(&refused), STAGE_MODEL_CALL);
+        assert_eq!(call_failure_stage(&empty), STAGE_MODEL_CALL);
+        // Classified by type, not wording
+        let worded_like_a_timeout = anyhow::anyhow!("Problem statement: the request timed out");
+        assert_eq!(call_failure_stage(&worded_like_a_timeout), STAGE_MODEL_CALL);
+        for error in [&timeout, &refused, &empty] {
+            assert!(should_retry_attempt(call_failure_stage(error)));
+        }
+
+        // A response arrived but its JSON was unusable: go to repair instead
+        for stage in [STAGE_JSON_EXTRACT, STAGE_JSON_PARSE, STAGE_TRUNCATED, STAGE_RESPONSE_TOO_LARGE, STAGE_TIMEOUT_TRUNCATION] {
+            assert!(!should_retry_attempt(stage), "{} should not be retried", stage);
+        }
+    }
+
+    #[test]
+    fn test_max_retries_defaults_to_two() {
+        use crate::config::models::ModelConfig;
+        let legacy: ModelConfig = toml::from_str(
+            "proof_model = \"a\"\nproblem_model = \"b\"\ngeneral_model = \"c\""
+        ).unwrap();
+        assert_eq!(legacy.max_retries, 2);
+        let fail_fast: ModelConfig = toml::from_str(
+            "proof_model = \"a\"\nproblem_model = \"b\"\ngeneral_model = \"c\"\nmax_retries = 0"
+        ).unwrap();
+        assert_eq!(fail_fast.max_retries, 0);
+    }
+
+    #[test]
+    fn test_retryable_is_serialized() {
+        let json = serde_json::to_value(ZosError::new("bad json", STAGE_JSON_PARSE)).unwrap();
+        assert_eq!(json["retryable"], true);
+        assert_eq!(json["stage"], "json_parse");
+    }
+
+    #[test]
+    fn test_with_request_id_appends_to_context() {
+        let err = ZosError::new("bad json", STAGE_JSON_PARSE).with_request_id("req_0000abcd");
+        assert_eq!(err.context.as_deref(), Some("request_id: req_0000abcd"));
+
+        let err = ZosError::new("bad json", STAGE_JSON_PARSE)
+            .with_context("attempt 2")
+            .with_request_id("req_0000abcd");
+        assert_eq!(err.context.as_deref(), Some("attempt 2; request_id: req_0000abcd"));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_ollama_fails_fast() {
+        // Nothing listens on the discard port, so the ping is refused immediately
+        let start = std::time::Instant::now();
+        let err = ensure_ollama_reachable("http://127.0.0.1:9").await.unwrap_err();
+        assert_eq!(err.stage, STAGE_OLLAMA_UNREACHABLE);
+        assert!(!err.is_retryable());
+        assert!(start.elapsed() < std::time::Duration::from_secs(2));
+    }
 }
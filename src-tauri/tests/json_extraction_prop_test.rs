@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod tests {
+    use crate::pipelines::ollama_utils::{extract_json, sanitize_raw_output};
+    use proptest::prelude::*;
+    use serde_json::Value;
+
+    /// The path the router takes for every structured response
+    fn recover(raw: &str) -> Option<Value> {
+        let sanitized = sanitize_raw_output(raw);
+        let json = extract_json(&sanitized).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    /// String values, including quotes, braces, brackets and commas. Backslashes,
+    /// smart quotes and whitespace runs are left out: the sanitizer strips
+    /// `\(`-style LaTeX markers, rewrites smart quotes and collapses whitespace.
+    fn json_text() -> impl Strategy<Value = String> {
+        r#"[A-Za-z0-9_+*^=.()<>,:;"{}\[\]-]{1,12}( [A-Za-z0-9_+*^=.()<>,:;"{}\[\]-]{1,12}){0,3}"#
+    }
+
+    fn json_leaf() -> impl Strategy<Value = Value> {
+        prop_oneof![
+            Just(Value::Null),
+            any::<bool>().prop_map(Value::Bool),
+            any::<i32>().prop_map(Value::from),
+            json_text().prop_map(Value::String),
+        ]
+    }
+
+    fn json_value() -> impl Strategy<Value = Value> {
+        json_leaf().prop_recursive(3, 24, 4, |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..4).prop_map(Value::Array),
+                prop::collection::btree_map("[a-z_]{1,10}", inner, 0..4)
+                    .prop_map(|m| Value::Object(m.into_iter().collect())),
+            ]
+        })
+    }
+
+    /// Top-level objects, which is what every prompt asks the model for
+    fn json_object() -> impl Strategy<Value = Value> {
+        prop::collection::btree_map("[a-z_]{1,10}", json_value(), 1..5)
+            .prop_map(|m| Value::Object(m.into_iter().collect()))
+    }
+
+    /// Prose a model might put around the JSON: no braces, fences or quotes
+    fn prose() -> impl Strategy<Value = String> {
+        prop_oneof![
+            Just(String::new()),
+            "[A-Za-z ,.:;!?]{1,60}",
+            Just("Here is the analysis of the proof:".to_string()),
+            Just("Let \\(n\\) be an integer, so \\(n^2 \\geq 0\\).".to_string()),
+            Just("The key step uses \\[a^2 + b^2 = c^2\\].".to_string()),
+        ]
+    }
+
+    fn think_block() -> impl Strategy<Value = String> {
+        prop_oneof![
+            Just(String::new()),
+            "[A-Za-z ,.]{0,40}".prop_map(|t| format!("<think>{}</think>", t)),
+            Just("<think>Maybe {\"draft\": true} first?</think>\n".to_string()),
+        ]
+    }
+
+    /// Serialize `value` the way a model might: compact or pretty, with or
+    /// without a trailing comma, inside a fence or not
+    fn render(value: &Value, pretty: bool, trailing_comma: bool, fence: u8) -> String {
+        let mut body = if pretty {
+            serde_json::to_string_pretty(value).unwrap()
+        } else {
+            serde_json::to_string(value).unwrap()
+        };
+        if trailing_comma {
+            // Insert a comma before the final closing brace
+            let close = body.rfind('}').unwrap();
+            body.insert(close, ',');
+        }
+        match fence {
+            0 => body,
+            1 => format!("```json\n{}\n```", body),
+            _ => format!("```\n{}\n```", body),
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(256))]
+
+        #[test]
+        fn prop_extract_json_recovers_noisy_object(
+            value in json_object(),
+            think in think_block(),
+            before in prose(),
+            after in prose(),
+            pretty in any::<bool>(),
+            trailing_comma in any::<bool>(),
+            fence in 0u8..3,
+        ) {
+            let raw = format!("{}{}\n{}\n{}", think, before, render(&value, pretty, trailing_comma, fence), after);
+            prop_assert_eq!(recover(&raw), Some(value), "raw input: {}", raw);
+        }
+    }
+
+    /// Shapes that broke extraction in the past, checked against the same property
+    #[test]
+    fn test_extract_json_known_cases() {
+        let expected = serde_json::json!({
+            "issues": [{"step_id": "s1", "type": "logic", "description": "x^2 >= 0 needs justification"}],
+            "questions": ["Why is n even?"],
+        });
+        let cases = [
+            // Unclosed code fence
+            "```json\n{\"issues\": [{\"step_id\": \"s1\", \"type\": \"logic\", \"description\": \"x^2 >= 0 needs justification\"}], \"questions\": [\"Why is n even?\"]}".to_string(),
+            // Reasoning block containing braces before the answer
+            "<think>Output {\"issues\": []}? No, there is an issue.</think>{\"issues\": [{\"step_id\": \"s1\", \"type\": \"logic\", \"description\": \"x^2 >= 0 needs justification\"}], \"questions\": [\"Why is n even?\"]}".to_string(),
+            // Close tag only (template opened the think block in the prompt)
+            "thinking about it...</think>\n{\"issues\": [{\"step_id\": \"s1\", \"type\": \"logic\", \"description\": \"x^2 >= 0 needs justification\"}], \"questions\": [\"Why is n even?\"]}".to_string(),
+            // Trailing commas in nested arrays and objects
+            "{\"issues\": [{\"step_id\": \"s1\", \"type\": \"logic\", \"description\": \"x^2 >= 0 needs justification\",},], \"questions\": [\"Why is n even?\",],}".to_string(),
+            // Smart quotes around keys and values
+            "{\u{201C}issues\u{201D}: [{\u{201C}step_id\u{201D}: \u{201C}s1\u{201D}, \u{201C}type\u{201D}: \u{201C}logic\u{201D}, \u{201C}description\u{201D}: \u{201C}x^2 >= 0 needs justification\u{201D}}], \u{201C}questions\u{201D}: [\u{201C}Why is n even?\u{201D}]}".to_string(),
+            // LaTeX prose before a fenced block
+            format!("Since \\(x^2 \\geq 0\\) we have:\n```json\n{}\n```\nHope this helps.", expected),
+        ];
+        for raw in &cases {
+            assert_eq!(recover(raw), Some(expected.clone()), "raw input: {}", raw);
+        }
+    }
+}
@@ -1,6 +1,12 @@
 #[cfg(test)]
 mod tests {
-    use crate::pipelines::ollama_utils::extract_json;
+    use crate::pipelines::ollama_utils::{
+        close_truncated_json, extract_json, extract_json_cached, sanitize_raw_output, strip_think_blocks,
+    };
+    use crate::state::app::AppState;
+    use std::sync::atomic::Ordering;
+    use crate::pipelines::proof::Step1Response;
+    use crate::pipelines::schema::OutputSchema;
 
     #[test]
     fn test_extract_json_from_code_block() {
@@ -47,4 +53,137 @@ mod tests {
         // Should either succeed with fixed JSON or provide helpful error
         assert!(result.is_ok() || result.unwrap_err().to_string().contains("Failed to extract"));
     }
+
+    #[test]
+    fn test_think_block_with_braces_is_ignored() {
+        let raw = r#"<think>
+        The answer should look like {"steps": [ ... maybe {"id": "s0"} and an unbalanced { here
+        </think>
+        {"steps": [{"id": "s1"}], "issues": []}"#;
+
+        let json = extract_json(&sanitize_raw_output(raw)).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["steps"][0]["id"], "s1");
+        assert!(value["issues"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_strip_unclosed_and_orphan_think_blocks() {
+        assert_eq!(strip_think_blocks(r#"{"a": 1}<think>still {thinking"#), r#"{"a": 1}"#);
+        assert_eq!(strip_think_blocks(r#"{"a": 1}</think>"#), r#"{"a": 1}"#);
+        assert_eq!(strip_think_blocks(r#"{"a": 1}</think> done"#), r#"{"a": 1} done"#);
+        assert_eq!(
+            strip_think_blocks(r#"<think>{</think>{"a": 1}<think>}</think>"#),
+            r#"{"a": 1}"#
+        );
+        assert_eq!(strip_think_blocks(r#"{"a": 1}"#), r#"{"a": 1}"#);
+    }
+
+    const FULL_STEP1: &str = r#"{"steps": [{"id": "s1", "text": "Assume n is odd", "role": "assumption"}, {"id": "s2", "text": "Then n^2 is odd", "role": "deduction"}], "issues": [{"step_id": "s2", "type": "missing_justification", "explanation": "Why?"}], "questions": ["Why is n^2 odd?"], "summary": "Mostly fine"}"#;
+
+    /// The Step 1 response salvaged from `FULL_STEP1` cut after `marker`
+    fn salvage_cut_after(marker: &str) -> Step1Response {
+        let cut = FULL_STEP1.find(marker).unwrap() + marker.len();
+        let candidates = close_truncated_json(&FULL_STEP1[..cut]);
+        candidates
+            .into_iter()
+            .find_map(|json| Step1Response::from_partial(serde_json::from_str(&json).unwrap()))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_complete_object_needs_no_closing() {
+        assert!(close_truncated_json(FULL_STEP1).is_empty());
+        assert!(close_truncated_json("no json here").is_empty());
+    }
+
+    #[test]
+    fn test_truncated_object_closes_after_last_complete_value() {
+        let candidates = close_truncated_json(r#"{"a": 1, "b": [1, 2, 3"#);
+        assert_eq!(candidates[0], r#"{"a": 1, "b": [1, 2]}"#);
+
+        let candidates = close_truncated_json(r#"{"a": {"b": "unfinished str"#);
+        assert_eq!(candidates[0], r#"{"a": {}}"#);
+    }
+
+    #[test]
+    fn test_step1_salvaged_at_several_truncation_points() {
+        // Mid second step: only the first step survives
+        let partial = salvage_cut_after(r#""Then n^2"#);
+        assert!(partial.partial);
+        assert_eq!(partial.steps.len(), 1);
+        assert!(partial.issues.is_empty() && partial.questions.is_empty());
+
+        // Mid issue: both steps, no issues yet
+        let partial = salvage_cut_after(r#""type": "missing_"#);
+        assert_eq!(partial.steps.len(), 2);
+        assert!(partial.issues.is_empty());
+
+        // Mid summary: everything but the summary
+        let partial = salvage_cut_after(r#""summary": "Most"#);
+        assert_eq!(partial.steps.len(), 2);
+        assert_eq!(partial.issues.len(), 1);
+        assert_eq!(partial.questions, ["Why is n^2 odd?"]);
+        assert_eq!(partial.summary, "");
+        assert!(partial.partial);
+    }
+
+    #[test]
+    fn test_truncated_primary_and_failing_fallback_return_a_partial() {
+        use crate::error::{ZosError, STAGE_JSON_PARSE, STAGE_RETRY_EXHAUSTED, STAGE_TRUNCATED};
+        use crate::pipelines::router::{or_partial, truncated_partial};
+
+        let cut = FULL_STEP1.find(r#""summary": "Most"#).unwrap();
+        let raw = &FULL_STEP1[..cut];
+        let truncated = ZosError::new("cut off", STAGE_TRUNCATED);
+        let fallback_failed = ZosError::new("fallback failed too", STAGE_RETRY_EXHAUSTED);
+
+        let partial = truncated_partial::<Step1Response>(&truncated, Some(raw));
+        let result = or_partial(Err(fallback_failed.clone()), partial, "primary").unwrap();
+        assert!(result.partial);
+        assert_eq!(result.steps.len(), 2);
+
+        // Only a truncated response is salvaged, and a successful fallback wins
+        let unparsed = ZosError::new("bad json", STAGE_JSON_PARSE);
+        let partial = truncated_partial::<Step1Response>(&unparsed, Some(raw));
+        assert_eq!(or_partial(Err(fallback_failed), partial, "primary").unwrap_err().stage, STAGE_RETRY_EXHAUSTED);
+        let full: Step1Response = serde_json::from_str(FULL_STEP1).unwrap();
+        let partial = truncated_partial::<Step1Response>(&truncated, Some(raw));
+        assert!(!or_partial(Ok(full), partial, "primary").unwrap().partial);
+    }
+
+    #[test]
+    fn test_repeated_extraction_hits_cache() {
+        let state = AppState::new();
+        // Prose around a large object pushes extraction past its fast paths
+        let raw = format!("Here is the analysis:\n```json\n{}\n```\nDone.", FULL_STEP1.replace("Mostly fine", &"x".repeat(50_000)));
+
+        let first = extract_json_cached(&state, &raw).unwrap();
+        assert_eq!(state.metrics.extraction_cache_hit_count.load(Ordering::Relaxed), 0);
+
+        let second = extract_json_cached(&state, &raw).unwrap();
+        assert_eq!(state.metrics.extraction_cache_hit_count.load(Ordering::Relaxed), 1);
+        assert_eq!(first, second);
+        assert_eq!(second, extract_json(&raw).unwrap());
+
+        // Different text is extracted afresh
+        extract_json_cached(&state, r#"{"key": "value"}"#).unwrap();
+        assert_eq!(state.metrics.extraction_cache_hit_count.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_hash_collision_is_not_a_cache_hit() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let state = AppState::new();
+        let text = r#"{"key": "value"}"#;
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        // Another text's entry under the same key
+        state.extraction_cache.lock().put(hasher.finish(), ("other text".to_string(), r#"{"other": 1}"#.to_string()));
+
+        assert_eq!(extract_json_cached(&state, text).unwrap(), text);
+        assert_eq!(state.metrics.extraction_cache_hit_count.load(Ordering::Relaxed), 0);
+    }
 }
@@ -0,0 +1,20 @@
+#[cfg(test)]
+mod tests {
+    use crate::logging::{parse_log_level, LOG_LEVELS};
+
+    #[test]
+    fn test_parse_log_level_accepts_known_levels() {
+        for level in LOG_LEVELS {
+            assert_eq!(parse_log_level(level).unwrap(), level);
+        }
+        assert_eq!(parse_log_level(" DEBUG ").unwrap(), "debug");
+    }
+
+    #[test]
+    fn test_parse_log_level_rejects_unknown() {
+        let err = parse_log_level("verbose").unwrap_err();
+        assert_eq!(err.stage, "validation");
+        assert!(parse_log_level("").is_err());
+        assert!(parse_log_level("info,zos=debug").is_err());
+    }
+}
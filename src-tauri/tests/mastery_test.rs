@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::sessions::SessionRecord;
+    use crate::skills::mastery::{is_mastered, mastered_skills, MASTERY_SKILL_THRESHOLD};
+    use crate::skills::model::SkillVector;
+    use crate::error::ZosError;
+    use crate::skills::calibration::{
+        complete_calibration_in, load_calibration_in, seed_from_calibration, CALIBRATED_CORRECT, CALIBRATED_INCORRECT,
+    };
+
+    fn session(skill: &str, timestamp: i64, correct: bool) -> SessionRecord {
+        SessionRecord {
+            session_id: format!("sess_{}_{}", skill, timestamp),
+            problem_id: "p1".to_string(),
+            skill: skill.to_string(),
+            user_attempt: String::new(),
+            issues: vec![],
+            eval_summary: String::new(),
+            skill_before: 0.9,
+            skill_after: 0.9,
+            difficulty: 0.5,
+            timestamp,
+            correct: Some(correct),
+            assessments: vec![],
+            issue_types: vec![],
+            revealed: false,
+        }
+    }
+
+    #[test]
+    fn test_skill_threshold_is_strict() {
+        assert!(!is_mastered(MASTERY_SKILL_THRESHOLD, 1.0));
+        assert!(!is_mastered(0.9, 1.0));
+        assert!(is_mastered(0.901, 1.0));
+        assert!(!is_mastered(0.95, 0.8));
+        assert!(is_mastered(0.95, 0.81));
+    }
+
+    #[test]
+    fn test_mastered_skills_need_recent_success() {
+        let skills: HashMap<String, f32> = [
+            ("algorithms", 0.95),
+            ("rl_theory", 0.95),
+            ("ml_theory", 0.9),
+            ("ai_research", 0.95),
+        ].into_iter().map(|(k, v)| (k.to_string(), v)).collect();
+
+        let mut sessions = Vec::new();
+        for t in 0..5 {
+            sessions.push(session("algorithms", t, true));
+            // 4 of 5 correct is exactly 0.8, not above it
+            sessions.push(session("rl_theory", t, t != 0));
+            sessions.push(session("ml_theory", t, true));
+        }
+        // An old failure outside the 5-session window doesn't count
+        sessions.push(session("algorithms", -10, false));
+        // Too few sessions: neutral success rate
+        sessions.push(session("ai_research", 0, true));
+        sessions.push(session("ai_research", 1, true));
+
+        assert_eq!(mastered_skills(&skills, &sessions), vec!["algorithms".to_string()]);
+    }
+
+    #[test]
+    fn test_weakest_skill_skips_excluded() {
+        let mut skills = SkillVector::new();
+        skills.skills.insert("algorithms".to_string(), 0.1);
+        skills.skills.insert("rl_theory".to_string(), 0.2);
+
+        let excluded = ["algorithms".to_string()];
+        assert_eq!(skills.get_weakest_skill_excluding(&excluded).unwrap().0, "rl_theory");
+
+        // Everything excluded: fall back to the weakest overall
+        let all: Vec<String> = skills.skills.keys().cloned().collect();
+        assert_eq!(skills.get_weakest_skill_excluding(&all).unwrap().0, "algorithms");
+    }
+
+    #[test]
+    fn test_calibration_seeding() {
+        let skills = SkillVector::new();
+        let results = vec![
+            ("algorithms".to_string(), true),
+            ("rl_theory".to_string(), false),
+            ("ml_theory".to_string(), true),
+            ("ml_theory".to_string(), false),
+        ];
+        let seeded = seed_from_calibration(&skills, &results).unwrap();
+        assert_eq!(seeded.skills["algorithms"], CALIBRATED_CORRECT);
+        assert_eq!(seeded.skills["rl_theory"], CALIBRATED_INCORRECT);
+        assert!((seeded.skills["ml_theory"] - 0.5).abs() < 1e-6);
+        // Uncalibrated skills keep their value
+        assert_eq!(seeded.skills["proof_strategy"], skills.skills["proof_strategy"]);
+
+        assert!(seed_from_calibration(&skills, &[("not_a_skill".to_string(), true)]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_calibration_completes_only_once() {
+        let path = std::env::temp_dir()
+            .join(format!("zos_calibration_{}", std::process::id()))
+            .join("calibration.json");
+        let skills = SkillVector::new();
+        let results = vec![("algorithms".to_string(), true)];
+
+        assert!(load_calibration_in(&path).await.is_none());
+        let seeded = complete_calibration_in(&path, &skills, &results, |_| async { Ok(()) }).await.unwrap();
+        assert_eq!(seeded.skills["algorithms"], CALIBRATED_CORRECT);
+        assert_eq!(load_calibration_in(&path).await.unwrap().skills, vec!["algorithms"]);
+
+        // A second submission is refused rather than re-seeding
+        let again = complete_calibration_in(&path, &seeded, &[("algorithms".to_string(), false)], |_| async { Ok(()) }).await;
+        assert_eq!(again.unwrap_err().stage, "validation");
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_calibration_is_not_marked_done_if_skills_fail_to_save() {
+        let path = std::env::temp_dir()
+            .join(format!("zos_calibration_unsaved_{}", std::process::id()))
+            .join("calibration.json");
+        let results = vec![("algorithms".to_string(), true)];
+
+        let failed = complete_calibration_in(&path, &SkillVector::new(), &results, |_| async {
+            Err(ZosError::new("disk full", "io"))
+        }).await;
+        assert_eq!(failed.unwrap_err().stage, "io");
+        assert!(load_calibration_in(&path).await.is_none());
+
+        // So the user can retry once saving works again
+        let seeded = complete_calibration_in(&path, &SkillVector::new(), &results, |_| async { Ok(()) }).await;
+        assert_eq!(seeded.unwrap().skills["algorithms"], CALIBRATED_CORRECT);
+        assert!(load_calibration_in(&path).await.is_some());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod tests {
+    use crate::cache::{cache_response, get_cached};
+    use crate::metrics::{LatencyHistogram, Metrics, LATENCY_BUCKETS_MS};
+    use crate::pipelines::ollama::{estimate_tokens, ModelThroughput};
+    use crate::state::app::AppState;
+    use std::sync::atomic::Ordering;
+
+    #[test]
+    fn test_metrics_text_exposition_format() {
+        let metrics = Metrics::new();
+        metrics.record_model_latency(120);
+        metrics.record_model_latency(30);
+        metrics.record_fallback();
+
+        let text = metrics.metrics_text();
+        assert!(text.contains("# HELP zos_model_latency_ms_total "));
+        assert!(text.contains("# TYPE zos_model_latency_ms_total counter\n"));
+        assert!(text.contains("\nzos_model_latency_ms_total 150\n"));
+        assert!(text.contains("\nzos_fallbacks_total 1\n"));
+        assert!(text.contains("\nzos_cache_hits_total 0\n"));
+
+        // Every sample line is preceded by its HELP and TYPE lines
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len() % 3, 0);
+        for chunk in lines.chunks(3) {
+            assert!(chunk[0].starts_with("# HELP "));
+            assert!(chunk[1].starts_with("# TYPE ") && chunk[1].ends_with(" counter"));
+            assert!(!chunk[2].starts_with('#'));
+        }
+    }
+
+    #[test]
+    fn test_cache_lookups_update_hit_and_miss_counters() {
+        let state = AppState::new();
+
+        assert!(get_cached::<String>(&state, "model", "", "prompt").is_none());
+        assert_eq!(state.metrics.cache_miss_count.load(Ordering::Relaxed), 1);
+        assert_eq!(state.metrics.cache_hit_count.load(Ordering::Relaxed), 0);
+
+        cache_response(&state, "model", "", "prompt", &"answer".to_string()).unwrap();
+        assert_eq!(get_cached::<String>(&state, "model", "", "prompt").as_deref(), Some("answer"));
+        assert_eq!(state.metrics.cache_hit_count.load(Ordering::Relaxed), 1);
+        assert_eq!(state.metrics.cache_miss_count.load(Ordering::Relaxed), 1);
+        assert!(state.metrics.metrics_text().contains("\nzos_cache_hits_total 1\n"));
+    }
+
+    #[test]
+    fn test_model_throughput_estimates_tokens_per_second() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcde"), 2);
+
+        let throughput = ModelThroughput::new("deepseek-r1:7b", &"x".repeat(400), 2_000);
+        assert_eq!(throughput.response_chars, 400);
+        assert_eq!(throughput.estimated_tokens, 100);
+        assert!((throughput.tokens_per_second - 50.0).abs() < 1e-9);
+
+        assert_eq!(ModelThroughput::new("m", "abcd", 0).tokens_per_second, 0.0);
+
+        // Kept per app state, not process-wide
+        let state = AppState::new();
+        assert!(state.last_call_throughput().is_none());
+        state.record_call_throughput("deepseek-r1:7b", &"x".repeat(400), 2_000);
+        assert_eq!(state.last_call_throughput().unwrap().estimated_tokens, 100);
+        assert!(AppState::new().last_call_throughput().is_none());
+    }
+
+    #[test]
+    fn test_latency_histogram_percentiles() {
+        let mut histogram = LatencyHistogram::default();
+        assert_eq!(histogram.percentile(50.0), None);
+
+        // 98 fast calls and two very slow ones
+        for _ in 0..98 {
+            histogram.record(800);
+        }
+        histogram.record(40_000);
+        histogram.record(95_000);
+
+        assert_eq!(histogram.count(), 100);
+        assert_eq!(histogram.percentile(50.0), Some(1_000));
+        assert_eq!(histogram.percentile(90.0), Some(1_000));
+        assert_eq!(histogram.percentile(99.0), Some(45_000));
+        // Overflow bucket reports the slowest call seen
+        assert_eq!(histogram.percentile(100.0), Some(95_000));
+        assert!(LATENCY_BUCKETS_MS.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_model_latency_percentiles_per_model() {
+        let state = AppState::new();
+        state.record_model_latency("qwen2.5:7b-instruct", 300);
+        state.record_model_latency("deepseek-r1:7b", 4_000);
+        state.record_model_latency("deepseek-r1:7b", 12_000);
+
+        let percentiles = state.model_latency_percentiles();
+        assert_eq!(percentiles.len(), 2);
+        assert_eq!(percentiles[0].model, "deepseek-r1:7b");
+        assert_eq!(percentiles[0].count, 2);
+        assert_eq!(percentiles[0].p50_ms, 5_000);
+        assert_eq!(percentiles[0].p99_ms, 12_000);
+        assert_eq!(percentiles[1].p50_ms, 300);
+    }
+}
@@ -0,0 +1,281 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::{ZosError, STAGE_MODEL_UNAVAILABLE, STAGE_RESPONSE_TOO_LARGE};
+    use crate::config::models::{resolve_ollama_base_url, validate_ollama_url, ModelConfig, SamplingOptions};
+    use crate::models::availability::list_installed_models_at;
+    use crate::pipelines::ollama::{
+        call_ollama_model_at, call_with_schema_at, http_diagnostics_at, parse_structured_response, StreamCollector,
+    };
+    use crate::pipelines::schema::OutputSchema;
+    use crate::problems::problem::Problem;
+    use crate::pipelines::router::TaskType;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    /// Answer `count` HTTP requests with `body`, reporting each request line
+    /// and request body
+    fn serve(count: usize, body: &'static str) -> (String, mpsc::Receiver<(String, String)>) {
+        serve_responses(vec![("200 OK", body); count])
+    }
+
+    /// Answer one HTTP request per `(status, body)` in `responses`, in order
+    fn serve_responses(responses: Vec<(&'static str, &'static str)>) -> (String, mpsc::Receiver<(String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for (status, body) in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some(len) = header.to_lowercase().strip_prefix("content-length:") {
+                        content_length = len.trim().parse().unwrap();
+                    }
+                }
+                let mut request_body = vec![0; content_length];
+                reader.read_exact(&mut request_body).unwrap();
+                let mut stream = reader.into_inner();
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                ).unwrap();
+                tx.send((request_line.trim().to_string(), String::from_utf8_lossy(&request_body).into_owned())).unwrap();
+            }
+        });
+        (base_url, rx)
+    }
+
+    #[test]
+    fn test_validate_ollama_url() {
+        assert_eq!(validate_ollama_url("http://gpu-box.lan:11434/").unwrap(), "http://gpu-box.lan:11434");
+        assert_eq!(validate_ollama_url(" https://10.0.0.5 ").unwrap(), "https://10.0.0.5");
+        assert!(validate_ollama_url("gpu-box.lan:11434").is_err());
+        assert!(validate_ollama_url("ftp://gpu-box.lan").is_err());
+        assert!(validate_ollama_url("").is_err());
+    }
+
+    #[test]
+    fn test_env_override_wins_when_valid() {
+        let configured = "http://localhost:11434";
+        assert_eq!(resolve_ollama_base_url(Some("http://10.0.0.5:11434"), configured), "http://10.0.0.5:11434");
+        assert_eq!(resolve_ollama_base_url(Some("not a url"), configured), configured);
+        assert_eq!(resolve_ollama_base_url(None, configured), configured);
+    }
+
+    #[tokio::test]
+    async fn test_configured_host_is_used_for_model_list() {
+        let (base_url, requests) = serve(1, r#"{"models": [{"name": "qwen2.5:7b-instruct", "size": 42}]}"#);
+        let models = list_installed_models_at(&base_url).await.unwrap();
+        assert_eq!(models[0].name, "qwen2.5:7b-instruct");
+        assert_eq!(requests.recv_timeout(Duration::from_secs(5)).unwrap().0, "GET /api/tags HTTP/1.1");
+    }
+
+    #[tokio::test]
+    async fn test_configured_host_is_used_for_generate() {
+        let (base_url, requests) = serve(1, "{\"response\": \"ok\", \"done\": true}\n");
+        let text = call_ollama_model_at(&base_url, "test-model", "ping", None, None, Duration::from_secs(5)).await.unwrap();
+        assert_eq!(text, "ok");
+        assert_eq!(requests.recv_timeout(Duration::from_secs(5)).unwrap().0, "POST /api/generate HTTP/1.1");
+    }
+
+    #[tokio::test]
+    async fn test_ollama_error_line_is_reported() {
+        let (base_url, _requests) = serve(1, concat!(
+            "{\"response\": \"partial\", \"done\": false}\n",
+            "{\"error\": \"model 'missing:7b' not found\"}\n",
+        ));
+        let err = call_ollama_model_at(&base_url, "missing:7b", "ping", None, None, Duration::from_secs(5)).await.unwrap_err();
+        let zos: ZosError = err.into();
+        assert!(zos.message.contains("model 'missing:7b' not found"));
+        assert_eq!(zos.stage, STAGE_MODEL_UNAVAILABLE);
+        assert!(!zos.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_sampling_options_are_sent_only_when_given() {
+        let (base_url, requests) = serve(2, "{\"response\": \"ok\", \"done\": true}\n");
+        let options = SamplingOptions { temperature: 0.1, top_p: 0.9, num_predict: 512 };
+        call_ollama_model_at(&base_url, "test-model", "ping", Some(&options), None, Duration::from_secs(5)).await.unwrap();
+        let (_, body) = requests.recv_timeout(Duration::from_secs(5)).unwrap();
+        let sent: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(sent["options"]["num_predict"], 512);
+        assert!((sent["options"]["temperature"].as_f64().unwrap() - 0.1).abs() < 1e-6);
+
+        call_ollama_model_at(&base_url, "test-model", "ping", None, None, Duration::from_secs(5)).await.unwrap();
+        let (_, body) = requests.recv_timeout(Duration::from_secs(5)).unwrap();
+        let sent: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert!(sent.get("options").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_schema_constrained_problem_generation() {
+        // Valid JSON straight from the model, with LaTeX the sanitizer would strip
+        let (base_url, requests) = serve(1, concat!(
+            r#"{"response": "{\"id\": \"p1\", \"topic\": \"analysis\", \"difficulty\": 0.4, "#,
+            r#"\"statement\": \"Show \\\\(x^2 \\\\geq 0\\\\)\", \"solution_sketch\": \"Square\", \"tags\": []}", "done": true}"#,
+            "\n"
+        ));
+        let schema = Problem::output_schema().unwrap();
+        let raw = call_with_schema_at(&base_url, "test-model", "generate", None, Some(&schema), Duration::from_secs(5))
+            .await
+            .unwrap();
+        let problem: Problem = parse_structured_response(&raw).unwrap();
+        assert_eq!(problem.id, "p1");
+        assert_eq!(problem.statement, "Show \\(x^2 \\geq 0\\)");
+
+        let (_, body) = requests.recv_timeout(Duration::from_secs(5)).unwrap();
+        let sent: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(sent["format"], schema);
+        let required: Vec<&str> = schema["required"].as_array().unwrap().iter().map(|v| v.as_str().unwrap()).collect();
+        for field in ["id", "topic", "difficulty", "statement", "solution_sketch"] {
+            assert!(required.contains(&field), "{} should be required", field);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_schema_constrained_call_falls_back_to_extraction() {
+        // A model that ignores the schema still goes through extraction
+        let (base_url, _requests) = serve(1, concat!(
+            r#"{"response": "Here you go:\n```json\n{\"id\": \"p2\", \"topic\": \"logic\", \"difficulty\": 0.5, "#,
+            r#"\"statement\": \"Prove it\", \"solution_sketch\": \"Contradiction\"}\n```", "done": true}"#,
+            "\n"
+        ));
+        let schema = Problem::output_schema().unwrap();
+        let raw = call_with_schema_at(&base_url, "test-model", "generate", None, Some(&schema), Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(parse_structured_response::<Problem>(&raw).is_none());
+        let json = crate::pipelines::ollama_utils::extract_json(&raw).unwrap();
+        let problem: Problem = serde_json::from_str(&json).unwrap();
+        assert_eq!(problem.id, "p2");
+        assert_eq!(problem.solution_sketch, "Contradiction");
+    }
+
+    #[test]
+    fn test_schemas_follow_the_response_types() {
+        use crate::pipelines::proof::{Step1Response, Step2Response};
+
+        let step1 = Step1Response::output_schema().unwrap();
+        let properties = step1["properties"].as_object().unwrap();
+        assert!(!properties.contains_key("partial"), "partial is set by us, not the model");
+        assert_eq!(step1["properties"]["issues"]["items"]["properties"]["type"]["type"], "string");
+
+        let step2 = Step2Response::output_schema().unwrap();
+        assert_eq!(step2["properties"]["next_tasks"]["items"]["type"], "string");
+        let assessment = &step2["properties"]["evaluation"]["items"]["properties"]["assessment"];
+        assert_eq!(assessment["enum"].as_array().unwrap().len(), 4);
+
+        let problem = Problem::output_schema().unwrap();
+        assert_eq!(problem["properties"]["difficulty"]["maximum"], 1.0);
+        // Self-contained: nothing for Ollama to resolve
+        for schema in [&step1, &step2, &problem] {
+            assert!(!schema.to_string().contains("$ref"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejected_schema_is_retried_without_it() {
+        let (base_url, requests) = serve_responses(vec![
+            ("400 Bad Request", r#"{"error": "invalid format"}"#),
+            ("200 OK", "{\"response\": \"{\\\"id\\\": \\\"p3\\\"}\", \"done\": true}\n"),
+        ]);
+        let schema = Problem::output_schema().unwrap();
+        let raw = call_with_schema_at(&base_url, "test-model", "generate", None, Some(&schema), Duration::from_secs(5))
+            .await
+            .unwrap();
+        let value: serde_json::Value = parse_structured_response(&raw).unwrap();
+        assert_eq!(value["id"], "p3");
+
+        let (_, first) = requests.recv_timeout(Duration::from_secs(5)).unwrap();
+        let (_, second) = requests.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(serde_json::from_str::<serde_json::Value>(&first).unwrap().get("format").is_some());
+        assert!(serde_json::from_str::<serde_json::Value>(&second).unwrap().get("format").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_other_failures_are_not_retried_without_the_schema() {
+        let (base_url, requests) = serve(2, "{\"error\": \"out of memory\"}\n");
+        let schema = Problem::output_schema().unwrap();
+        let result = call_with_schema_at(&base_url, "test-model", "generate", None, Some(&schema), Duration::from_secs(5)).await;
+        assert!(result.unwrap_err().to_string().contains("out of memory"));
+
+        requests.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(requests.recv_timeout(Duration::from_millis(200)).is_err(), "only one request should be sent");
+    }
+
+    #[test]
+    fn test_sampling_defaults_per_task_and_partial_overrides() {
+        let legacy: ModelConfig = toml::from_str(
+            "proof_model = \"a\"\nproblem_model = \"b\"\ngeneral_model = \"c\""
+        ).unwrap();
+        let proof = TaskType::ProofAnalysis.sampling(&legacy.sampling);
+        let generation = TaskType::ProblemGeneration.sampling(&legacy.sampling);
+        assert!(proof.temperature < generation.temperature);
+
+        let tuned: ModelConfig = toml::from_str(
+            "proof_model = \"a\"\nproblem_model = \"b\"\ngeneral_model = \"c\"\n\
+             [sampling.proof_analysis]\ntemperature = 0.0\ntop_p = 1.0\nnum_predict = 2048"
+        ).unwrap();
+        assert_eq!(tuned.sampling.proof_analysis.num_predict, 2048);
+        assert_eq!(tuned.sampling.problem_generation, legacy.sampling.problem_generation);
+    }
+
+    #[tokio::test]
+    async fn test_http_diagnostics_times_each_ping() {
+        let (base_url, requests) = serve(3, r#"{"models": []}"#);
+        let diagnostics = http_diagnostics_at(&base_url, 3).await;
+        assert_eq!(diagnostics.base_url, base_url);
+        assert_eq!(diagnostics.pings.len(), 3);
+        for ping in &diagnostics.pings {
+            assert_eq!(ping.status, Some(200));
+            assert!(ping.error.is_none());
+            assert_eq!(requests.recv_timeout(Duration::from_secs(5)).unwrap().0, "GET /api/tags HTTP/1.1");
+        }
+    }
+
+    #[test]
+    fn test_stream_split_across_chunks_is_reassembled() {
+        let stream = concat!(
+            "{\"response\": \"Hello\", \"done\": false}\n",
+            "{\"response\": \", world\", \"done\": true}\n",
+        );
+        let mut collector = StreamCollector::new("test-model", 1024);
+        let (first, second) = stream.as_bytes().split_at(20);
+        assert!(!collector.push(first).unwrap());
+        assert!(collector.push(second).unwrap());
+        assert_eq!(collector.finish().unwrap(), "Hello, world");
+    }
+
+    #[test]
+    fn test_oversized_stream_stops_reading() {
+        // A model repeating itself forever, never sending `done`
+        let line = "{\"response\": \"again and again \", \"done\": false}\n";
+        let mut collector = StreamCollector::new("looping-model", 1000);
+        let mut chunks_read = 0;
+        let err = loop {
+            match collector.push(line.as_bytes()) {
+                Ok(done) => assert!(!done),
+                Err(e) => break e,
+            }
+            chunks_read += 1;
+            assert!(chunks_read < 10_000, "collector never stopped");
+        };
+        assert_eq!(err.stage, STAGE_RESPONSE_TOO_LARGE);
+        assert_eq!(err.model.as_deref(), Some("looping-model"));
+        // Stopped at the first chunk past the cap
+        assert_eq!(chunks_read, 1000 / "again and again ".len());
+    }
+}
@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod tests {
+    use crate::paths::{app_data_dir, data_dir, log_dir, path_within, resolve_app_data_dir};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_env_override_wins_over_platform_dir() {
+        let platform = PathBuf::from("/home/u/.local/share/com.zacnwo.zos");
+        assert_eq!(resolve_app_data_dir(Some("/srv/zos "), Some(platform.clone())), PathBuf::from("/srv/zos"));
+        assert_eq!(resolve_app_data_dir(Some(""), Some(platform.clone())), platform);
+        assert_eq!(resolve_app_data_dir(None, Some(platform.clone())), platform);
+        assert_eq!(resolve_app_data_dir(None, None), PathBuf::new());
+    }
+
+    #[test]
+    fn test_files_share_one_root_and_data_subdir() {
+        let root = app_data_dir();
+        assert_eq!(data_dir(), root.join("data"));
+        assert_eq!(log_dir(), root.join("logs"));
+        assert_eq!(crate::config::models::get_config_path(), root.join("models.toml"));
+        assert_eq!(crate::skills::store::target_difficulty_path(), root.join("target_difficulty.json"));
+
+        let data = data_dir();
+        assert_eq!(crate::sessions::sessions_dir(), data.join("sessions"));
+        assert_eq!(crate::brain::store::get_plan_path(), data.join("daily_plan.json"));
+        assert_eq!(crate::cache::response_cache_path(), data.join("response_cache.json"));
+        assert_eq!(crate::problems::cache::cache_path(), data.join("problems_cache.json"));
+        assert_eq!(crate::problems::feedback::get_feedback_path(), data.join("problem_feedback.jsonl"));
+    }
+
+    #[test]
+    fn test_paths_outside_the_root_are_rejected() {
+        let root = Path::new("/srv/zos");
+        assert_eq!(path_within(root, "reports/week.md"), Some(root.join("reports/week.md")));
+        assert_eq!(path_within(root, "/srv/zos/week.md"), Some(root.join("week.md")));
+
+        for outside in ["/etc/passwd", "/srv/zos-other/week.md", "../week.md", "reports/../../week.md", "", "/srv/zos"] {
+            assert_eq!(path_within(root, outside), None, "{:?}", outside);
+        }
+        // Without a known root, absolute paths could point anywhere
+        assert_eq!(path_within(Path::new(""), "/tmp/week.md"), None);
+    }
+}
@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod tests {
+    use crate::brain::store::{append_plan_history_in, load_plan_history_in};
+    use crate::brain::{build_plan, CurriculumPlan, PlanOptions, TaskDirective};
+    use crate::skills::model::SkillVector;
+
+    fn counts(tasks: &[TaskDirective]) -> (usize, usize) {
+        let drills = tasks.iter().filter(|t| matches!(t, TaskDirective::Adaptive { .. })).count();
+        (drills, tasks.len() - drills)
+    }
+
+    #[test]
+    fn test_plan_has_requested_drills_plus_reviews() {
+        // All skills at 0.5: no prerequisite is unmet, so drills aren't merged
+        let skills = SkillVector::new().skills;
+        let reviews = vec!["rl_theory".to_string(), "algorithms".to_string()];
+
+        for num_drills in [1, 2, 5] {
+            let plan = build_plan(&skills, num_drills, reviews.clone());
+            assert_eq!(plan.tasks.len(), num_drills + reviews.len());
+            assert_eq!(counts(&plan.tasks), (num_drills, reviews.len()));
+        }
+
+        let plan = build_plan(&skills, 4, vec![]);
+        assert_eq!(counts(&plan.tasks), (4, 0));
+    }
+
+    #[test]
+    fn test_plan_options_validation() {
+        let skill_count = SkillVector::new().skills.len();
+        assert!(PlanOptions::default().validate(skill_count).is_ok());
+
+        let all = PlanOptions { num_drills: skill_count, ..PlanOptions::default() };
+        assert!(all.validate(skill_count).is_ok());
+
+        let too_many = PlanOptions { num_drills: skill_count + 1, ..PlanOptions::default() };
+        assert_eq!(too_many.validate(skill_count).unwrap_err().stage, "validation");
+        let none = PlanOptions { num_drills: 0, ..PlanOptions::default() };
+        assert!(none.validate(skill_count).is_err());
+        let no_horizon = PlanOptions { horizon_days: 0, ..PlanOptions::default() };
+        assert!(no_horizon.validate(skill_count).is_err());
+    }
+
+    #[test]
+    fn test_taking_tasks_advances_progress() {
+        let mut plan = build_plan(&SkillVector::new().skills, 3, vec!["algorithms".to_string()]);
+        let progress = plan.progress();
+        assert_eq!((progress.completed, progress.total), (0, 4));
+        assert_eq!(progress.percent, 0.0);
+
+        assert!(matches!(plan.take_next_task(), Some(TaskDirective::Adaptive { .. })));
+        plan.take_next_task();
+        let progress = plan.progress();
+        assert_eq!((progress.completed, progress.total), (2, 4));
+        assert!((progress.percent - 50.0).abs() < 1e-4);
+
+        plan.take_next_task();
+        assert!(matches!(plan.take_next_task(), Some(TaskDirective::Review { .. })));
+        assert!(plan.take_next_task().is_none());
+        let progress = plan.progress();
+        assert_eq!((progress.completed, progress.total), (4, 4));
+        assert!((progress.percent - 100.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_plan_without_completed_count_still_loads() {
+        let json = r#"{"tasks": [{"Review": {"skill": "algorithms"}}], "generated_at": 0, "expires_at": 86400}"#;
+        let plan: CurriculumPlan = serde_json::from_str(json).unwrap();
+        assert_eq!(plan.completed, 0);
+        assert_eq!(plan.progress().total, 1);
+    }
+
+    #[tokio::test]
+    async fn test_each_generated_plan_adds_a_history_line() {
+        let dir = std::env::temp_dir().join(format!("zos_plan_history_{}", std::process::id()));
+        let path = dir.join("plan_history.jsonl");
+        let skills = SkillVector::new().skills;
+
+        let mut first = build_plan(&skills, 2, vec![]);
+        let mut second = build_plan(&skills, 3, vec!["algorithms".to_string()]);
+        second.generated_at = first.generated_at + 1;
+        append_plan_history_in(&path, &first).await.unwrap();
+        append_plan_history_in(&path, &second).await.unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text.lines().count(), 2);
+
+        // Newest first, limited
+        let history = load_plan_history_in(&path, 10).await.unwrap();
+        assert_eq!(history.iter().map(|p| p.tasks.len()).collect::<Vec<_>>(), vec![4, 2]);
+        assert_eq!(load_plan_history_in(&path, 1).await.unwrap().len(), 1);
+
+        // Logging the first plan again once finished replaces its entry
+        while first.take_next_task().is_some() {}
+        append_plan_history_in(&path, &first).await.unwrap();
+        let history = load_plan_history_in(&path, 10).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].completed, 0);
+        assert_eq!((history[1].completed, history[1].tasks.len()), (2, 0));
+
+        assert!(load_plan_history_in(&dir.join("missing.jsonl"), 10).await.unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_focus_mode_never_recommends_other_skills() {
+        let mut skills = SkillVector::new();
+        // logical_reasoning is the weakest overall and a prerequisite of proof_strategy
+        skills.skills.insert("logical_reasoning".to_string(), 0.05);
+        skills.skills.insert("putnam_competition".to_string(), 0.6);
+        skills.skills.insert("proof_strategy".to_string(), 0.7);
+        let focus = vec!["putnam_competition".to_string(), "proof_strategy".to_string()];
+
+        let focused = skills.restricted_to(&focus);
+        for _ in 0..20 {
+            assert_eq!(focused.get_weakest_skill().unwrap().0, "putnam_competition");
+        }
+        let plan = build_plan(&focused.skills, 4, vec![]);
+        for task in &plan.tasks {
+            let TaskDirective::Adaptive { skill, .. } = task else { panic!("unexpected review") };
+            assert!(focus.contains(skill), "{} is outside the focus", skill);
+        }
+
+        // No focus: back to the overall weakest
+        assert_eq!(skills.restricted_to(&[]).get_weakest_skill().unwrap().0, "logical_reasoning");
+    }
+}
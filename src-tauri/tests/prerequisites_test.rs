@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::brain::prerequisites::{
+        prerequisite_graph, select_drill_skills, weakest_unmet_prerequisite, PREREQUISITE_THRESHOLD,
+    };
+
+    fn skills(values: &[(&str, f32)]) -> HashMap<String, f32> {
+        values.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+    }
+
+    #[test]
+    fn test_weak_prerequisite_replaces_dependent_skill() {
+        let graph = prerequisite_graph();
+        let values = skills(&[
+            ("analysis_math", 0.2),
+            ("proof_strategy", 0.35),
+            ("logical_reasoning", 0.8),
+            ("algorithms", 0.6),
+        ]);
+        assert_eq!(
+            weakest_unmet_prerequisite("analysis_math", &values, &graph, PREREQUISITE_THRESHOLD),
+            Some("proof_strategy".to_string())
+        );
+
+        // analysis_math is weakest, but proof_strategy is drilled in its place
+        // and is not repeated when it comes up on its own
+        let drills = select_drill_skills(&values, 2, &graph, PREREQUISITE_THRESHOLD);
+        let names: Vec<&str> = drills.iter().map(|(s, _)| s.as_str()).collect();
+        assert_eq!(names, vec!["proof_strategy", "algorithms"]);
+        assert_eq!(drills[0].1, 0.35);
+    }
+
+    #[test]
+    fn test_prerequisites_above_threshold_are_not_substituted() {
+        let graph = prerequisite_graph();
+        let values = skills(&[
+            ("analysis_math", 0.2),
+            ("proof_strategy", 0.5),
+            ("logical_reasoning", 0.6),
+        ]);
+        assert_eq!(weakest_unmet_prerequisite("analysis_math", &values, &graph, PREREQUISITE_THRESHOLD), None);
+
+        let drills = select_drill_skills(&values, 1, &graph, PREREQUISITE_THRESHOLD);
+        assert_eq!(drills, vec![("analysis_math".to_string(), 0.2)]);
+    }
+
+    #[test]
+    fn test_transitive_prerequisite_picks_the_weakest() {
+        let graph = prerequisite_graph();
+        // rl_theory -> ml_theory -> analysis_math -> proof_strategy -> logical_reasoning
+        let values = skills(&[
+            ("rl_theory", 0.1),
+            ("ml_theory", 0.3),
+            ("analysis_math", 0.6),
+            ("proof_strategy", 0.5),
+            ("logical_reasoning", 0.25),
+        ]);
+        assert_eq!(
+            weakest_unmet_prerequisite("rl_theory", &values, &graph, PREREQUISITE_THRESHOLD),
+            Some("logical_reasoning".to_string())
+        );
+    }
+}
@@ -0,0 +1,182 @@
+#[cfg(test)]
+mod tests {
+    use crate::problems::feedback::{
+        append_rating_in, avoid_note, avoid_patterns, compact_ratings, load_ratings_in, ProblemRating,
+        MAX_STORED_RATINGS, MAX_STORED_STATEMENT_CHARS,
+    };
+    use crate::problems::problem::{known_topics, orphan_topics, Problem};
+    use crate::config::models::{resolve_problems_dir, PROBLEMS_DIR_ENV};
+    use crate::problems::selector::closest_known_topic;
+
+    fn parse_sketch(sketch: &str) -> String {
+        let json = format!(
+            r#"{{"id": "p1", "topic": "algorithms", "difficulty": 0.5, "statement": "s", "solution_sketch": {}}}"#,
+            sketch
+        );
+        serde_json::from_str::<Problem>(&json).unwrap().solution_sketch
+    }
+
+    #[test]
+    fn test_sketch_plain_string() {
+        assert_eq!(parse_sketch(r#""Use induction""#), "Use induction");
+    }
+
+    #[test]
+    fn test_sketch_flat_structures() {
+        assert_eq!(parse_sketch(r#"["a", "b"]"#), "a\nb");
+        assert_eq!(parse_sketch(r#"[{"step2": "b", "step1": "a"}]"#), "step1: a\nstep2: b");
+        assert_eq!(parse_sketch(r#"{"step2": "b", "step1": "a"}"#), "step1: a\nstep2: b");
+    }
+
+    #[test]
+    fn test_sketch_nested_structures_keep_all_content() {
+        let sketch = parse_sketch(
+            r#"[{"step": {"a": ["x", "y"], "b": {"c": 3}}}, {"note": null}, "done"]"#
+        );
+        assert_eq!(sketch, "step.a: x\nstep.a: y\nstep.b.c: 3\ndone");
+
+        // Same input always yields the same output
+        let again = parse_sketch(
+            r#"[{"step": {"b": {"c": 3}, "a": ["x", "y"]}}, {"note": null}, "done"]"#
+        );
+        assert_eq!(sketch, again);
+    }
+
+    fn problem_with_topic(id: &str, topic: &str) -> Problem {
+        Problem {
+            id: id.to_string(),
+            topic: topic.to_string(),
+            difficulty: 0.5,
+            statement: String::new(),
+            solution_sketch: String::new(),
+            tags: vec![],
+        }
+    }
+
+    #[test]
+    fn test_orphan_topics_lists_unknown_topics_once() {
+        let problems = vec![
+            problem_with_topic("a", "algorithms"),
+            problem_with_topic("b", "graph_theory"),
+            problem_with_topic("c", "graph_theory"),
+            problem_with_topic("d", "graph-theory"),
+            // Normalizes to a known skill, so selection already accepts it
+            problem_with_topic("e", "RL-Theory"),
+        ];
+        assert_eq!(orphan_topics(&problems), vec!["graph-theory", "graph_theory"]);
+    }
+
+    #[test]
+    fn test_closest_known_topic() {
+        let known = known_topics();
+        assert_eq!(closest_known_topic("rl-theory", &known).as_deref(), Some("rl_theory"));
+        assert_eq!(closest_known_topic("algorithm_s", &known).as_deref(), Some("algorithms"));
+        assert_eq!(closest_known_topic("logical_reasonin", &known).as_deref(), Some("logical_reasoning"));
+    }
+
+    #[test]
+    fn test_legacy_problem_without_tags_deserializes() {
+        let json = r#"{"id": "p1", "topic": "algorithms", "difficulty": 0.5, "statement": "s", "solution_sketch": "t"}"#;
+        let problem: Problem = serde_json::from_str(json).unwrap();
+        assert!(problem.tags.is_empty());
+
+        let tagged = r#"{"id": "p2", "topic": "algorithms", "difficulty": 0.5, "statement": "s", "solution_sketch": "t", "tags": ["induction"]}"#;
+        let problem: Problem = serde_json::from_str(tagged).unwrap();
+        assert_eq!(problem.tags, vec!["induction"]);
+    }
+
+    #[test]
+    fn test_out_of_range_difficulty_is_clamped_on_load() {
+        let dir = std::env::temp_dir().join(format!("zos_difficulty_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cases = [("too_hard", "5.0", 1.0), ("negative", "-1.0", 0.1), ("fine", "0.45", 0.45)];
+        for (id, raw, expected) in cases {
+            let path = dir.join(format!("{}.json", id));
+            let json = format!(
+                r#"{{"id": "{}", "topic": "algorithms", "difficulty": {}, "statement": "s", "solution_sketch": "t"}}"#,
+                id, raw
+            );
+            std::fs::write(&path, json).unwrap();
+            assert_eq!(Problem::load_from_file(&path).unwrap().difficulty, expected, "{}", id);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    fn rating(problem_id: &str, quality: i8, statement: &str, timestamp: i64) -> ProblemRating {
+        ProblemRating::new(problem_id.to_string(), "algorithms".to_string(), quality, statement, timestamp).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_problem_feedback_persists_and_feeds_avoid_note() {
+        let path = std::env::temp_dir()
+            .join(format!("zos_problem_feedback_{}", std::process::id()))
+            .join("problem_feedback.jsonl");
+        assert!(load_ratings_in(&path).await.unwrap().is_empty());
+
+        append_rating_in(&path, &rating("p1", -1, "Show that 1 + 1 = 2.", 100)).await.unwrap();
+        append_rating_in(&path, &rating("p2", 1, "Prove the AM-GM inequality.", 200)).await.unwrap();
+        append_rating_in(&path, &rating("p3", -1, &"x".repeat(1000), 300)).await.unwrap();
+        // Re-rating is appended; the latest rating wins
+        append_rating_in(&path, &rating("p3", 0, "x", 400)).await.unwrap();
+
+        let ratings = load_ratings_in(&path).await.unwrap();
+        assert_eq!(ratings.len(), 4);
+        assert_eq!(ratings[2].statement.len(), MAX_STORED_STATEMENT_CHARS);
+
+        let patterns = avoid_patterns(&ratings, "algorithms");
+        assert_eq!(patterns, ["Show that 1 + 1 = 2."]);
+        assert!(avoid_patterns(&ratings, "rl_theory").is_empty());
+        assert!(avoid_note(&patterns).contains("- Show that 1 + 1 = 2.\n"));
+        assert_eq!(avoid_note(&[]), "");
+
+        assert!(ProblemRating::new("p1".to_string(), "algorithms".to_string(), 2, "s", 0).is_err());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_feedback_log_stays_bounded() {
+        let ratings = vec![rating("p1", -1, "a", 1), rating("p2", 1, "b", 2), rating("p1", 0, "c", 3)];
+        let compacted = compact_ratings(ratings, 10);
+        let kept: Vec<(&str, i8)> = compacted.iter().map(|r| (r.problem_id.as_str(), r.quality)).collect();
+        assert_eq!(kept, [("p2", 1), ("p1", 0)]);
+
+        let path = std::env::temp_dir()
+            .join(format!("zos_problem_feedback_bounded_{}", std::process::id()))
+            .join("problem_feedback.jsonl");
+        for i in 0..=MAX_STORED_RATINGS as i64 {
+            append_rating_in(&path, &rating(&format!("p{}", i), -1, "s", i)).await.unwrap();
+        }
+        let stored = load_ratings_in(&path).await.unwrap();
+        assert_eq!(stored.len(), MAX_STORED_RATINGS);
+        // The oldest rating went first
+        assert_eq!(stored[0].problem_id, "p1");
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_problems_dir_env_is_used_exclusively() {
+        let dir = std::env::temp_dir().join(format!("zos_problems_dir_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("autogen")).unwrap();
+        let json = |id: &str| format!(
+            r#"{{"id": "{}", "topic": "algorithms", "difficulty": 0.5, "statement": "s", "solution_sketch": "t"}}"#,
+            id
+        );
+        std::fs::write(dir.join("bank.json"), json("bank")).unwrap();
+        std::fs::write(dir.join("autogen").join("generated.json"), json("generated")).unwrap();
+
+        std::env::set_var(PROBLEMS_DIR_ENV, &dir);
+        let loaded = Problem::load_all();
+        let writes_to = crate::paths::problems_dir();
+        std::env::remove_var(PROBLEMS_DIR_ENV);
+
+        let mut ids: Vec<String> = loaded.unwrap().into_iter().map(|p| p.id).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["bank", "generated"]);
+        assert_eq!(writes_to, dir);
+
+        assert_eq!(resolve_problems_dir(Some(" /env "), Some("/config")), Some("/env".into()));
+        assert_eq!(resolve_problems_dir(Some(""), Some("/config")), Some("/config".into()));
+        assert_eq!(resolve_problems_dir(None, None), None);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,178 @@
+#[cfg(test)]
+mod tests {
+    use crate::state::app::AppState;
+    use crate::pipelines::proof::{Step1Response, Step2Response};
+    use crate::state::session::{
+        begin_step, get_state, proof_diff, reset_state, set_state, state_after_step1, DiffTag, ProofState, ProofStateSummary, RecordPolicy,
+    };
+    use std::sync::Arc;
+    use tokio::sync::Barrier;
+
+    #[test]
+    fn test_second_step_rejected_while_first_in_progress() {
+        let state = AppState::new();
+        let first = begin_step(&state, "p1").unwrap();
+        let err = begin_step(&state, "p1").err().unwrap();
+        assert_eq!(err.stage, "proof_state");
+        assert!(err.message.contains("already in progress"));
+
+        drop(first);
+        assert!(begin_step(&state, "p1").is_ok());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_submissions_only_one_proceeds() {
+        let state = AppState::new();
+        let barrier = Arc::new(Barrier::new(4));
+        let mut handles = Vec::new();
+        for _ in 0..4 {
+            let state = state.clone();
+            let barrier = barrier.clone();
+            handles.push(tokio::spawn(async move {
+                barrier.wait().await;
+                match begin_step(&state, "p1") {
+                    Ok(_step) => {
+                        // Hold the session across an await, like a model call
+                        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                        true
+                    }
+                    Err(_) => false,
+                }
+            }));
+        }
+
+        let mut proceeded = 0;
+        for handle in handles {
+            if handle.await.unwrap() {
+                proceeded += 1;
+            }
+        }
+        assert_eq!(proceeded, 1);
+        assert!(begin_step(&state, "p1").is_ok());
+    }
+
+    #[test]
+    fn test_reset_round_trip() {
+        let state = AppState::new();
+        let summary = ProofStateSummary::from(&get_state(&state, "p1"));
+        assert_eq!(summary.state, "AwaitingSolution");
+        assert!(!summary.has_step1 && !summary.has_step2);
+
+        set_state(&state, "p1", ProofState::AwaitingClarifyingAnswers {
+            step1_response: Step1Response {
+                steps: vec![],
+                issues: vec![],
+                questions: vec!["Why is n even?".to_string()],
+                summary: String::new(),
+                partial: false,
+            },
+            proof: "Let n be even.".to_string(),
+        });
+        let summary = ProofStateSummary::from(&get_state(&state, "p1"));
+        assert_eq!(summary.state, "AwaitingClarifyingAnswers");
+        assert!(summary.has_step1 && !summary.has_step2);
+
+        reset_state(&state, "p1");
+        let summary = ProofStateSummary::from(&get_state(&state, "p1"));
+        assert_eq!(summary.state, "AwaitingSolution");
+        assert!(!summary.has_step1);
+    }
+
+    #[test]
+    fn test_sessions_are_independent() {
+        let state = AppState::new();
+        let p1_step = begin_step(&state, "p1").unwrap();
+        // A run on another problem is not blocked by p1's
+        assert!(begin_step(&state, "p2").is_ok());
+        drop(p1_step);
+
+        set_state(&state, "p1", ProofState::AwaitingClarifyingAnswers {
+            step1_response: Step1Response {
+                steps: vec![],
+                issues: vec![],
+                questions: vec![],
+                summary: String::new(),
+                partial: false,
+            },
+            proof: String::new(),
+        });
+        // Starting p2 over leaves p1 where it was
+        reset_state(&state, "p2");
+        assert_eq!(get_state(&state, "p1").name(), "AwaitingClarifyingAnswers");
+        assert_eq!(get_state(&state, "p2").name(), "AwaitingSolution");
+        assert_eq!(get_state(&state, "never-opened").name(), "AwaitingSolution");
+    }
+
+    #[test]
+    fn test_browsing_problems_keeps_in_progress_session() {
+        let state = AppState::new();
+        set_state(&state, "p1", ProofState::AwaitingClarifyingAnswers {
+            step1_response: Step1Response {
+                steps: vec![],
+                issues: vec![],
+                questions: vec!["Why is n even?".to_string()],
+                summary: String::new(),
+                partial: false,
+            },
+            proof: "Let n be even.".to_string(),
+        });
+        // Every recommended problem starts a fresh session
+        for i in 0..40 {
+            reset_state(&state, &format!("shown{}", i));
+        }
+        assert_eq!(get_state(&state, "p1").name(), "AwaitingClarifyingAnswers");
+        assert_eq!(state.session_state.read().len(), 1);
+    }
+
+    #[test]
+    fn test_revision_keeps_original_proof_until_reset() {
+        let state = AppState::new();
+        set_state(&state, "p1", ProofState::AwaitingRevision {
+            step2_response: Step2Response { evaluation: vec![], next_tasks: vec![], needs_revision: true },
+            original_proof: "Let n = 2k.\nThen n^2 = 4k^2.\nSo n^2 is even.".to_string(),
+        });
+        let current = get_state(&state, "p1");
+        let original = current.original_proof().unwrap();
+
+        let diff = proof_diff(original, "Let n = 2k.\nThen n^2 = 4k^2 = 2(2k^2).\nSo n^2 is even.");
+        let tags: Vec<DiffTag> = diff.iter().map(|line| line.tag).collect();
+        assert_eq!(tags, [DiffTag::Equal, DiffTag::Delete, DiffTag::Insert, DiffTag::Equal]);
+        assert_eq!(diff[1].text, "Then n^2 = 4k^2.");
+        assert_eq!(diff[2].text, "Then n^2 = 4k^2 = 2(2k^2).");
+
+        reset_state(&state, "p1");
+        assert!(get_state(&state, "p1").original_proof().is_none());
+    }
+
+    #[test]
+    fn test_quick_analysis_leaves_session_awaiting_solution() {
+        let state = AppState::new();
+        let response = Step1Response {
+            steps: vec![],
+            issues: vec![],
+            questions: vec!["Why is n even?".to_string()],
+            summary: String::new(),
+            partial: false,
+        };
+
+        set_state(&state, "p1", state_after_step1(&response, "Let n be even.", true));
+        let summary = ProofStateSummary::from(&get_state(&state, "p1"));
+        assert_eq!(summary.state, "AwaitingSolution");
+        assert!(!summary.has_step1);
+
+        // The full flow still moves on to the clarifying questions
+        set_state(&state, "p1", state_after_step1(&response, "Let n be even.", false));
+        assert_eq!(get_state(&state, "p1").name(), "AwaitingClarifyingAnswers");
+    }
+
+    #[test]
+    fn test_record_policy_decides_which_attempts_are_recorded() {
+        // A quick self-check records nothing, not even a perfect proof
+        assert!(!RecordPolicy::Never.records(true));
+        assert!(!RecordPolicy::Never.records(false));
+        // The full flow always records perfect proofs
+        assert!(RecordPolicy::PerfectOnly.records(true));
+        assert!(!RecordPolicy::PerfectOnly.records(false));
+        assert!(RecordPolicy::Always.records(false));
+    }
+}
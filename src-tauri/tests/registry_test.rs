@@ -0,0 +1,33 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::registry::{get_available_models, get_model};
+
+    #[test]
+    fn test_registered_model_names_are_well_formed() {
+        for name in get_available_models() {
+            assert!(get_model(&name).is_some(), "registered model '{}' should resolve", name);
+
+            let (base, tag) = name.split_once(':')
+                .unwrap_or_else(|| panic!("model '{}' has no tag", name));
+            assert!(!base.is_empty() && !tag.is_empty(), "model '{}' has an empty base or tag", name);
+            assert!(!tag.contains(':'), "model '{}' has more than one tag separator", name);
+            assert!(
+                name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "-.:".contains(c)),
+                "model '{}' contains unexpected characters", name
+            );
+            // Tags are a size ("7b") optionally followed by a variant ("7b-instruct")
+            let size = tag.split('-').next().unwrap_or_default();
+            assert!(
+                size.ends_with('b') && size[..size.len() - 1].parse::<f32>().is_ok(),
+                "model '{}' has a malformed size tag '{}'", name, size
+            );
+        }
+    }
+
+    #[test]
+    fn test_aliases_are_registered() {
+        for name in ["deepseek-r1:7b", "qwen2-math:7b", "qwen2.5:7b-instruct"] {
+            assert!(get_model(name).is_some(), "alias '{}' should be registered", name);
+        }
+    }
+}
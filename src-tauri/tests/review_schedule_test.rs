@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod tests {
+    use crate::brain::schedule::ReviewSchedule;
+    use crate::sessions::SessionRecord;
+
+    const DAY: i64 = 86_400;
+
+    fn session(id: &str, skill: &str, before: f32, after: f32, timestamp: i64) -> SessionRecord {
+        SessionRecord {
+            session_id: id.to_string(),
+            problem_id: "p1".to_string(),
+            skill: skill.to_string(),
+            user_attempt: String::new(),
+            issues: vec![],
+            eval_summary: "1 evaluations".to_string(),
+            skill_before: before,
+            skill_after: after,
+            difficulty: 0.5,
+            timestamp,
+            correct: None,
+            assessments: vec![],
+            issue_types: vec![],
+            revealed: false,
+        }
+    }
+
+    #[test]
+    fn test_interval_grows_on_success() {
+        let mut schedule = ReviewSchedule::default();
+        schedule.record_outcome("algorithms", true, 0);
+        let first = schedule.items["algorithms"].interval_days;
+        schedule.record_outcome("algorithms", true, DAY);
+        let second = schedule.items["algorithms"].interval_days;
+        schedule.record_outcome("algorithms", true, 7 * DAY);
+        let third = schedule.items["algorithms"].interval_days;
+        assert!(first < second && second < third);
+    }
+
+    #[test]
+    fn test_failure_resets_interval() {
+        let mut schedule = ReviewSchedule::default();
+        for day in 0..4 {
+            schedule.record_outcome("algorithms", true, day * DAY);
+        }
+        schedule.record_outcome("algorithms", false, 10 * DAY);
+        let item = &schedule.items["algorithms"];
+        assert_eq!(item.interval_days, 1.0);
+        assert_eq!(item.repetitions, 0);
+        assert_eq!(item.next_review, 11 * DAY);
+    }
+
+    #[test]
+    fn test_due_skills() {
+        let mut schedule = ReviewSchedule::default();
+        schedule.record_outcome("algorithms", false, 0);
+        schedule.record_outcome("ml_theory", true, 0);
+        schedule.record_outcome("ml_theory", true, DAY);
+        // algorithms is due after 1 day, ml_theory only after 7
+        assert_eq!(schedule.due_skills(2 * DAY), vec!["algorithms".to_string()]);
+        assert!(schedule.due_skills(0).is_empty());
+    }
+
+    #[test]
+    fn test_update_uses_latest_session_once() {
+        let sessions = vec![
+            session("s1", "algorithms", 0.5, 0.6, 0),
+            session("s2", "algorithms", 0.6, 0.5, DAY),
+        ];
+        let mut schedule = ReviewSchedule::default();
+        schedule.update_from_sessions(&sessions);
+        // Latest session was a failure
+        assert_eq!(schedule.items["algorithms"].repetitions, 0);
+        assert_eq!(schedule.items["algorithms"].next_review, 2 * DAY);
+
+        // Re-applying the same sessions doesn't change anything
+        let before = schedule.items["algorithms"].ease;
+        schedule.update_from_sessions(&sessions);
+        assert_eq!(schedule.items["algorithms"].ease, before);
+    }
+
+    #[test]
+    fn test_forget_session_replays_the_rest_of_the_history() {
+        let s1 = session("s1", "algorithms", 0.5, 0.6, 0);
+        let s2 = session("s2", "algorithms", 0.6, 0.5, DAY);
+        let s3 = session("s3", "algorithms", 0.5, 0.6, 2 * DAY);
+        let other = session("s4", "ml_theory", 0.5, 0.6, DAY);
+        let mut schedule = ReviewSchedule::default();
+        for applied in [&s1, &s2, &s3] {
+            schedule.update_from_sessions(&[applied.clone(), other.clone()]);
+        }
+
+        // Without the failed s2, s1 and s3 are two successes in a row
+        let remaining = [s1.clone(), s3.clone(), other.clone()];
+        assert!(schedule.forget_session(&s2, &remaining));
+        let item = &schedule.items["algorithms"];
+        assert_eq!(item.repetitions, 2);
+        assert_eq!(item.interval_days, 6.0);
+        assert_eq!(item.last_session_id.as_deref(), Some("s3"));
+        assert!(schedule.items.contains_key("ml_theory"));
+
+        // Forgetting the only session for a skill drops its item
+        assert!(schedule.forget_session(&other, &[s1, s3]));
+        assert!(!schedule.items.contains_key("ml_theory"));
+        assert!(!schedule.forget_session(&other, &[]));
+    }
+}
@@ -0,0 +1,425 @@
+#[cfg(test)]
+mod tests {
+    use crate::problems::problem::Problem;
+    use crate::problems::selector::{
+        completed_problem_ids, get_problems_by_difficulty, get_problems_by_tag, get_problems_by_topic, normalize_topic,
+        current_target_difficulty, next_target_difficulty, pick_problem, pick_problem_avoiding_recent, pick_problem_from_list,
+        pick_review_problem, target_difficulty,
+    };
+    use crate::skills::store::{load_target_difficulties_in, save_target_difficulties_in};
+    use std::collections::HashMap;
+    use crate::sessions::SessionRecord;
+    use crate::skills::model::SkillVector;
+    use crate::state::app::AppState;
+    use rand::thread_rng;
+
+    const DAY: i64 = 86_400;
+
+    fn problem(id: &str, topic: &str, difficulty: f32) -> Problem {
+        Problem {
+            id: id.to_string(),
+            topic: topic.to_string(),
+            difficulty,
+            statement: format!("Statement for {}", id),
+            solution_sketch: String::new(),
+            tags: vec![],
+        }
+    }
+
+    #[test]
+    fn test_no_immediate_repeat_with_small_problem_set() {
+        let state = AppState::new();
+        let skills = SkillVector::new();
+        let problems = [
+            problem("p1", "algorithms", 0.3),
+            problem("p2", "algorithms", 0.5),
+        ];
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        let mut previous: Option<String> = None;
+        for _ in 0..10 {
+            let recent = state.get_recently_selected_problems();
+            let picked = pick_problem_avoiding_recent(&skills, &refs, &recent, &mut thread_rng())
+                .expect("a problem should always be picked");
+            assert_ne!(previous.as_deref(), Some(picked.id.as_str()));
+            state.record_problem_selected(picked.id.clone());
+            previous = Some(picked.id.clone());
+        }
+    }
+
+    #[test]
+    fn test_prefers_problems_not_recently_shown() {
+        let skills = SkillVector::new();
+        let problems = [
+            problem("p1", "algorithms", 0.3),
+            problem("p2", "ml_theory", 0.5),
+        ];
+        let refs: Vec<&Problem> = problems.iter().collect();
+        let recent = vec!["p1".to_string()];
+
+        let picked = pick_problem_avoiding_recent(&skills, &refs, &recent, &mut thread_rng()).unwrap();
+        assert_eq!(picked.id, "p2");
+    }
+
+    #[test]
+    fn test_repeats_least_recently_shown() {
+        let skills = SkillVector::new();
+        let problems = [
+            problem("p1", "algorithms", 0.3),
+            problem("p2", "algorithms", 0.5),
+            problem("p3", "algorithms", 0.7),
+        ];
+        let refs: Vec<&Problem> = problems.iter().collect();
+        // Most recent first: p2 was shown last, p3 longest ago
+        let recent = vec!["p2".to_string(), "p1".to_string(), "p3".to_string()];
+
+        let picked = pick_problem_avoiding_recent(&skills, &refs, &recent, &mut thread_rng()).unwrap();
+        assert_eq!(picked.id, "p3");
+    }
+
+    #[test]
+    fn test_empty_problem_list() {
+        let skills = SkillVector::new();
+        assert!(pick_problem_avoiding_recent(&skills, &[], &[], &mut thread_rng()).is_none());
+    }
+
+    #[test]
+    fn test_problems_by_difficulty_band_sorted() {
+        let problems = vec![
+            problem("hard", "algorithms", 0.9),
+            problem("mid", "algorithms", 0.5),
+            problem("easy", "algorithms", 0.2),
+            problem("other", "ml_theory", 0.3),
+        ];
+
+        let ids: Vec<String> = get_problems_by_difficulty(&problems, Some("algorithms"), 0.2, 0.5)
+            .into_iter().map(|p| p.id).collect();
+        assert_eq!(ids, vec!["easy", "mid"]);
+
+        // No topic: all topics in the band
+        let ids: Vec<String> = get_problems_by_difficulty(&problems, None, 0.0, 0.5)
+            .into_iter().map(|p| p.id).collect();
+        assert_eq!(ids, vec!["easy", "other", "mid"]);
+    }
+
+    #[test]
+    fn test_problems_by_difficulty_inverted_range_is_empty() {
+        let problems = vec![problem("mid", "algorithms", 0.5)];
+        assert!(get_problems_by_difficulty(&problems, None, 0.8, 0.2).is_empty());
+    }
+
+    #[test]
+    fn test_normalize_topic_variants() {
+        for variant in ["rl_theory", "rl-theory", "RL_theory", " RL Theory ", "rl", "reinforcement-learning"] {
+            assert_eq!(normalize_topic(variant), "rl_theory", "variant {:?}", variant);
+        }
+        assert_eq!(normalize_topic("Machine Learning"), "ml_theory");
+        assert_eq!(normalize_topic("analysis"), "analysis_math");
+        // Unknown topics are only normalized, not remapped
+        assert_eq!(normalize_topic("Graph-Theory"), "graph_theory");
+    }
+
+    #[test]
+    fn test_problems_by_topic_matches_variants() {
+        let problems = vec![
+            problem("a", "rl-theory", 0.5),
+            problem("b", "RL_theory", 0.5),
+            problem("c", "ml_theory", 0.5),
+        ];
+
+        let ids: Vec<String> = get_problems_by_topic(&problems, "rl_theory")
+            .into_iter().map(|p| p.id).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_get_problems_by_tag_is_case_insensitive() {
+        let mut induction = problem("p1", "algorithms", 0.3);
+        induction.tags = vec!["Induction".to_string(), "recursion".to_string()];
+        let mut analysis = problem("p2", "analysis", 0.5);
+        analysis.tags = vec!["epsilon-delta".to_string()];
+        let untagged = problem("p3", "algorithms", 0.4);
+        let problems = vec![induction, analysis, untagged];
+
+        let ids = |tag: &str| -> Vec<String> {
+            get_problems_by_tag(&problems, tag).into_iter().map(|p| p.id).collect()
+        };
+        assert_eq!(ids("induction"), vec!["p1"]);
+        assert_eq!(ids(" EPSILON-DELTA "), vec!["p2"]);
+        assert!(ids("pigeonhole").is_empty());
+        assert!(ids("").is_empty());
+    }
+
+    /// Skills where `algorithms` is the unique weakest, at `value`
+    fn skills_with_weakest_algorithms(value: f32) -> SkillVector {
+        let mut skills = SkillVector::new();
+        for v in skills.skills.values_mut() {
+            *v = 0.95;
+        }
+        skills.skills.insert("algorithms".to_string(), value);
+        skills
+    }
+
+    #[test]
+    fn test_selected_difficulty_follows_target() {
+        let problems: Vec<Problem> = [0.1, 0.3, 0.5, 0.7, 0.9].iter()
+            .enumerate()
+            .map(|(i, d)| problem(&format!("p{}", i), "algorithms", *d))
+            .collect();
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        for (value, expected) in [(0.1, 0.9), (0.3, 0.7), (0.5, 0.5), (0.7, 0.3), (0.9, 0.3)] {
+            assert!((target_difficulty(value) - expected).abs() < 1e-6);
+            let skills = skills_with_weakest_algorithms(value);
+            let picked = pick_problem_from_list(&skills, &refs, &mut thread_rng()).unwrap();
+            assert!((picked.difficulty - expected).abs() < 1e-6);
+
+            let owned = pick_problem(&skills, &problems, &mut thread_rng()).unwrap();
+            assert_eq!(owned.difficulty, picked.difficulty);
+        }
+    }
+
+    #[test]
+    fn test_closest_difficulty_ties_are_both_reachable() {
+        let problems = [
+            problem("low", "algorithms", 0.4),
+            problem("high", "algorithms", 0.6),
+        ];
+        let refs: Vec<&Problem> = problems.iter().collect();
+        let skills = skills_with_weakest_algorithms(0.5);
+
+        let mut seen = std::collections::HashSet::new();
+        for _ in 0..100 {
+            seen.insert(pick_problem_from_list(&skills, &refs, &mut thread_rng()).unwrap().id.clone());
+        }
+        assert_eq!(seen.len(), 2);
+    }
+
+    #[test]
+    fn test_pick_from_empty_list() {
+        let skills = skills_with_weakest_algorithms(0.5);
+        assert!(pick_problem_from_list(&skills, &[], &mut thread_rng()).is_none());
+        assert!(pick_problem(&skills, &[], &mut thread_rng()).is_none());
+    }
+
+    #[test]
+    fn test_pick_without_problems_for_weakest_skill() {
+        // No algorithms problems: fall back to the closest difficulty across all topics
+        let problems = vec![
+            problem("ml", "ml_theory", 0.9),
+            problem("rl", "rl_theory", 0.35),
+        ];
+        let refs: Vec<&Problem> = problems.iter().collect();
+        let skills = skills_with_weakest_algorithms(0.7);
+
+        assert_eq!(pick_problem_from_list(&skills, &refs, &mut thread_rng()).unwrap().id, "rl");
+        assert_eq!(pick_problem(&skills, &problems, &mut thread_rng()).unwrap().id, "rl");
+    }
+
+    #[test]
+    fn test_pick_prefers_weakest_skill() {
+        // The ml_theory problem is a closer difficulty match, but algorithms is weakest
+        let problems = [
+            problem("ml", "ml_theory", 0.3),
+            problem("algo", "algorithms", 0.8),
+        ];
+        let refs: Vec<&Problem> = problems.iter().collect();
+        let skills = skills_with_weakest_algorithms(0.3);
+
+        assert_eq!(pick_problem_from_list(&skills, &refs, &mut thread_rng()).unwrap().id, "algo");
+    }
+
+    fn attempt(problem_id: &str, timestamp: i64) -> SessionRecord {
+        SessionRecord {
+            session_id: format!("sess_{}_{}", problem_id, timestamp),
+            problem_id: problem_id.to_string(),
+            skill: "algorithms".to_string(),
+            user_attempt: String::new(),
+            issues: vec![],
+            eval_summary: String::new(),
+            skill_before: 0.5,
+            skill_after: 0.5,
+            difficulty: 0.5,
+            timestamp,
+            correct: Some(true),
+            assessments: vec![],
+            issue_types: vec![],
+            revealed: false,
+        }
+    }
+
+    #[test]
+    fn test_problems_completed_before_window_are_selectable_again() {
+        let now = 100 * DAY;
+        let sessions = vec![
+            attempt("old", now - 31 * DAY),
+            attempt("recent", now - 2 * DAY),
+            attempt("edge", now - 30 * DAY),
+        ];
+        let completed = completed_problem_ids(&sessions, now, 30);
+        assert!(!completed.contains("old"));
+        assert!(completed.contains("recent"));
+        assert!(completed.contains("edge"));
+
+        let problems = [
+            problem("old", "algorithms", 0.5),
+            problem("recent", "algorithms", 0.5),
+        ];
+        let eligible: Vec<&Problem> = problems.iter()
+            .filter(|p| !completed.contains(&p.id))
+            .collect();
+        let skills = skills_with_weakest_algorithms(0.5);
+        assert_eq!(pick_problem_from_list(&skills, &eligible, &mut thread_rng()).unwrap().id, "old");
+
+        // A zero-day window excludes only attempts made right now
+        assert!(completed_problem_ids(&sessions, now, 0).is_empty());
+    }
+
+    fn failure(problem_id: &str, timestamp: i64, incorrect: usize) -> SessionRecord {
+        let mut record = attempt(problem_id, timestamp);
+        record.correct = Some(false);
+        record.assessments = vec!["incorrect".to_string(); incorrect];
+        record
+    }
+
+    fn review_counts(sessions: &[SessionRecord], problems: &[Problem]) -> std::collections::HashMap<String, usize> {
+        let mut counts = std::collections::HashMap::new();
+        for _ in 0..3000 {
+            let picked = pick_review_problem(sessions, "algorithms", problems, &mut thread_rng()).unwrap();
+            *counts.entry(picked.id.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn test_review_selection_skews_to_recent_failures() {
+        let problems = [
+            problem("old", "algorithms", 0.5),
+            problem("mid", "algorithms", 0.5),
+            problem("new", "algorithms", 0.5),
+            problem("solved", "algorithms", 0.5),
+        ];
+        let mut sessions = vec![
+            failure("old", 100, 0),
+            failure("mid", 200, 0),
+            failure("new", 300, 0),
+            attempt("solved", 400),
+        ];
+        // Another skill's failure is never picked
+        let mut other = failure("old", 500, 3);
+        other.skill = "rl_theory".to_string();
+        sessions.push(other);
+
+        // Expected shares are 6/11, 3/11 and 2/11
+        let counts = review_counts(&sessions, &problems);
+        assert!(counts["new"] > counts["mid"]);
+        assert!(counts["mid"] > counts["old"]);
+        assert!(counts["new"] > 1400);
+        assert!(!counts.contains_key("solved"));
+
+        // Three incorrect answers outweigh the recency of the newest failure
+        sessions[0] = failure("old", 100, 3);
+        let counts = review_counts(&sessions, &problems);
+        assert!(counts["old"] > counts["new"]);
+    }
+
+    #[test]
+    fn test_review_selection_without_failures() {
+        let problems = [problem("p1", "algorithms", 0.5)];
+        assert!(pick_review_problem(&[attempt("p1", 100)], "algorithms", &problems, &mut thread_rng()).is_none());
+        // Failures on problems that no longer exist are ignored
+        assert!(pick_review_problem(&[failure("gone", 100, 1)], "algorithms", &problems, &mut thread_rng()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_target_difficulty_carries_across_calls() {
+        let path = std::env::temp_dir()
+            .join(format!("zos_target_difficulty_{}", std::process::id()))
+            .join("target_difficulty.json");
+        let mut targets = load_target_difficulties_in(&path).await;
+        assert!(targets.is_empty());
+
+        // First call starts from the default base
+        let (base, annealed) = next_target_difficulty(&mut targets, "algorithms", 0.5, 0.9);
+        assert_eq!(base, 0.5);
+        assert!((annealed - 0.6).abs() < 1e-6);
+        save_target_difficulties_in(&path, &targets).await.unwrap();
+
+        // The next call builds on the stored value, not the default
+        let mut targets = load_target_difficulties_in(&path).await;
+        let (base, annealed) = next_target_difficulty(&mut targets, "algorithms", 0.5, 0.9);
+        assert!((base - 0.6).abs() < 1e-6);
+        assert!((annealed - 0.7).abs() < 1e-6);
+
+        // Other skills are independent; the upper bound still holds
+        let (base, _) = next_target_difficulty(&mut targets, "rl_theory", 0.3, 0.5);
+        assert_eq!(base, 0.3);
+        let mut capped = HashMap::from([("algorithms".to_string(), 0.95)]);
+        assert_eq!(next_target_difficulty(&mut capped, "algorithms", 0.5, 1.0).1, 1.0);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_reading_the_target_does_not_move_it() {
+        let mut targets = HashMap::new();
+        // Recommending repeatedly before any attempt keeps the default
+        for _ in 0..3 {
+            assert_eq!(current_target_difficulty(&targets, "algorithms", 0.5), 0.5);
+        }
+
+        // Only a recorded attempt moves it on
+        next_target_difficulty(&mut targets, "algorithms", 0.5, 0.9);
+        for _ in 0..3 {
+            assert!((current_target_difficulty(&targets, "algorithms", 0.5) - 0.6).abs() < 1e-6);
+        }
+    }
+
+    fn tied_skills() -> SkillVector {
+        let mut skills = SkillVector::new();
+        skills.skills.clear();
+        for name in ["algorithms", "probability", "rl_theory", "ml_theory"] {
+            skills.skills.insert(name.to_string(), 0.3);
+        }
+        skills.skills.insert("logical_reasoning".to_string(), 0.9);
+        skills
+    }
+
+    #[test]
+    fn test_same_seed_picks_same_tied_skill() {
+        use crate::config::models::seeded_selection_rng;
+
+        // Separately built vectors iterate their maps in different orders
+        let (a, b) = (tied_skills(), tied_skills());
+        for seed in 0..32 {
+            let first = a.get_weakest_skill_excluding_with(&[], &mut seeded_selection_rng(Some(seed))).unwrap();
+            let second = b.get_weakest_skill_excluding_with(&[], &mut seeded_selection_rng(Some(seed))).unwrap();
+            assert_eq!(first.0, second.0);
+
+            let order_a = a.weakest_n_with(5, &mut seeded_selection_rng(Some(seed)));
+            let order_b = b.weakest_n_with(5, &mut seeded_selection_rng(Some(seed)));
+            assert_eq!(order_a, order_b);
+            assert_eq!(order_a[4].0, "logical_reasoning");
+        }
+    }
+
+    #[test]
+    fn test_shared_selection_rng_moves_on_between_picks() {
+        use crate::config::models::seeded_selection_rng;
+
+        let skills = tied_skills();
+        let run = |state: &AppState| -> Vec<String> {
+            (0..16)
+                .map(|_| state.with_selection_rng(|rng| skills.get_weakest_skill_excluding_with(&[], rng)).unwrap().0)
+                .collect()
+        };
+        let (a, b) = (AppState::new(), AppState::new());
+        *a.selection_rng.lock() = seeded_selection_rng(Some(7));
+        *b.selection_rng.lock() = seeded_selection_rng(Some(7));
+        let picks = run(&a);
+
+        // A seeded run repeats as a whole, but picks within it still vary
+        assert_eq!(picks, run(&b));
+        let distinct: std::collections::HashSet<&String> = picks.iter().collect();
+        assert!(distinct.len() > 1, "{:?}", picks);
+    }
+}
@@ -0,0 +1,235 @@
+#[cfg(test)]
+mod tests {
+    use crate::analytics::{
+        accuracy_by_week, current_streak, issue_type_counts, render_weekly_report, skill_details, summarize_sessions,
+        week_activity, AnalyticsPayload,
+    };
+    use crate::skills::model::SkillVector;
+    use std::collections::HashMap;
+    use crate::sessions::SessionRecord;
+    use chrono::{FixedOffset, NaiveDate, TimeZone};
+
+    fn ts(tz: &FixedOffset, y: i32, m: u32, d: u32, h: u32) -> i64 {
+        tz.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap().timestamp()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn session(skill: &str, timestamp: i64, eval_summary: &str, difficulty: f32) -> SessionRecord {
+        SessionRecord {
+            session_id: format!("sess_{}", timestamp),
+            problem_id: "p1".to_string(),
+            skill: skill.to_string(),
+            user_attempt: String::new(),
+            issues: vec![],
+            eval_summary: eval_summary.to_string(),
+            skill_before: 0.5,
+            skill_after: 0.5,
+            difficulty,
+            timestamp,
+            correct: None,
+            assessments: vec![],
+            issue_types: vec![],
+            revealed: false,
+        }
+    }
+
+    #[test]
+    fn test_streak_consecutive_days() {
+        let tz = FixedOffset::east_opt(0).unwrap();
+        let stamps = vec![
+            ts(&tz, 2024, 3, 8, 9),
+            ts(&tz, 2024, 3, 9, 22),
+            ts(&tz, 2024, 3, 10, 1),
+            ts(&tz, 2024, 3, 10, 8),
+        ];
+        assert_eq!(current_streak(&stamps, date(2024, 3, 10), &tz), 3);
+        // Nothing today yet: streak through yesterday still counts
+        assert_eq!(current_streak(&stamps, date(2024, 3, 11), &tz), 3);
+        // A full missed day breaks it
+        assert_eq!(current_streak(&stamps, date(2024, 3, 12), &tz), 0);
+    }
+
+    #[test]
+    fn test_streak_gap_resets() {
+        let tz = FixedOffset::east_opt(0).unwrap();
+        let stamps = vec![
+            ts(&tz, 2024, 3, 5, 12),
+            ts(&tz, 2024, 3, 6, 12),
+            ts(&tz, 2024, 3, 9, 12),
+            ts(&tz, 2024, 3, 10, 12),
+        ];
+        assert_eq!(current_streak(&stamps, date(2024, 3, 10), &tz), 2);
+        assert_eq!(current_streak(&[], date(2024, 3, 10), &tz), 0);
+    }
+
+    #[test]
+    fn test_streak_buckets_by_local_day() {
+        // 23:30 and 00:30 local are different days even though they're an hour apart
+        let tz = FixedOffset::east_opt(-5 * 3600).unwrap();
+        let late = ts(&tz, 2024, 3, 9, 23) + 30 * 60;
+        let early = ts(&tz, 2024, 3, 10, 0) + 30 * 60;
+        assert_eq!(current_streak(&[late, early], date(2024, 3, 10), &tz), 2);
+
+        // In UTC both fall on March 10th
+        let utc = FixedOffset::east_opt(0).unwrap();
+        assert_eq!(current_streak(&[late, early], date(2024, 3, 10), &utc), 1);
+    }
+
+    #[test]
+    fn test_summary_accuracy_and_weekly_difficulty() {
+        let tz = FixedOffset::east_opt(0).unwrap();
+        let now = ts(&tz, 2024, 3, 10, 12);
+        let sessions = vec![
+            session("algorithms", ts(&tz, 2024, 2, 1, 12), "Perfect solution", 0.9),
+            session("algorithms", ts(&tz, 2024, 3, 9, 12), "incorrect step 2", 0.4),
+            session("ml_theory", ts(&tz, 2024, 3, 10, 10), "2 evaluations", 0.6),
+        ];
+
+        let summary = summarize_sessions(&sessions, now, &tz);
+        assert_eq!(summary.total_sessions, 3);
+        assert_eq!(summary.current_streak_days, 2);
+        assert!((summary.per_skill_accuracy["algorithms"] - 0.5).abs() < 1e-6);
+        assert!((summary.per_skill_accuracy["ml_theory"] - 1.0).abs() < 1e-6);
+        assert!((summary.avg_difficulty_this_week - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_overall_accuracy_by_iso_week() {
+        let tz = FixedOffset::east_opt(0).unwrap();
+        let graded = |day: u32, correct: bool| {
+            let mut s = session("algorithms", ts(&tz, 2024, 3, day, 12), "", 0.5);
+            s.correct = Some(correct);
+            s
+        };
+        // March 4th and 11th 2024 are Mondays; the 10th is the Sunday before
+        let sessions = vec![
+            graded(4, true),
+            graded(7, false),
+            graded(10, true),
+            graded(11, true),
+            graded(12, false),
+        ];
+
+        let weeks = accuracy_by_week(&sessions, &tz);
+        assert_eq!(weeks.len(), 2);
+        assert_eq!(weeks[0].0, ts(&tz, 2024, 3, 4, 0));
+        assert!((weeks[0].1 - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(weeks[1].0, ts(&tz, 2024, 3, 11, 0));
+        assert!((weeks[1].1 - 0.5).abs() < 1e-6);
+        assert!(accuracy_by_week(&[], &tz).is_empty());
+    }
+
+    #[test]
+    fn test_issue_type_counts_across_sessions() {
+        let tz = FixedOffset::east_opt(0).unwrap();
+        let with_issues = |day: u32, types: &[&str]| {
+            let mut s = session("algorithms", ts(&tz, 2024, 3, day, 12), "", 0.5);
+            s.issue_types = types.iter().map(|t| t.to_string()).collect();
+            s
+        };
+        let mut legacy = session("algorithms", ts(&tz, 2024, 3, 1, 12), "", 0.5);
+        legacy.issues = vec!["s1: no justification".to_string()];
+        let sessions = vec![
+            legacy,
+            with_issues(2, &["missing_justification", "logical_gap"]),
+            with_issues(3, &["missing_justification"]),
+            with_issues(4, &[]),
+        ];
+
+        let counts = issue_type_counts(&sessions);
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts["missing_justification"], 2);
+        assert_eq!(counts["logical_gap"], 1);
+    }
+
+    #[test]
+    fn test_weekly_report_snapshot() {
+        let tz = FixedOffset::east_opt(0).unwrap();
+        let now = ts(&tz, 2024, 3, 10, 20);
+        let attempt = |problem: &str, skill: &str, day: u32, before: f32, after: f32, correct: bool| {
+            let mut s = session(skill, ts(&tz, 2024, 3, day, 12), "", 0.5);
+            s.problem_id = problem.to_string();
+            s.skill_before = before;
+            s.skill_after = after;
+            s.correct = Some(correct);
+            s
+        };
+        let sessions = vec![
+            attempt("old", "algorithms", 1, 0.2, 0.9, true),
+            attempt("p1", "algorithms", 5, 0.50, 0.48, false),
+            attempt("p1", "algorithms", 6, 0.48, 0.60, true),
+            attempt("p2", "ml_theory", 8, 0.40, 0.35, false),
+            attempt("p3", "probability", 9, 0.30, 0.33, true),
+        ];
+        let trends: HashMap<String, f32> = [
+            ("algorithms".to_string(), 0.12),
+            ("probability".to_string(), 0.03),
+            ("ml_theory".to_string(), -0.05),
+        ].into_iter().collect();
+        let analytics = AnalyticsPayload {
+            weekly_trends: trends,
+            this_week: week_activity(&sessions, now),
+            ..Default::default()
+        };
+
+        let expected = "\
+# zOS weekly report
+
+Week ending 2024-03-10
+
+- Sessions: 4
+- Accuracy: 50% (2/4)
+- Most improved: algorithms (+0.120/day)
+- Most declined: ml_theory (-0.050/day)
+
+## Problems attempted
+
+- p1 (algorithms): correct
+- p2 (ml_theory): incorrect
+- p3 (probability): correct
+";
+        assert_eq!(render_weekly_report(&analytics, now, &tz), expected);
+
+        let quiet = AnalyticsPayload {
+            this_week: week_activity(&sessions[..1], now),
+            ..Default::default()
+        };
+        let empty = render_weekly_report(&quiet, now, &tz);
+        assert!(empty.contains("No sessions this week"));
+        assert!(!empty.contains("## Problems attempted"));
+    }
+
+    #[test]
+    fn test_skill_details_cover_each_skill_once() {
+        let tz = FixedOffset::east_opt(0).unwrap();
+        let mut skills = SkillVector::new();
+        skills.skills.insert("graph_theory".to_string(), 0.2);
+        let sessions = vec![
+            session("algorithms", ts(&tz, 2024, 3, 8, 12), "", 0.5),
+            session("algorithms", ts(&tz, 2024, 3, 9, 12), "", 0.5),
+            // Sessions in skills no longer tracked don't add rows
+            session("retired_skill", ts(&tz, 2024, 3, 9, 12), "", 0.5),
+        ];
+        let trends: HashMap<String, f32> = [("algorithms".to_string(), 0.02)].into_iter().collect();
+
+        let details = skill_details(&skills, &sessions, &trends);
+        let mut names: Vec<&str> = details.iter().map(|d| d.name.as_str()).collect();
+        names.dedup();
+        assert_eq!(names.len(), skills.skills.len());
+        assert!(skills.skills.keys().all(|k| names.contains(&k.as_str())));
+
+        let algorithms = details.iter().find(|d| d.name == "algorithms").unwrap();
+        assert_eq!(algorithms.label, "Algorithms & Data Structures");
+        assert_eq!(algorithms.session_count, 2);
+        assert_eq!(algorithms.last_practiced, Some(ts(&tz, 2024, 3, 9, 12)));
+        assert!((algorithms.weekly_trend - 0.02).abs() < 1e-6);
+
+        let graph = details.iter().find(|d| d.name == "graph_theory").unwrap();
+        assert_eq!(graph.label, "Graph Theory");
+        assert_eq!(graph.last_practiced, None);
+        assert_eq!(graph.session_count, 0);
+    }
+}
@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod tests {
+    use crate::analytics::domain_scores;
+    use crate::config::skills::{SkillDefaults, SkillDomains, SkillWeights};
+    use crate::pipelines::proof::{ProofIssue, QuestionEvaluation};
+    use crate::sessions::SessionRecord;
+    use crate::skills::model::SkillVector;
+
+    fn issue(issue_type: &str) -> ProofIssue {
+        ProofIssue {
+            step_id: "1".to_string(),
+            issue_type: issue_type.to_string(),
+            explanation: String::new(),
+        }
+    }
+
+    fn session(timestamp: i64, issue_types: &[&str], assessments: &[&str], correct: Option<bool>) -> SessionRecord {
+        SessionRecord {
+            session_id: format!("sess_{}", timestamp),
+            problem_id: "p1".to_string(),
+            skill: "algorithms".to_string(),
+            user_attempt: String::new(),
+            issues: issue_types.iter().map(|t| format!("1: {}", t)).collect(),
+            eval_summary: String::new(),
+            skill_before: 0.5,
+            skill_after: 0.5,
+            difficulty: 0.5,
+            timestamp,
+            correct,
+            assessments: assessments.iter().map(|a| a.to_string()).collect(),
+            issue_types: issue_types.iter().map(|t| t.to_string()).collect(),
+            revealed: false,
+        }
+    }
+
+    fn value(skills: &SkillVector, skill: &str) -> f32 {
+        *skills.skills.get(skill).unwrap()
+    }
+
+    #[test]
+    fn test_default_weights_match_original_deltas() {
+        let mut skills = SkillVector::new();
+        skills.update_from_issues_with(&[issue("math_gaps")], &SkillWeights::default());
+        assert!((value(&skills, "analysis_math") - 0.47).abs() < 1e-6);
+        assert!((value(&skills, "putnam_competition") - 0.48).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_custom_weights_change_update_magnitude() {
+        let weights = SkillWeights {
+            major_issue_penalty: 0.1,
+            correct_answer_reward: 0.2,
+            ..SkillWeights::default()
+        };
+
+        let mut skills = SkillVector::new();
+        skills.update_from_issues_with(&[issue("incorrect_logic")], &weights);
+        assert!((value(&skills, "logical_reasoning") - 0.4).abs() < 1e-6);
+
+        let evaluation = [QuestionEvaluation {
+            question: String::new(),
+            user_answer: String::new(),
+            assessment: "correct".to_string(),
+            comment: String::new(),
+        }];
+        skills.update_from_evaluation_with(&evaluation, &weights);
+        assert!((value(&skills, "logical_reasoning") - 0.6).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_partial_weights_toml_keeps_defaults() {
+        let weights: SkillWeights = toml::from_str("major_issue_penalty = 0.05").unwrap();
+        assert_eq!(weights.major_issue_penalty, 0.05);
+        assert_eq!(weights.minor_issue_penalty, 0.02);
+        assert_eq!(weights.partial_answer_reward, 0.005);
+    }
+
+    #[test]
+    fn test_replaying_sessions_rebuilds_skills() {
+        let perfect = session(100, &[], &[], Some(true));
+        let step2 = session(200, &["incorrect_logic", "missing_justification"], &["correct", "partially_correct", "incorrect"], Some(false));
+        let revealed = SessionRecord::revealed("p1".to_string(), "algorithms".to_string(), 0.5, 0.5);
+        // Saved before issue types were stored: nothing to replay
+        let mut legacy = session(400, &[], &[], None);
+        legacy.issues = vec!["s1: gap in the induction step".to_string()];
+
+        // Input order doesn't matter; sessions are replayed by timestamp
+        let sessions = [legacy, step2, revealed, perfect];
+        let skills = SkillVector::from_sessions(&sessions, &SkillWeights::default());
+        assert!((value(&skills, "algorithms") - 0.52).abs() < 1e-6);
+        assert!((value(&skills, "logical_reasoning") - 0.49).abs() < 1e-6);
+        assert!((value(&skills, "proof_strategy") - 0.495).abs() < 1e-6);
+        assert!((value(&skills, "analysis_math") - 0.5).abs() < 1e-6);
+
+        // Replaying with different weights gives a different, consistent vector
+        let weights = SkillWeights { major_issue_penalty: 0.1, ..SkillWeights::default() };
+        let reweighted = SkillVector::from_sessions(&sessions, &weights);
+        assert!((value(&reweighted, "logical_reasoning") - 0.42).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_domain_score_is_mean_of_members() {
+        let mut skills = SkillVector::new();
+        skills.skills.insert("analysis_math".to_string(), 0.2);
+        skills.skills.insert("putnam_competition".to_string(), 0.6);
+        skills.skills.insert("proof_strategy".to_string(), 0.9);
+
+        let scores = domain_scores(&skills, &SkillDomains::default());
+        assert_eq!(scores.len(), 4);
+        assert!((scores["math"] - 0.4).abs() < 1e-6);
+        assert!((scores["reasoning"] - (0.9 + 0.5) / 2.0).abs() < 1e-6);
+
+        // Edited domains: unknown members are skipped, empty domains dropped
+        let domains: SkillDomains = toml::from_str(
+            "[domains]\ntheory = [\"analysis_math\", \"no_such_skill\"]\nempty = [\"no_such_skill\"]"
+        ).unwrap();
+        let scores = domain_scores(&skills, &domains);
+        assert_eq!(scores.len(), 1);
+        assert!((scores["theory"] - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_configured_initial_skill_values() {
+        let defaults: SkillDefaults = toml::from_str(
+            "default_value = 0.7\n[overrides]\nalgorithms = 0.9\nrl_theory = 1.5\nnot_a_skill = 0.1"
+        ).unwrap();
+        let skills = SkillVector::with_defaults(&defaults);
+
+        assert_eq!(skills.skills.len(), SkillVector::new().skills.len());
+        assert!(!skills.skills.contains_key("not_a_skill"));
+        assert!((value(&skills, "algorithms") - 0.9).abs() < 1e-6);
+        // Clamped to [0, 1]
+        assert_eq!(value(&skills, "rl_theory"), 1.0);
+        // No override: the global default
+        assert!((value(&skills, "ml_theory") - 0.7).abs() < 1e-6);
+        assert!((defaults.initial_value("unknown_skill") - 0.7).abs() < 1e-6);
+
+        let unconfigured: SkillDefaults = toml::from_str("").unwrap();
+        assert_eq!(unconfigured.initial_value("algorithms"), 0.5);
+    }
+}
@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+    use crate::problems::generator::hash_statement;
+    use crate::problems::problem::Problem;
+    use crate::problems::transfer::{export_problems_to, import_problems_into};
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("zos_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn problem(id: &str, statement: &str, difficulty: f32) -> Problem {
+        Problem {
+            id: id.to_string(),
+            topic: "algorithms".to_string(),
+            difficulty,
+            statement: statement.to_string(),
+            solution_sketch: String::new(),
+            tags: vec![],
+        }
+    }
+
+    #[test]
+    fn test_import_validates_and_skips_duplicates() {
+        let dir = scratch_dir("import");
+        let mut existing: HashSet<String> = HashSet::from([hash_statement("Already known")]);
+        let batch = vec![
+            problem("a", "Prove A", 0.4),
+            problem("b", "Already known", 0.4),
+            problem("c", "Prove A", 0.6),
+            problem("d", "   ", 0.5),
+            problem("e", "Prove E", 5.0),
+            problem("", "Prove F", 0.2),
+            // Below MIN_DIFFICULTY, which loading would silently raise
+            problem("g", "Prove G", 0.05),
+        ];
+
+        let summary = import_problems_into(batch, &mut existing, &dir).unwrap();
+        assert_eq!(summary.imported, 2);
+        assert_eq!(summary.skipped, 2);
+        assert_eq!(summary.rejected, 3);
+        assert!(summary.errors.iter().any(|e| e.starts_with("e: difficulty")));
+        assert!(summary.errors.iter().any(|e| e.starts_with("g: difficulty")));
+
+        let written = std::fs::read_dir(&dir).unwrap().count();
+        assert_eq!(written, 2);
+        assert!(dir.join("a.json").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_export_import_round_trip_keeps_structured_sketch() {
+        let dir = scratch_dir("round_trip");
+        // Sketch authored in the structured format, as a hand-written problem file might be
+        let structured: Problem = serde_json::from_str(
+            r#"{"id": "p1", "topic": "algorithms", "difficulty": 0.5, "statement": "Prove P",
+                "solution_sketch": [{"step1": "base case"}, {"step2": "inductive step"}]}"#
+        ).unwrap();
+        let mut autogen = problem("autogen_1_algorithms", "Prove Q", 0.7);
+        autogen.tags = vec!["induction".to_string()];
+        let problems = vec![structured, autogen];
+
+        let export_path = dir.join("export.json");
+        assert_eq!(export_problems_to(&problems, &export_path).unwrap(), 2);
+
+        let exported: Vec<Problem> =
+            serde_json::from_str(&std::fs::read_to_string(&export_path).unwrap()).unwrap();
+        let import_dir = dir.join("problems");
+        let summary = import_problems_into(exported, &mut HashSet::new(), &import_dir).unwrap();
+        assert_eq!(summary.imported, 2);
+
+        let reloaded: Problem =
+            serde_json::from_str(&std::fs::read_to_string(import_dir.join("p1.json")).unwrap()).unwrap();
+        assert_eq!(reloaded.solution_sketch, "step1: base case\nstep2: inductive step");
+        assert_eq!(reloaded.solution_sketch, problems[0].solution_sketch);
+        let reloaded: Problem = serde_json::from_str(
+            &std::fs::read_to_string(import_dir.join("autogen_1_algorithms.json")).unwrap()
+        ).unwrap();
+        assert_eq!(reloaded.tags, vec!["induction"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
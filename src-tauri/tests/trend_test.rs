@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod tests {
+    use crate::brain::{trend_slope, REVIEW_TREND_THRESHOLD};
+
+    const DAY: i64 = 86_400;
+
+    #[test]
+    fn test_trend_slope_needs_two_points() {
+        assert_eq!(trend_slope(&[]), 0.0);
+        assert_eq!(trend_slope(&[(0, 0.5)]), 0.0);
+    }
+
+    #[test]
+    fn test_trend_slope_linear_series() {
+        let points: Vec<(i64, f32)> = (0..5)
+            .map(|d| (d * DAY, 0.5 + 0.02 * d as f32))
+            .collect();
+        assert!((trend_slope(&points) - 0.02).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_trend_slope_same_timestamp() {
+        assert_eq!(trend_slope(&[(100, 0.4), (100, 0.6)]), 0.0);
+    }
+
+    #[test]
+    fn test_noisy_rising_series_not_flagged() {
+        // Steady improvement with one bad final session
+        let values = [0.50, 0.55, 0.58, 0.62, 0.66, 0.70, 0.45];
+        let points: Vec<(i64, f32)> = values.iter()
+            .enumerate()
+            .map(|(d, v)| (d as i64 * DAY, *v))
+            .collect();
+
+        // The old first-vs-last delta would have flagged this as declining
+        let naive_delta = values[values.len() - 1] - values[0];
+        assert!(naive_delta < REVIEW_TREND_THRESHOLD);
+
+        let slope = trend_slope(&points);
+        assert!(slope > 0.0, "slope should be positive, got {}", slope);
+        assert!(slope >= REVIEW_TREND_THRESHOLD);
+    }
+
+    #[test]
+    fn test_declining_series_flagged() {
+        let values = [0.70, 0.62, 0.66, 0.55, 0.50];
+        let points: Vec<(i64, f32)> = values.iter()
+            .enumerate()
+            .map(|(d, v)| (d as i64 * DAY, *v))
+            .collect();
+        assert!(trend_slope(&points) < REVIEW_TREND_THRESHOLD);
+    }
+}
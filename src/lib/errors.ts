@@ -0,0 +1,33 @@
+// Structured error returned by commands that use ZosError on the backend
+export type ZosError = {
+  message: string;
+  stage: string;
+  model: string | null;
+  retry_succeeded: boolean;
+  context: string | null;
+  source: string | null;
+  retryable: boolean;
+};
+
+export function formatError(err: unknown): string {
+  if (err && typeof err === "object" && "stage" in err && "message" in err) {
+    const e = err as ZosError;
+    if (e.stage === "ollama_unreachable") {
+      return "Ollama is not running. Start it with `ollama serve` and try again.";
+    }
+    if (e.stage === "cancelled") {
+      return "Analysis cancelled.";
+    }
+    if (e.stage === "model_availability") {
+      return `${e.message}. Make sure Ollama is running and the model is pulled.`;
+    }
+    if (e.stage === "json_parse" || e.stage === "json_extract") {
+      return `The model returned a malformed response. Please try again. (${e.message})`;
+    }
+    if (e.stage === "retry_exhausted") {
+      return `All models failed to produce a usable answer. Please try again. (${e.message})`;
+    }
+    return e.message;
+  }
+  return String(err);
+}